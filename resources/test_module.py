@@ -1,5 +1,9 @@
 """Test module for python_analyzer tests."""
 
+import warnings
+
+from typing_extensions import deprecated
+
 
 def simple_function():
     """A simple function with no parameters."""
@@ -64,3 +68,49 @@ class ComplexClass:
     def method(self):
         """A method."""
         pass
+
+
+@deprecated("Use function_with_return instead")
+def deprecated_function(x: int):
+    """A deprecated function."""
+    return x
+
+
+def warned_function():
+    """A function deprecated at runtime."""
+    warnings.warn("warned_function is deprecated", DeprecationWarning, stacklevel=2)
+
+
+def future_warned_function():
+    """A function deprecated for end users."""
+    import warnings
+
+    warnings.warn("future_warned_function will be removed", category=FutureWarning)
+
+
+def late_warned_function(x: int):
+    """A function that only warns about some inputs."""
+    if x < 0:
+        x = -x
+    warnings.warn("negative inputs are deprecated", DeprecationWarning)
+    return x
+
+
+def pending_warned_function():
+    """A function that will be deprecated later."""
+    warnings.warn("pending_warned_function will be deprecated", PendingDeprecationWarning)
+
+
+def function_with_deprecated_param(value: int, old_value: int | None = None):
+    """Function with a deprecated parameter."""
+    if old_value is not None:
+        warnings.warn("old_value is deprecated, use value", DeprecationWarning)
+    return value
+
+
+@warnings.deprecated("Use ClassWithInit instead")
+class DeprecatedClass:
+    """A deprecated class."""
+
+    def __init__(self, name: str):
+        self.name = name
@@ -1,5 +1,7 @@
 """Test package for module resolution."""
 
+from .submodule import SubmoduleClass
+
 
 def package_function():
     """A function in a package."""
@@ -0,0 +1,122 @@
+"""Test module for classifying _target_ symbols."""
+
+from abc import ABC, ABCMeta, abstractmethod
+from dataclasses import dataclass, field
+from subprocess import run as run_command
+from typing import Callable, Protocol, TypedDict
+
+from typing_extensions import Required
+
+from test_package import submodule
+from test_package.submodule import MissingClass as BrokenReexport
+from test_package.submodule import SubmoduleClass as ReexportedClass
+
+DEFAULT_LR = 0.01
+LAYERS: list[int] = [64, 128]
+ModelAlias = ReexportedClass
+
+
+class BaseModel(ABC):
+    """An abstract base class."""
+
+    @abstractmethod
+    def forward(self, x):
+        """Run the model."""
+
+    @abstractmethod
+    def loss(self, x):
+        """Compute the loss."""
+
+
+class PartialModel(BaseModel):
+    """Implements only some abstract methods."""
+
+    def forward(self, x):
+        return x
+
+
+class ConcreteModel(PartialModel):
+    """Implements every abstract method."""
+
+    def loss(self, x):
+        return 0
+
+
+class MetaBase(metaclass=ABCMeta):
+    """Abstract through its metaclass."""
+
+    @abstractmethod
+    def run(self):
+        """Run."""
+
+
+class Encoder(Protocol):
+    """A structural protocol."""
+
+    def encode(self, x): ...
+
+
+class NotEnforced:
+    """Abstract methods without ABCMeta are not enforced."""
+
+    @abstractmethod
+    def run(self):
+        """Run."""
+
+
+class Optimizer:
+    """A base optimizer."""
+
+
+class Adam(Optimizer):
+    """An optimizer."""
+
+    def __init__(self, lr: float = 0.001):
+        self.lr = lr
+
+
+def make_optimizer() -> Adam:
+    """Build an optimizer."""
+    return Adam()
+
+
+def schedule(make: Callable[..., Optimizer]):
+    """Takes a factory."""
+    return make
+
+
+class Trainer:
+    """Takes nested targets."""
+
+    def __init__(self, optimizer: Optimizer, encoder: Encoder | None = None):
+        self.optimizer = optimizer
+        self.encoder = encoder
+
+
+@dataclass
+class OptimConfig:
+    """A structured optimizer config."""
+
+    lr: float
+    momentum: float = 0.9
+    betas: list[float] = field(default_factory=list)
+    steps: int = field(init=False, default=0)
+
+
+@dataclass
+class SchedulerConfig(OptimConfig):
+    """A structured config inheriting fields."""
+
+    warmup: int = 0
+
+
+class DataOptions(TypedDict, total=False):
+    """A TypedDict with optional keys."""
+
+    batch_size: int
+    path: Required[str]
+
+
+def build(optim: OptimConfig, data: DataOptions | None = None, scheduler: SchedulerConfig | None = None):
+    """Takes structured configs."""
+    return optim, data, scheduler
@@ -0,0 +1,458 @@
+use serde_yaml::Value;
+
+/// A parsed Python type annotation, as rendered by the Python analyzer
+/// Example: "Callable[..., torch.nn.Module]" -> Generic { base: "Callable", args: [Ellipsis, Name] }
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    /// A plain or dotted name such as `int` or `torch.nn.Module`
+    Name(String),
+    /// A subscripted type such as `list[int]` or `Optional[str]`
+    Generic { base: String, args: Vec<Annotation> },
+    /// A bracketed list of types, as used for the parameters of `Callable`
+    List(Vec<Annotation>),
+    /// A union written with the `|` operator
+    Union(Vec<Annotation>),
+    /// The `...` literal
+    Ellipsis,
+    /// Anything we could not make sense of
+    Unknown,
+}
+
+impl Annotation {
+    /// Parse an annotation string, unwrapping string forward references
+    pub fn parse(annotation: &str) -> Self {
+        let tokens = tokenize(annotation);
+        let mut parser = Parser { tokens, pos: 0 };
+        let result = parser.parse_union();
+        if parser.pos == parser.tokens.len() {
+            result
+        } else {
+            Annotation::Unknown
+        }
+    }
+
+    /// Get the last segment of a (possibly dotted) name
+    /// Example: "torch.optim.Optimizer" -> "Optimizer"
+    pub fn short_name(&self) -> Option<&str> {
+        match self {
+            Annotation::Name(name) | Annotation::Generic { base: name, .. } => {
+                name.rsplit('.').next()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the members of a union, treating `Optional[X]` as `X | None`
+    /// Non-union annotations are returned as a single member
+    pub fn union_members(&self) -> Vec<Annotation> {
+        match self {
+            Annotation::Union(members) => members.iter().flat_map(|m| m.union_members()).collect(),
+            Annotation::Generic { args, .. } if self.short_name() == Some("Union") => {
+                args.iter().flat_map(|m| m.union_members()).collect()
+            }
+            Annotation::Generic { args, .. } if self.short_name() == Some("Optional") => args
+                .iter()
+                .flat_map(|m| m.union_members())
+                .chain(std::iter::once(Annotation::Name("None".to_string())))
+                .collect(),
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// Interpret this annotation as a `Callable[[...], R]`
+    /// Returns the number of positional parameters (`None` for `...`) and the return type
+    pub fn callable_signature(&self) -> Option<(Option<usize>, &Annotation)> {
+        match self {
+            Annotation::Generic { args, .. } if self.short_name() == Some("Callable") => {
+                match args.as_slice() {
+                    [Annotation::Ellipsis, ret] => Some((None, ret)),
+                    [Annotation::List(params), ret] => Some((Some(params.len()), ret)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether a YAML value is acceptable for this annotation
+    /// Returns `None` when the annotation is not one we know how to check
+    pub fn accepts(&self, value: &Value) -> Option<bool> {
+        let mut unknown = false;
+        for member in self.union_members() {
+            match member.accepts_single(value) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        if unknown {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    /// Check whether a YAML mapping or sequence only fits as a plain `dict` or `list`, which
+    /// OmegaConf's `DictConfig` and `ListConfig` are not
+    /// Abstract types such as `Mapping` and `Sequence` accept both
+    pub fn needs_primitive_container(&self, value: &Value) -> bool {
+        let mut needs_primitive = false;
+        for member in self.union_members() {
+            match (member.short_name(), value) {
+                (Some("dict" | "Dict"), Value::Mapping(_))
+                | (Some("list" | "List"), Value::Sequence(_)) => needs_primitive = true,
+                _ if member.accepts_single(value) == Some(false) => {}
+                _ => return false,
+            }
+        }
+        needs_primitive
+    }
+
+    /// Check whether a parameter accepts a config block that isn't instantiated, which arrives
+    /// as a `DictConfig`, or as a `dict` when `_convert_` converts containers
+    /// Returns `None` when the annotation is not one we know how to check
+    pub fn accepts_config(&self, as_dict: bool) -> Option<bool> {
+        let mut unknown = false;
+        for member in self.union_members() {
+            match member.short_name() {
+                Some("Any" | "object" | "Mapping" | "MutableMapping") => return Some(true),
+                Some("DictConfig" | "Container") if !as_dict => return Some(true),
+                Some("dict" | "Dict") if as_dict => return Some(true),
+                Some(_) => {}
+                None => unknown = true,
+            }
+        }
+        if unknown {
+            None
+        } else {
+            Some(false)
+        }
+    }
+
+    fn accepts_single(&self, value: &Value) -> Option<bool> {
+        let accepted = match self.short_name()? {
+            "Any" | "object" => true,
+            "None" => value.is_null(),
+            "int" => value.is_i64() || value.is_u64(),
+            "float" => value.is_number(),
+            "str" => value.is_string(),
+            "bool" => value.is_bool(),
+            "list" | "List" | "Sequence" | "tuple" | "Tuple" => value.is_sequence(),
+            "dict" | "Dict" | "Mapping" => value.is_mapping(),
+            _ => return None,
+        };
+        Some(accepted)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Str(String),
+    Ellipsis,
+    Open(char),
+    Close(char),
+    Comma,
+    Pipe,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            c if c.is_whitespace() => i += 1,
+            '[' | '(' => {
+                tokens.push(Token::Open(ch));
+                i += 1;
+            }
+            ']' | ')' => {
+                tokens.push(Token::Close(ch));
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '|' => {
+                tokens.push(Token::Pipe);
+                i += 1;
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == ch)
+                    .map(|offset| i + 1 + offset)
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            '.' if chars[i..].starts_with(&['.', '.', '.']) => {
+                tokens.push(Token::Ellipsis);
+                i += 3;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !matches!(chars[i], '[' | ']' | '(' | ')' | ',' | '|' | '\'' | '"')
+                    && !chars[i].is_whitespace()
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_union(&mut self) -> Annotation {
+        let mut members = vec![self.parse_primary()];
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            members.push(self.parse_primary());
+        }
+        if members.len() == 1 {
+            members.pop().unwrap()
+        } else {
+            Annotation::Union(members)
+        }
+    }
+
+    fn parse_list(&mut self, close: char) -> Vec<Annotation> {
+        let mut items = Vec::new();
+        while let Some(token) = self.peek() {
+            if *token == Token::Close(close) {
+                self.pos += 1;
+                break;
+            }
+            if *token == Token::Comma {
+                self.pos += 1;
+                continue;
+            }
+            let before = self.pos;
+            items.push(self.parse_union());
+            if self.pos == before {
+                // No progress, bail out to avoid looping forever
+                self.pos = self.tokens.len();
+                break;
+            }
+        }
+        items
+    }
+
+    fn parse_primary(&mut self) -> Annotation {
+        let Some(token) = self.peek().cloned() else {
+            return Annotation::Unknown;
+        };
+        self.pos += 1;
+        match token {
+            Token::Name(name) => {
+                if self.peek() == Some(&Token::Open('[')) {
+                    self.pos += 1;
+                    let args = self.parse_list(']');
+                    Annotation::Generic { base: name, args }
+                } else {
+                    Annotation::Name(name)
+                }
+            }
+            Token::Str(inner) => Annotation::parse(&inner),
+            Token::Ellipsis => Annotation::Ellipsis,
+            Token::Open('[') => Annotation::List(self.parse_list(']')),
+            Token::Open(_) => Annotation::List(self.parse_list(')')),
+            _ => Annotation::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_name() {
+        assert_eq!(
+            Annotation::parse("int"),
+            Annotation::Name("int".to_string())
+        );
+        let dotted = Annotation::parse("torch.optim.Optimizer");
+        assert_eq!(dotted.short_name(), Some("Optimizer"));
+    }
+
+    #[test]
+    fn test_parse_generic() {
+        let annotation = Annotation::parse("dict[str, int]");
+        assert_eq!(
+            annotation,
+            Annotation::Generic {
+                base: "dict".to_string(),
+                args: vec![
+                    Annotation::Name("str".to_string()),
+                    Annotation::Name("int".to_string())
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_forward_reference() {
+        assert_eq!(
+            Annotation::parse("'ClassB'"),
+            Annotation::Name("ClassB".to_string())
+        );
+    }
+
+    #[test]
+    fn test_union_members() {
+        let annotation = Annotation::parse("int | None");
+        let members = annotation.union_members();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1], Annotation::Name("None".to_string()));
+
+        let optional = Annotation::parse("Optional[str]");
+        let members = optional.union_members();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].short_name(), Some("str"));
+    }
+
+    #[test]
+    fn test_callable_signature() {
+        let annotation = Annotation::parse("Callable[..., torch.nn.Module]");
+        let (arity, ret) = annotation.callable_signature().unwrap();
+        assert_eq!(arity, None);
+        assert_eq!(ret.short_name(), Some("Module"));
+
+        let annotation = Annotation::parse("typing.Callable[[int, str], float]");
+        let (arity, ret) = annotation.callable_signature().unwrap();
+        assert_eq!(arity, Some(2));
+        assert_eq!(ret.short_name(), Some("float"));
+
+        assert!(Annotation::parse("list[int]")
+            .callable_signature()
+            .is_none());
+    }
+
+    #[test]
+    fn test_accepts_value() {
+        let value = |text: &str| serde_yaml::from_str::<Value>(text).unwrap();
+
+        assert_eq!(Annotation::parse("int").accepts(&value("3")), Some(true));
+        assert_eq!(Annotation::parse("int").accepts(&value("0.1")), Some(false));
+        assert_eq!(Annotation::parse("float").accepts(&value("3")), Some(true));
+        assert_eq!(
+            Annotation::parse("bool").accepts(&value("yes")),
+            Some(false)
+        );
+        assert_eq!(
+            Annotation::parse("Optional[str]").accepts(&value("null")),
+            Some(true)
+        );
+        assert_eq!(
+            Annotation::parse("list[int] | None").accepts(&value("{a: 1}")),
+            Some(false)
+        );
+
+        // Unknown members make the result unknown unless another member matches
+        assert_eq!(Annotation::parse("Path").accepts(&value("a/b")), None);
+        assert_eq!(
+            Annotation::parse("Path | str").accepts(&value("a/b")),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_accepts_matrix() {
+        let values = ["3", "0.5", "text", "true", "null", "[1, 2]", "{a: 1}"];
+        // Which of `values` each annotation accepts, in order
+        let matrix = [
+            ("int", [true, false, false, false, false, false, false]),
+            ("float", [true, true, false, false, false, false, false]),
+            ("str", [false, false, true, false, false, false, false]),
+            ("bool", [false, false, false, true, false, false, false]),
+            ("None", [false, false, false, false, true, false, false]),
+            (
+                "list[int]",
+                [false, false, false, false, false, true, false],
+            ),
+            (
+                "Tuple[int, int]",
+                [false, false, false, false, false, true, false],
+            ),
+            (
+                "Sequence[int]",
+                [false, false, false, false, false, true, false],
+            ),
+            (
+                "dict[str, int]",
+                [false, false, false, false, false, false, true],
+            ),
+            (
+                "Mapping[str, int]",
+                [false, false, false, false, false, false, true],
+            ),
+            (
+                "Optional[int]",
+                [true, false, false, false, true, false, false],
+            ),
+            ("int | str", [true, false, true, false, false, false, false]),
+            ("Any", [true; 7]),
+            ("object", [true; 7]),
+        ];
+        for (annotation, accepted) in matrix {
+            for (text, accepted) in values.iter().zip(accepted) {
+                let value = serde_yaml::from_str::<Value>(text).unwrap();
+                assert_eq!(
+                    Annotation::parse(annotation).accepts(&value),
+                    Some(accepted),
+                    "{} accepting {}",
+                    annotation,
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_needs_primitive_container() {
+        let value = |text: &str| serde_yaml::from_str::<Value>(text).unwrap();
+        let needs = |annotation: &str, text: &str| {
+            Annotation::parse(annotation).needs_primitive_container(&value(text))
+        };
+
+        assert!(needs("dict[str, int]", "{a: 1}"));
+        assert!(needs("Optional[list[float]]", "[0.1]"));
+        assert!(!needs("Mapping[str, int]", "{a: 1}"));
+        assert!(!needs("list[int] | DictConfig", "[1]"));
+        assert!(!needs("dict", "[1]"));
+    }
+
+    #[test]
+    fn test_accepts_config() {
+        let accepts =
+            |annotation: &str, as_dict: bool| Annotation::parse(annotation).accepts_config(as_dict);
+
+        assert_eq!(accepts("DictConfig", false), Some(true));
+        assert_eq!(accepts("Optional[omegaconf.DictConfig]", false), Some(true));
+        assert_eq!(accepts("DictConfig", true), Some(false));
+        assert_eq!(accepts("dict[str, Any]", true), Some(true));
+        assert_eq!(accepts("Optimizer", false), Some(false));
+        assert_eq!(accepts("", false), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(Annotation::parse(""), Annotation::Unknown);
+    }
+}
@@ -1,19 +1,50 @@
+use dashmap::DashMap;
 use parking_lot::RwLock;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
+use crate::code_actions;
+use crate::code_lens::{self, LensData, LensKind};
+use crate::composition::MISSING_VALUE;
 use crate::diagnostics;
 use crate::document::DocumentStore;
-use crate::python_analyzer::{DefinitionInfo, PythonAnalyzer};
-use crate::yaml_parser::{CompletionContext, YamlParser};
+use crate::document_symbols;
+use crate::inlay_hints;
+use crate::interpolation::{self, InterpolationResolver};
+use crate::python_analyzer::{DefinitionInfo, FunctionSignature, ParameterInfo, PythonAnalyzer};
+use crate::rules::{self, RuleSettings, RuleSeverity, PROJECT_CONFIG_FILE};
+use crate::semantic_tokens::{self, SemanticTokensCache};
+use crate::workspace_index::{self, WorkspaceIndex};
+use crate::yaml_parser::{CompletionContext, TargetInfo, YamlParser};
+
+/// Most results returned for a workspace symbol query, the best matches first
+const MAX_WORKSPACE_SYMBOLS: usize = 200;
 
 #[derive(Debug)]
 pub struct HydraLspBackend {
     pub client: Client,
     pub documents: Arc<DocumentStore>,
     pub python_interpreter: Arc<RwLock<Option<String>>>,
+    /// Rule settings from the initialization options
+    pub rule_settings: Arc<RwLock<RuleSettings>>,
+    /// Project config files of workspace folders, `None` without one
+    /// Only kept while the client watches files for us, so a changed file is seen
+    pub project_settings: Arc<DashMap<PathBuf, Option<RuleSettings>>>,
+    /// Whether the client lets us register file watchers, and has registered them
+    pub watching_files: Arc<RwLock<bool>>,
+    pub workspace_folders: Arc<RwLock<Vec<PathBuf>>>,
+    /// Every `_target_` and config group in the workspace, for references and symbol search
+    pub workspace_index: Arc<WorkspaceIndex>,
+    /// The last semantic tokens sent for each document, for sending only what changed
+    pub semantic_tokens: Arc<SemanticTokensCache>,
 }
 
 impl HydraLspBackend {
@@ -22,6 +53,12 @@ impl HydraLspBackend {
             client,
             documents: Arc::new(DocumentStore::new()),
             python_interpreter: Arc::new(RwLock::new(None)),
+            rule_settings: Arc::new(RwLock::new(RuleSettings::default())),
+            project_settings: Arc::new(DashMap::new()),
+            watching_files: Arc::new(RwLock::new(false)),
+            workspace_folders: Arc::new(RwLock::new(Vec::new())),
+            workspace_index: Arc::new(WorkspaceIndex::new()),
+            semantic_tokens: Arc::new(SemanticTokensCache::new()),
         }
     }
 }
@@ -38,6 +75,42 @@ impl LanguageServer for HydraLspBackend {
                 ),
             )
             .await;
+
+        if let Some(options) = &params.initialization_options {
+            match RuleSettings::deserialize(options) {
+                Ok(settings) => *self.rule_settings.write() = settings,
+                Err(e) => {
+                    self.client
+                        .log_message(
+                            MessageType::ERROR,
+                            format!("Invalid rule settings in initialization options: {}", e),
+                        )
+                        .await;
+                }
+            }
+        }
+
+        #[allow(deprecated)] // Fall back to root_uri for clients without workspace folders
+        let folders: Vec<PathBuf> = match &params.workspace_folders {
+            Some(folders) => folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect(),
+            None => params
+                .root_uri
+                .iter()
+                .filter_map(|uri| uri.to_file_path().ok())
+                .collect(),
+        };
+        *self.workspace_folders.write() = folders;
+        *self.watching_files.write() = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched_files| watched_files.dynamic_registration)
+            .unwrap_or(false);
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
@@ -55,31 +128,53 @@ impl LanguageServer for HydraLspBackend {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: code_lens::COMMANDS.map(String::from).to_vec(),
+                    ..Default::default()
+                }),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        ..Default::default()
+                    },
+                )),
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(
                         SemanticTokensOptions {
-                            legend: SemanticTokensLegend {
-                                token_types: vec![
-                                    SemanticTokenType::NAMESPACE,
-                                    SemanticTokenType::CLASS,
-                                    SemanticTokenType::FUNCTION,
-                                    SemanticTokenType::PARAMETER,
-                                    SemanticTokenType::PROPERTY,
-                                    SemanticTokenType::VARIABLE,
-                                    SemanticTokenType::STRING,
-                                    SemanticTokenType::NUMBER,
-                                ],
-                                token_modifiers: vec![
-                                    SemanticTokenModifier::DECLARATION,
-                                    SemanticTokenModifier::DEFINITION,
-                                ],
-                            },
-                            range: Some(false),
-                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            legend: semantic_tokens::legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
                             ..Default::default()
                         },
                     ),
                 ),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    // Moving Python files and packages changes the module paths of targets
+                    file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(FileOperationRegistrationOptions {
+                            filters: vec![
+                                file_operation_filter("**/*.py", FileOperationPatternKind::File),
+                                file_operation_filter("**", FileOperationPatternKind::Folder),
+                            ],
+                        }),
+                        ..Default::default()
+                    }),
+                }),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -90,6 +185,12 @@ impl LanguageServer for HydraLspBackend {
     }
 
     async fn initialized(&self, _params: InitializedParams) {
+        self.ensure_indexed().await;
+
+        if *self.watching_files.read() {
+            self.register_file_watchers().await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "Hydra LSP server initialized")
             .await;
@@ -99,12 +200,102 @@ impl LanguageServer for HydraLspBackend {
         Ok(())
     }
 
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let paths = |folders: &[WorkspaceFolder]| -> Vec<PathBuf> {
+            folders
+                .iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect()
+        };
+        let removed = paths(&params.event.removed);
+        let added = paths(&params.event.added);
+        let folders = {
+            let mut folders = self.workspace_folders.write();
+            folders.retain(|folder| !removed.contains(folder));
+            for folder in &added {
+                if !folders.contains(folder) {
+                    folders.push(folder.clone());
+                }
+            }
+            folders.clone()
+        };
+        for folder in &removed {
+            self.project_settings.remove(folder);
+        }
+
+        self.workspace_index.retain_folders(&folders);
+        let workspace_index = self.workspace_index.clone();
+        let indexing = tokio::task::spawn_blocking(move || {
+            for folder in &added {
+                workspace_index.index_folder(folder);
+            }
+        });
+        if let Err(error) = indexing.await {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Failed to index workspace: {}", error),
+                )
+                .await;
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let folders = self.workspace_folders.read().clone();
+        let mut changed_settings = Vec::new();
+        for change in params.changes {
+            let Ok(path) = change.uri.to_file_path() else {
+                continue;
+            };
+            // Configs changed outside the editor, open ones are kept up to date through edits
+            if WorkspaceIndex::is_workspace_config(&path, &folders)
+                && self.documents.get(&change.uri).is_none()
+            {
+                match change.typ {
+                    FileChangeType::DELETED => self.workspace_index.remove(&path),
+                    _ => self.workspace_index.reload(&path),
+                }
+            }
+            // A project config file sets the rule settings of its workspace folder
+            if path
+                .file_name()
+                .is_some_and(|name| name == PROJECT_CONFIG_FILE)
+            {
+                if let Some(folder) = path.parent() {
+                    self.project_settings.remove(folder);
+                    changed_settings.push(folder.to_path_buf());
+                }
+            }
+        }
+
+        // Severities and disabled rules change for the open documents of those folders
+        for uri in self.documents.uris() {
+            let in_folder = uri.to_file_path().is_ok_and(|path| {
+                changed_settings
+                    .iter()
+                    .any(|folder| path.starts_with(folder))
+            });
+            if !in_folder {
+                continue;
+            }
+            if let Some(document) = self.documents.get(&uri) {
+                if YamlParser::is_hydra_file(&document.content) {
+                    self.publish_diagnostics_for_document(&uri, &document.content)
+                        .await;
+                }
+            }
+        }
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
         let text = params.text_document.text;
         let version = params.text_document.version;
 
         self.documents.insert(uri.clone(), text.clone(), version);
+        if let Some(path) = self.config_path(&uri) {
+            self.workspace_index.update(&path, &text);
+        }
 
         // Publish diagnostics if this is a Hydra file
         if YamlParser::is_hydra_file(&text) {
@@ -124,6 +315,9 @@ impl LanguageServer for HydraLspBackend {
         if let Some(change) = params.content_changes.into_iter().next() {
             self.documents
                 .update(uri.clone(), change.text.clone(), version);
+            if let Some(path) = self.config_path(&uri) {
+                self.workspace_index.update(&path, &change.text);
+            }
 
             // Re-publish diagnostics if this is a Hydra file
             if YamlParser::is_hydra_file(&change.text) {
@@ -149,6 +343,11 @@ impl LanguageServer for HydraLspBackend {
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         self.documents.remove(&uri);
+        self.semantic_tokens.remove(&uri);
+        // Unsaved edits are discarded, so go back to the file on disk
+        if let Some(path) = self.config_path(&uri) {
+            self.workspace_index.reload(&path);
+        }
 
         self.client
             .log_message(MessageType::INFO, format!("Document closed: {}", uri))
@@ -172,8 +371,9 @@ impl LanguageServer for HydraLspBackend {
 
         // Find _target_ at cursor position
         let target_info = match YamlParser::find_target_at_position(&document.content, position) {
-            Ok(Some(info)) => info,
-            Ok(None) => return Ok(None),
+            Ok(Some(info)) => Self::resolve_interpolations(&document.content, &uri, info),
+            // Otherwise show the parameter a key under the cursor is passed to
+            Ok(None) => return Ok(self.parameter_hover(&document.content, &uri, position)),
             Err(e) => {
                 self.client
                     .log_message(MessageType::ERROR, format!("YAML parse error: {}", e))
@@ -190,15 +390,16 @@ impl LanguageServer for HydraLspBackend {
             .await;
 
         // Split target into module and symbol
-        let (_module_path, _symbol_name) = match PythonAnalyzer::split_target(&target_info.value) {
-            Ok(parts) => parts,
-            Err(e) => {
-                self.client
-                    .log_message(MessageType::ERROR, format!("Invalid target: {}", e))
-                    .await;
-                return Ok(None);
-            }
-        };
+        let (_module_path, _symbol_name) =
+            match PythonAnalyzer::split_target(target_info.target_path()) {
+                Ok(parts) => parts,
+                Err(e) => {
+                    self.client
+                        .log_message(MessageType::ERROR, format!("Invalid target: {}", e))
+                        .await;
+                    return Ok(None);
+                }
+            };
 
         // Try to get the workspace root from the URI
         let workspace_root = uri
@@ -211,15 +412,20 @@ impl LanguageServer for HydraLspBackend {
 
         // Try to extract Python definition information
         match PythonAnalyzer::extract_definition_info(
-            &target_info.value,
+            target_info.target_path(),
             workspace_root.as_deref(),
             python_interpreter.as_deref(),
         ) {
             Ok(definition_info) => {
-                let hover_content = match definition_info {
+                let mut hover_content = match definition_info {
                     DefinitionInfo::Function(sig) => PythonAnalyzer::format_signature(&sig),
                     DefinitionInfo::Class(class_info) => PythonAnalyzer::format_class(&class_info),
                 };
+                if target_info.raw {
+                    hover_content.push_str(
+                        "\n\n---\n\n**Not instantiated**: the enclosing target sets `_recursive_: false`, so this block is passed on as a config",
+                    );
+                }
                 let range = Range {
                     start: Position {
                         line: target_info.line,
@@ -391,7 +597,7 @@ impl LanguageServer for HydraLspBackend {
 
         // Find _target_ at or near cursor position to get context
         let target_info = match YamlParser::find_target_at_position(&document.content, position) {
-            Ok(Some(info)) => info,
+            Ok(Some(info)) => Self::resolve_interpolations(&document.content, &uri, info),
             Ok(None) => return Ok(None),
             Err(e) => {
                 self.client
@@ -402,15 +608,16 @@ impl LanguageServer for HydraLspBackend {
         };
 
         // Split target into module and symbol
-        let (_module_path, _symbol_name) = match PythonAnalyzer::split_target(&target_info.value) {
-            Ok(parts) => parts,
-            Err(e) => {
-                self.client
-                    .log_message(MessageType::ERROR, format!("Invalid target: {}", e))
-                    .await;
-                return Ok(None);
-            }
-        };
+        let (_module_path, _symbol_name) =
+            match PythonAnalyzer::split_target(target_info.target_path()) {
+                Ok(parts) => parts,
+                Err(e) => {
+                    self.client
+                        .log_message(MessageType::ERROR, format!("Invalid target: {}", e))
+                        .await;
+                    return Ok(None);
+                }
+            };
 
         // Try to get the workspace root from the URI
         let workspace_root = uri
@@ -423,32 +630,32 @@ impl LanguageServer for HydraLspBackend {
 
         // Try to extract Python definition information
         match PythonAnalyzer::extract_definition_info(
-            &target_info.value,
+            target_info.target_path(),
             workspace_root.as_deref(),
             python_interpreter.as_deref(),
         ) {
             Ok(definition_info) => {
                 let (signature_label, parameters, doc_string) = match definition_info {
                     crate::python_analyzer::DefinitionInfo::Function(sig) => {
-                        let param_strs: Vec<String> = sig
-                            .parameters
-                            .iter()
-                            .map(|p| {
-                                let mut s = String::new();
-                                if p.is_variadic {
-                                    s.push('*');
-                                } else if p.is_variadic_keyword {
-                                    s.push_str("**");
-                                }
-                                s.push_str(&p.name);
-                                if let Some(type_ann) = &p.type_annotation {
-                                    s.push_str(&format!(": {}", type_ann));
-                                }
-                                s
-                            })
-                            .collect();
-
-                        let label = format!("{}({})", sig.name, param_strs.join(", "));
+                        let param_strs = sig.parameters.iter().map(|p| {
+                            let mut s = String::new();
+                            if p.is_variadic {
+                                s.push('*');
+                            } else if p.is_variadic_keyword {
+                                s.push_str("**");
+                            }
+                            s.push_str(&p.name);
+                            if let Some(type_ann) = &p.type_annotation {
+                                s.push_str(&format!(": {}", type_ann));
+                            }
+                            (p, s)
+                        });
+
+                        let label = format!(
+                            "{}({})",
+                            sig.name,
+                            PythonAnalyzer::join_parameters(param_strs)
+                        );
 
                         let params = sig
                             .parameters
@@ -478,7 +685,7 @@ impl LanguageServer for HydraLspBackend {
                     }
                     crate::python_analyzer::DefinitionInfo::Class(class_info) => {
                         if let Some(init_sig) = &class_info.init_signature {
-                            let param_strs: Vec<String> = init_sig
+                            let param_strs = init_sig
                                 .parameters
                                 .iter()
                                 .filter(|p| p.name != "self")
@@ -493,11 +700,14 @@ impl LanguageServer for HydraLspBackend {
                                     if let Some(type_ann) = &p.type_annotation {
                                         s.push_str(&format!(": {}", type_ann));
                                     }
-                                    s
-                                })
-                                .collect();
+                                    (p, s)
+                                });
 
-                            let label = format!("{}({})", class_info.name, param_strs.join(", "));
+                            let label = format!(
+                                "{}({})",
+                                class_info.name,
+                                PythonAnalyzer::join_parameters(param_strs)
+                            );
 
                             let params = init_sig
                                 .parameters
@@ -584,8 +794,23 @@ impl LanguageServer for HydraLspBackend {
 
         // Find _target_ at cursor position
         let target_info = match YamlParser::find_target_at_position(&document.content, position) {
-            Ok(Some(info)) => info,
-            Ok(None) => return Ok(None),
+            Ok(Some(info)) => Self::resolve_interpolations(&document.content, &uri, info),
+            // Otherwise go to the parameter a key under the cursor is passed to
+            Ok(None) => {
+                let location = self
+                    .parameter_at_position(&document.content, &uri, position)
+                    .and_then(|found| {
+                        Some(Location {
+                            uri: Url::from_file_path(&found.file_path).ok()?,
+                            range: name_range(
+                                found.parameter.line,
+                                found.parameter.column,
+                                found.parameter.end_column,
+                            ),
+                        })
+                    });
+                return Ok(location.map(GotoDefinitionResponse::Scalar));
+            }
             Err(e) => {
                 self.client
                     .log_message(MessageType::ERROR, format!("YAML parse error: {}", e))
@@ -595,7 +820,8 @@ impl LanguageServer for HydraLspBackend {
         };
 
         // Split target into module and symbol
-        let (module_path, _symbol_name) = match PythonAnalyzer::split_target(&target_info.value) {
+        let target = target_info.target_path();
+        let (module_path, _symbol_name) = match PythonAnalyzer::split_target(target) {
             Ok(parts) => parts,
             Err(e) => {
                 self.client
@@ -614,21 +840,51 @@ impl LanguageServer for HydraLspBackend {
         // Get the python interpreter path
         let python_interpreter = self.python_interpreter.read().clone();
 
-        // Try to resolve the module to a file
-        let file_path = match PythonAnalyzer::resolve_module(
-            &module_path,
-            workspace_root.as_deref(),
-            python_interpreter.as_deref(),
-        ) {
-            Ok(path) => path,
-            Err(e) => {
-                self.client
-                    .log_message(
-                        MessageType::WARNING,
-                        format!("Could not resolve module {}: {}", module_path, e),
-                    )
-                    .await;
-                return Ok(None);
+        // On the module part of the path, go to the module the cursor is in
+        // Example: the cursor on "nn" in "torch.nn.Linear" goes to `torch.nn`
+        // Positions in an interpolated value don't map onto the resolved path
+        let cursor_module = target_info
+            .segment_at(position.character)
+            .filter(|segment| target_info.resolved.is_none() && segment.end <= module_path.len())
+            .map(|segment| &target[..segment.end]);
+
+        let definition = match cursor_module {
+            Some(_) => None,
+            None => PythonAnalyzer::extract_definition_info(
+                target,
+                workspace_root.as_deref(),
+                python_interpreter.as_deref(),
+            )
+            .ok(),
+        };
+        let (file_path, range) = match definition {
+            Some(DefinitionInfo::Function(sig)) => (
+                sig.file_path,
+                name_range(sig.line, sig.column, sig.end_column),
+            ),
+            Some(DefinitionInfo::Class(class_info)) => (
+                class_info.file_path,
+                name_range(class_info.line, class_info.column, class_info.end_column),
+            ),
+            // Modules, and symbols we can't extract such as constants, go to the module file
+            None => {
+                let module = cursor_module.unwrap_or(&module_path);
+                match PythonAnalyzer::resolve_module(
+                    module,
+                    workspace_root.as_deref(),
+                    python_interpreter.as_deref(),
+                ) {
+                    Ok(path) => (path, Range::default()),
+                    Err(e) => {
+                        self.client
+                            .log_message(
+                                MessageType::WARNING,
+                                format!("Could not resolve module {}: {}", module, e),
+                            )
+                            .await;
+                        return Ok(None);
+                    }
+                }
             }
         };
 
@@ -646,27 +902,186 @@ impl LanguageServer for HydraLspBackend {
             }
         };
 
-        // For now, just navigate to the file (line 0)
-        // TODO: Find the exact line number of the definition
         Ok(Some(GotoDefinitionResponse::Scalar(Location {
             uri: target_uri,
-            range: Range {
-                start: Position {
-                    line: 0,
-                    character: 0,
-                },
-                end: Position {
-                    line: 0,
-                    character: 0,
-                },
-            },
+            range,
         })))
     }
 
-    async fn semantic_tokens_full(
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let Ok(path) = uri.to_file_path() else {
+            return Ok(None);
+        };
+
+        // Fall back to the file on disk for documents the client hasn't sent
+        let content = match self.documents.get(&uri) {
+            Some(doc) => doc.content,
+            None => match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => return Ok(None),
+            },
+        };
+        let python_interpreter = self.python_interpreter.read().clone();
+
+        // Find the symbol under the cursor, and the definition it resolves to
+        let (name, target, definition) = if path.extension().is_some_and(|ext| ext == "py") {
+            let Some((name, line)) =
+                PythonAnalyzer::definition_at_position(&content, position.line, position.character)
+            else {
+                return Ok(None);
+            };
+            let definition = (fs::canonicalize(&path).unwrap_or(path), line);
+            (name, None, Some(definition))
+        } else {
+            let target_info = match YamlParser::find_target_at_position(&content, position) {
+                Ok(Some(info)) => Self::resolve_interpolations(&content, &uri, info),
+                _ => return Ok(None),
+            };
+            let target = target_info.target_path().to_string();
+            let Ok((_, name)) = PythonAnalyzer::split_target(&target) else {
+                return Ok(None);
+            };
+            let definition =
+                Self::definition_location(&target, path.parent(), python_interpreter.as_deref());
+            (name, Some(target), definition)
+        };
+
+        self.ensure_indexed().await;
+
+        // Targets with the same name are references if they resolve to the same definition
+        // Targets that can't be resolved only match the same dotted path
+        // The declaration is in Python, so only the configs are returned
+        let locations: Vec<Location> = self
+            .workspace_index
+            .targets_named(&name)
+            .into_iter()
+            .filter(|(config_path, indexed)| match &definition {
+                Some(definition) => {
+                    Self::definition_location(
+                        &indexed.target,
+                        config_path.parent(),
+                        python_interpreter.as_deref(),
+                    )
+                    .as_ref()
+                        == Some(definition)
+                }
+                None => target.as_deref() == Some(indexed.target.as_str()),
+            })
+            .filter_map(|(config_path, indexed)| {
+                Some(Location {
+                    uri: Url::from_file_path(&config_path).ok()?,
+                    range: name_range(indexed.line, indexed.value_start, indexed.value_end),
+                })
+            })
+            .collect();
+
+        Ok(Some(locations))
+    }
+
+    async fn prepare_rename(
         &self,
-        params: SemanticTokensParams,
-    ) -> Result<Option<SemanticTokensResult>> {
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let Some((target_info, segment)) = self.target_segment_at(&params) else {
+            return Ok(None);
+        };
+        // Refuse up front what `rename` can't do, so the client can say why
+        self.rename_subject(&params.text_document.uri, &target_info, &segment)
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+        Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: name_range(
+                target_info.line,
+                target_info.value_start + segment.start as u32,
+                target_info.value_start + segment.end as u32,
+            ),
+            placeholder: target_info.value[segment].to_string(),
+        }))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let Some((target_info, segment)) = self.target_segment_at(&params.text_document_position)
+        else {
+            return Ok(None);
+        };
+        let new_name = params.new_name.trim();
+        if !is_identifier(new_name) {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "'{}' is not a valid Python identifier",
+                new_name
+            )));
+        }
+        let uri = &params.text_document_position.text_document.uri;
+        let subject = self
+            .rename_subject(uri, &target_info, &segment)
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        // Only configs are edited, the Python language server renames the module or definition
+        let changes = match subject {
+            RenameSubject::Module(module_file) => {
+                // Every target under the renamed segment changes, e.g. renaming the module
+                // `models` in "pkg.models.Encoder" also rewrites "pkg.models.Decoder"
+                let old_prefix = &target_info.value[..segment.end];
+                let new_prefix = format!("{}{}", &target_info.value[..segment.start], new_name);
+                self.rename_prefix(old_prefix, &new_prefix, &module_location(&module_file))
+                    .await
+            }
+            RenameSubject::Symbol(location) => {
+                let name = &target_info.value[segment];
+                self.rename_symbol(name, new_name, &location)
+                    .await
+                    .map_err(tower_lsp::jsonrpc::Error::invalid_params)?
+            }
+        };
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    async fn will_rename_files(&self, params: RenameFilesParams) -> Result<Option<WorkspaceEdit>> {
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for file in &params.files {
+            let (Ok(old_uri), Ok(new_uri)) = (Url::parse(&file.old_uri), Url::parse(&file.new_uri))
+            else {
+                continue;
+            };
+            let (Ok(old_path), Ok(new_path)) = (old_uri.to_file_path(), new_uri.to_file_path())
+            else {
+                continue;
+            };
+            // Sent before the move, so the old path still exists to tell files from packages
+            // The folder filter can't tell packages from other directories, so they're skipped here
+            if old_path.is_dir() && !old_path.join("__init__.py").exists() {
+                continue;
+            }
+            let (Some(old_module), Some(new_module)) = (
+                PythonAnalyzer::module_name(&old_path),
+                PythonAnalyzer::module_name(&new_path),
+            ) else {
+                continue;
+            };
+            if old_module == new_module {
+                continue;
+            }
+            let edits = self
+                .rename_prefix(&old_module, &new_module, &module_location(&old_path))
+                .await;
+            for (uri, edits) in edits {
+                changes.entry(uri).or_default().extend(edits);
+            }
+        }
+        Ok((!changes.is_empty()).then(|| WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
         let uri = params.text_document.uri;
 
         // Get document content
@@ -680,80 +1095,956 @@ impl LanguageServer for HydraLspBackend {
             return Ok(None);
         }
 
-        // TODO: Implement semantic token generation
-        // This would involve:
-        // 1. Parse YAML to find all _target_ values
-        // 2. Identify parameter keys associated with targets
-        // 3. Generate semantic tokens for:
-        //    - Module paths (NAMESPACE)
-        //    - Class/function names (CLASS/FUNCTION)
-        //    - Parameter names (PARAMETER/PROPERTY)
-        //    - Values (STRING/NUMBER/etc)
-        // 4. Return tokens in the LSP delta format
+        let document_path = uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
+        let python_interpreter = self.python_interpreter.read().clone();
+        let symbols = document_symbols::document_symbols(
+            &document.content,
+            document_path.as_deref(),
+            |target| {
+                Self::target_symbol_kind(target, workspace_root, python_interpreter.as_deref())
+            },
+        );
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
 
-        self.client
-            .log_message(
-                MessageType::INFO,
-                "Semantic tokens requested (not yet fully implemented)".to_string(),
-            )
-            .await;
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        self.ensure_indexed().await;
+        let query = params.query.trim();
+
+        // Targets match on their class or function name, or failing that on the dotted path
+        let mut found: Vec<(u32, SymbolInformation)> = Vec::new();
+        for (config_path, indexed) in self.workspace_index.targets() {
+            let Some(score) = workspace_index::fuzzy_score(query, indexed.symbol_name())
+                .or_else(|| workspace_index::fuzzy_score(query, &indexed.target))
+            else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(&config_path) else {
+                continue;
+            };
+            found.push((
+                score,
+                document_symbols::symbol_information(
+                    indexed.symbol_name().to_string(),
+                    SymbolKind::OBJECT,
+                    Some(indexed.target.clone()),
+                    Location {
+                        uri,
+                        range: name_range(indexed.line, indexed.value_start, indexed.value_end),
+                    },
+                ),
+            ));
+        }
+
+        // Config group options match on their name, and point at their file
+        for option in self.workspace_index.group_options() {
+            let Some(score) = workspace_index::fuzzy_score(query, &option.name) else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(&option.path) else {
+                continue;
+            };
+            found.push((
+                score,
+                document_symbols::symbol_information(
+                    option.name,
+                    SymbolKind::MODULE,
+                    Some(option.group),
+                    Location {
+                        uri,
+                        range: Range::default(),
+                    },
+                ),
+            ));
+        }
+
+        found.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.location.uri.as_str().cmp(b.location.uri.as_str()))
+                .then_with(|| {
+                    a.location
+                        .range
+                        .start
+                        .line
+                        .cmp(&b.location.range.start.line)
+                })
+        });
+        found.truncate(MAX_WORKSPACE_SYMBOLS);
+        Ok(Some(found.into_iter().map(|(_, symbol)| symbol).collect()))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri;
+        let Some(document) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        if !YamlParser::is_hydra_file(&document.content) {
+            return Ok(None);
+        }
+
+        let document_path = uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
+        let python_interpreter = self.python_interpreter.read().clone();
+        let hints = inlay_hints::inlay_hints(
+            &document.content,
+            document_path.as_deref(),
+            params.range,
+            |target| Self::target_signature(target, workspace_root, python_interpreter.as_deref()),
+        );
+        Ok(Some(hints))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let uri = params.text_document.uri;
+        let Some(document) = self.documents.get(&uri) else {
+            return Ok(None);
+        };
+        if !YamlParser::is_hydra_file(&document.content) {
+            return Ok(None);
+        }
+        Ok(Some(code_lens::code_lenses(&document.content, &uri)))
+    }
+
+    async fn code_lens_resolve(&self, mut lens: CodeLens) -> Result<CodeLens> {
+        let Some(data) = lens
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value::<LensData>(data).ok())
+        else {
+            return Ok(lens);
+        };
+        let Some(document) = self.documents.get(&data.uri) else {
+            return Ok(lens);
+        };
+        let outline = YamlParser::outline(&document.content);
+        let Some((block, mut target)) = code_lens::target_at_line(&outline, data.line) else {
+            return Ok(lens);
+        };
+
+        let document_path = data.uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
+        InterpolationResolver::new(&document.content, document_path.as_deref())
+            .resolve_target(&mut target);
+        let python_interpreter = self.python_interpreter.read().clone();
+        let command = match data.kind {
+            LensKind::Definition => {
+                let definition = PythonAnalyzer::extract_definition_info(
+                    target.target_path(),
+                    workspace_root,
+                    python_interpreter.as_deref(),
+                )
+                .ok();
+                let folders = self.workspace_folders.read().clone();
+                code_lens::definition_command(definition.as_ref(), &folders)
+            }
+            LensKind::Parameters => {
+                let signature =
+                    Self::target_signature(&target, workspace_root, python_interpreter.as_deref());
+                let counts = signature.map(|signature| {
+                    let (required, missing) =
+                        code_lens::missing_parameters(&outline, block, &signature);
+                    (required, missing.len())
+                });
+                code_lens::parameters_command(&data, counts)
+            }
+            LensKind::ComposedConfig => code_lens::composed_config_command(&data),
+        };
+        lens.command = Some(command);
+        Ok(lens)
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        match params.command.as_str() {
+            code_lens::OPEN_DEFINITION_COMMAND => {
+                let location: Location = command_argument(&params.arguments, 0)?;
+                self.show_document(location.uri, Some(location.range)).await;
+            }
+            code_lens::INSERT_MISSING_PARAMETERS_COMMAND => {
+                let uri: Url = command_argument(&params.arguments, 0)?;
+                let line: u32 = command_argument(&params.arguments, 1)?;
+                if let Some(edit) = self.missing_parameters_edit(&uri, line) {
+                    let changes = HashMap::from([(uri, vec![edit])]);
+                    let edit = WorkspaceEdit {
+                        changes: Some(changes),
+                        ..Default::default()
+                    };
+                    if let Err(error) = self.client.apply_edit(edit).await {
+                        self.client
+                            .log_message(
+                                MessageType::ERROR,
+                                format!("Failed to insert missing parameters: {}", error),
+                            )
+                            .await;
+                    }
+                }
+            }
+            code_lens::SHOW_COMPOSED_CONFIG_COMMAND => {
+                let uri: Url = command_argument(&params.arguments, 0)?;
+                let line: u32 = command_argument(&params.arguments, 1)?;
+                self.show_composed_config(&uri, line).await;
+            }
+            command => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Unknown command '{}'",
+                    command
+                )));
+            }
+        }
+        Ok(None)
+    }
 
-        // Placeholder: return empty token list
-        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+
+        // Get document content
+        let document = match self.documents.get(&uri) {
+            Some(doc) => doc,
+            None => return Ok(None),
+        };
+
+        // Quick fixes are computed from the diagnostics the client sends back
+        let document_path = uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
+        let python_interpreter = self.python_interpreter.read().clone();
+        let actions = code_actions::quick_fixes(
+            &document.content,
+            &uri,
+            &params.context.diagnostics,
+            |module_path| {
+                diagnostics::unresolved_segment(
+                    module_path,
+                    workspace_root,
+                    python_interpreter.as_deref(),
+                )
+            },
+            |target, segment| {
+                diagnostics::segment_candidates(
+                    target,
+                    segment,
+                    workspace_root,
+                    python_interpreter.as_deref(),
+                )
+            },
+        );
+        Ok((!actions.is_empty()).then_some(actions))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let uri = params.text_document.uri;
+        let Some(tokens) = self.document_tokens(&uri, None) else {
+            return Ok(None);
+        };
+        let tokens = self
+            .semantic_tokens
+            .store(&uri, semantic_tokens::encode(&tokens));
+        Ok(Some(SemanticTokensResult::Tokens(tokens)))
+    }
+
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let uri = params.text_document.uri;
+        let Some(tokens) = self.document_tokens(&uri, None) else {
+            return Ok(None);
+        };
+        Ok(Some(self.semantic_tokens.delta(
+            &uri,
+            &params.previous_result_id,
+            semantic_tokens::encode(&tokens),
+        )))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let Some(tokens) = self.document_tokens(&params.text_document.uri, Some(&params.range))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
             result_id: None,
-            data: vec![],
+            data: semantic_tokens::encode(&tokens),
         })))
     }
 }
 
+/// What a segment of a `_target_` being renamed names in Python
+enum RenameSubject {
+    /// A module, by its file or its package's `__init__.py`
+    Module(PathBuf),
+    /// A class or function, by the file defining it and the line of its name there
+    Symbol((PathBuf, u32)),
+}
+
+/// Get the path identifying a module: its package directory, or its file without extension
+/// Example: "pkg/__init__.py" and "pkg" give "pkg", "pkg/models.pyi" gives "pkg/models"
+fn module_location(path: &Path) -> PathBuf {
+    let path = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    if path.is_dir() {
+        return path;
+    }
+    match (path.file_stem(), path.parent()) {
+        (Some(stem), Some(package)) if stem == "__init__" => package.to_path_buf(),
+        _ => path.with_extension(""),
+    }
+}
+
+/// Register interest in file operations on `file:` URIs matching a glob
+fn file_operation_filter(glob: &str, kind: FileOperationPatternKind) -> FileOperationFilter {
+    FileOperationFilter {
+        scheme: Some("file".to_string()),
+        pattern: FileOperationPattern {
+            glob: glob.to_string(),
+            matches: Some(kind),
+            options: None,
+        },
+    }
+}
+
+/// Check if a name can be used as a Python module, class or function name
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+/// A Python parameter that a config key is passed to
+struct KeyParameter {
+    /// The config key and its range
+    key: String,
+    range: Range,
+    /// File the parameter is declared in
+    file_path: PathBuf,
+    parameter: ParameterInfo,
+    /// The parameter's entry in the docstring
+    documentation: Option<String>,
+}
+
+/// Deserialize an argument of a `workspace/executeCommand` request
+fn command_argument<T: serde::de::DeserializeOwned>(
+    arguments: &[serde_json::Value],
+    index: usize,
+) -> Result<T> {
+    arguments
+        .get(index)
+        .and_then(|argument| serde_json::from_value(argument.clone()).ok())
+        .ok_or_else(|| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid argument {}", index))
+        })
+}
+
+/// A range within a single line, such as a symbol name in a Python file
+fn name_range(line: u32, start: u32, end: u32) -> Range {
+    Range {
+        start: Position {
+            line,
+            character: start,
+        },
+        end: Position {
+            line,
+            character: end,
+        },
+    }
+}
+
 impl HydraLspBackend {
-    /// Publish diagnostics for a document
-    async fn publish_diagnostics_for_document(&self, uri: &Url, content: &str) {
+    /// Substitute static `${...}` interpolations in a target found at the cursor
+    fn resolve_interpolations(content: &str, uri: &Url, mut target_info: TargetInfo) -> TargetInfo {
+        if !interpolation::has_interpolation(&target_info.value) {
+            return target_info;
+        }
+        let document_path = uri.to_file_path().ok();
+        InterpolationResolver::new(content, document_path.as_deref())
+            .resolve_target(&mut target_info);
+        target_info
+    }
+
+    /// Find the Python parameter that the config key at a position is passed to
+    fn parameter_at_position(
+        &self,
+        content: &str,
+        uri: &Url,
+        position: Position,
+    ) -> Option<KeyParameter> {
+        let (target_info, key) =
+            YamlParser::find_parameter_at_position(content, position).ok()??;
+        let target_info = Self::resolve_interpolations(content, uri, target_info);
         let workspace_root = uri
             .to_file_path()
             .ok()
             .and_then(|path| path.parent().map(|p| p.to_path_buf()));
+        let python_interpreter = self.python_interpreter.read().clone();
+        let definition = PythonAnalyzer::extract_definition_info(
+            target_info.target_path(),
+            workspace_root.as_deref(),
+            python_interpreter.as_deref(),
+        )
+        .ok()?;
+
+        // Class parameters may be documented on `__init__` or on the class itself
+        let (signature, docstrings) = match &definition {
+            DefinitionInfo::Function(sig) => (sig, vec![sig.docstring.as_deref()]),
+            DefinitionInfo::Class(class_info) => {
+                let sig = class_info.init_signature.as_ref()?;
+                (
+                    sig,
+                    vec![sig.docstring.as_deref(), class_info.docstring.as_deref()],
+                )
+            }
+        };
+        let parameter = signature.keyword_parameter(&key.key)?;
+        let documentation = docstrings
+            .into_iter()
+            .flatten()
+            .find_map(|docstring| PythonAnalyzer::parameter_doc(docstring, &parameter.name));
+
+        Some(KeyParameter {
+            range: name_range(
+                key.line,
+                target_info.key_start,
+                target_info.key_start + key.key.len() as u32,
+            ),
+            key: key.key,
+            file_path: signature.file_path.clone(),
+            parameter: parameter.clone(),
+            documentation,
+        })
+    }
 
-        // Get the python interpreter path
+    /// Show the annotation, default and documentation of the parameter a key is passed to
+    fn parameter_hover(&self, content: &str, uri: &Url, position: Position) -> Option<Hover> {
+        let found = self.parameter_at_position(content, uri, position)?;
+        let mut value =
+            PythonAnalyzer::format_parameter(&found.parameter, found.documentation.as_deref());
+        if found.parameter.is_variadic_keyword {
+            value.push_str(&format!(
+                "\n\n`{}` is passed through `**{}`",
+                found.key, found.parameter.name
+            ));
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(found.range),
+        })
+    }
+
+    /// Find the dotted path segment of a `_target_` value at a position
+    /// Interpolated values are skipped, since their text isn't the path that would be renamed
+    fn target_segment_at(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<(TargetInfo, std::ops::Range<usize>)> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let target_info =
+            YamlParser::find_target_at_position(&document.content, params.position).ok()??;
+        if interpolation::has_interpolation(&target_info.value) {
+            return None;
+        }
+        let segment = target_info.segment_at(params.position.character)?;
+        Some((target_info, segment))
+    }
+
+    /// Find what a segment of a `_target_` being renamed names in Python
+    /// Only modules and definitions in the workspace can be renamed, not installed packages
+    fn rename_subject(
+        &self,
+        uri: &Url,
+        target_info: &TargetInfo,
+        segment: &std::ops::Range<usize>,
+    ) -> std::result::Result<RenameSubject, String> {
+        let document_path = uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
         let python_interpreter = self.python_interpreter.read().clone();
+        let prefix = &target_info.value[..segment.end];
+
+        let subject = if let Ok(module_file) =
+            PythonAnalyzer::resolve_module(prefix, workspace_root, python_interpreter.as_deref())
+        {
+            RenameSubject::Module(module_file)
+        } else if segment.end == target_info.value.len() {
+            // Renaming the definition would break the import re-exporting it
+            let chain = PythonAnalyzer::reexport_chain(
+                prefix,
+                workspace_root,
+                python_interpreter.as_deref(),
+            );
+            if let Some(defining) = chain.last().filter(|_| chain.len() > 1) {
+                return Err(format!(
+                    "'{}' is re-exported from '{}', rename it in Python",
+                    prefix, defining
+                ));
+            }
+            let Some(location) =
+                Self::definition_location(prefix, workspace_root, python_interpreter.as_deref())
+            else {
+                return Err(format!("Cannot find the definition of '{}'", prefix));
+            };
+            RenameSubject::Symbol(location)
+        } else {
+            return Err(format!("Cannot find the module '{}'", prefix));
+        };
 
-        match YamlParser::parse(content) {
-            Ok((targets, _line_map)) => {
-                let diagnostics = diagnostics::validate_document(
-                    targets,
-                    workspace_root.as_deref(),
-                    python_interpreter.as_deref(),
-                );
+        let path = match &subject {
+            RenameSubject::Module(path) | RenameSubject::Symbol((path, _)) => path,
+        };
+        let path = fs::canonicalize(path).unwrap_or(path.clone());
+        let in_workspace = self
+            .workspace_folders
+            .read()
+            .iter()
+            .any(|folder| path.starts_with(fs::canonicalize(folder).unwrap_or(folder.clone())));
+        if !in_workspace {
+            return Err(format!("'{}' is defined outside the workspace", prefix));
+        }
+        Ok(subject)
+    }
+
+    /// Rewrite every target in the workspace written as `old_prefix` or a path under it, where
+    /// `old_prefix` resolves to the module at `module`, see `module_location`
+    async fn rename_prefix(
+        &self,
+        old_prefix: &str,
+        new_prefix: &str,
+        module: &Path,
+    ) -> HashMap<Url, Vec<TextEdit>> {
+        self.ensure_indexed().await;
+        let python_interpreter = self.python_interpreter.read().clone();
+
+        // The same path can name another module from a different config directory
+        let mut resolves_here: HashMap<PathBuf, bool> = HashMap::new();
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (config_path, indexed) in self.workspace_index.targets_with_prefix(old_prefix) {
+            let config_dir = config_path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let same_module = *resolves_here.entry(config_dir).or_insert_with_key(|dir| {
+                PythonAnalyzer::resolve_module(old_prefix, Some(dir), python_interpreter.as_deref())
+                    .is_ok_and(|module_file| module_location(&module_file) == module)
+            });
+            if !same_module {
+                continue;
+            }
+            let Ok(uri) = Url::from_file_path(&config_path) else {
+                continue;
+            };
+            changes.entry(uri).or_default().push(TextEdit {
+                range: name_range(
+                    indexed.line,
+                    indexed.value_start,
+                    indexed.value_start + old_prefix.len() as u32,
+                ),
+                new_text: new_prefix.to_string(),
+            });
+        }
+        changes
+    }
+
+    /// Rewrite the name of every target in the workspace that resolves to the definition at
+    /// `location`, failing if one of them reaches it through a re-export
+    async fn rename_symbol(
+        &self,
+        name: &str,
+        new_name: &str,
+        location: &(PathBuf, u32),
+    ) -> std::result::Result<HashMap<Url, Vec<TextEdit>>, String> {
+        self.ensure_indexed().await;
+        let python_interpreter = self.python_interpreter.read().clone();
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (config_path, indexed) in self.workspace_index.targets_named(name) {
+            // The text of an interpolated target isn't the path it resolves to
+            if indexed.interpolated {
+                continue;
+            }
+            let config_dir = config_path.parent();
+            let definition = Self::definition_location(
+                &indexed.target,
+                config_dir,
+                python_interpreter.as_deref(),
+            );
+            if definition.as_ref() != Some(location) {
+                continue;
+            }
+            let chain = PythonAnalyzer::reexport_chain(
+                &indexed.target,
+                config_dir,
+                python_interpreter.as_deref(),
+            );
+            if chain.len() > 1 {
+                return Err(format!(
+                    "'{}' is re-exported as '{}', rename it in Python",
+                    name, indexed.target
+                ));
+            }
+            let Ok(uri) = Url::from_file_path(&config_path) else {
+                continue;
+            };
+            changes.entry(uri).or_default().push(TextEdit {
+                range: name_range(
+                    indexed.line,
+                    indexed.value_end - name.len() as u32,
+                    indexed.value_end,
+                ),
+                new_text: new_name.to_string(),
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Get the path of a document that belongs in the workspace index
+    /// Documents outside the workspace folders, like composed configs, are left out
+    fn config_path(&self, uri: &Url) -> Option<PathBuf> {
+        let folders = self.workspace_folders.read();
+        uri.to_file_path()
+            .ok()
+            .filter(|path| WorkspaceIndex::is_workspace_config(path, &folders))
+    }
+
+    /// Get the semantic tokens of an open Hydra config, or of a range of it
+    fn document_tokens(
+        &self,
+        uri: &Url,
+        range: Option<&Range>,
+    ) -> Option<Vec<semantic_tokens::Token>> {
+        let document = self.documents.get(uri)?;
+        if !YamlParser::is_hydra_file(&document.content) {
+            return None;
+        }
+        let document_path = uri.to_file_path().ok();
+        let workspace_root = document_path.as_deref().and_then(Path::parent);
+        let python_interpreter = self.python_interpreter.read().clone();
+
+        // Configs often repeat a target, e.g. one block per layer
+        let mut kinds: HashMap<String, Option<SymbolKind>> = HashMap::new();
+        Some(semantic_tokens::tokens(
+            &document.content,
+            range,
+            |target| {
+                *kinds
+                    .entry(target.target_path().to_string())
+                    .or_insert_with(|| {
+                        Self::target_symbol_kind(
+                            target,
+                            workspace_root,
+                            python_interpreter.as_deref(),
+                        )
+                    })
+            },
+        ))
+    }
+
+    /// Get the signature a target is called with: a function's, or a class's `__init__`
+    fn target_signature(
+        target: &TargetInfo,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Option<FunctionSignature> {
+        match PythonAnalyzer::extract_definition_info(
+            target.target_path(),
+            workspace_root,
+            python_interpreter,
+        )
+        .ok()?
+        {
+            DefinitionInfo::Function(sig) => Some(sig),
+            DefinitionInfo::Class(class_info) => class_info.init_signature,
+        }
+    }
+
+    /// Build the edit adding the required parameters a target is missing, set to `???`
+    fn missing_parameters_edit(&self, uri: &Url, line: u32) -> Option<TextEdit> {
+        let document = self.documents.get(uri)?;
+        let outline = YamlParser::outline(&document.content);
+        let (block, mut target) = code_lens::target_at_line(&outline, line)?;
+        let document_path = uri.to_file_path().ok();
+        InterpolationResolver::new(&document.content, document_path.as_deref())
+            .resolve_target(&mut target);
+
+        let python_interpreter = self.python_interpreter.read().clone();
+        let signature = Self::target_signature(
+            &target,
+            document_path.as_deref().and_then(Path::parent),
+            python_interpreter.as_deref(),
+        )?;
+        let (_, missing) = code_lens::missing_parameters(&outline, block, &signature);
+        let parameters: Vec<(String, String)> = missing
+            .iter()
+            .map(|param| (param.name.clone(), MISSING_VALUE.to_string()))
+            .collect();
+        if parameters.is_empty() {
+            return None;
+        }
+        code_actions::insert_parameters(&document.content, &outline, block, &parameters)
+    }
+
+    /// Write the composed config of a document to a temporary file and open it at a target
+    async fn show_composed_config(&self, uri: &Url, line: u32) {
+        let composed = self.documents.get(uri).and_then(|document| {
+            let path = uri.to_file_path().ok()?;
+            let (yaml, block_line) = code_lens::composed_config(&document.content, &path, line)?;
+            // Named after the config and a hash of its path, so configs named alike don't clash
+            let mut hasher = DefaultHasher::new();
+            path.hash(&mut hasher);
+            let stem = path.file_stem()?.to_string_lossy().into_owned();
+            let file = std::env::temp_dir().join("hydra-lsp").join(format!(
+                "{}-{:x}.composed.yaml",
+                stem,
+                hasher.finish()
+            ));
+            fs::create_dir_all(file.parent()?).ok()?;
+            fs::write(&file, yaml).ok()?;
+            Some((Url::from_file_path(&file).ok()?, block_line))
+        });
+        match composed {
+            Some((composed_uri, block_line)) => {
+                let position = Position::new(block_line, 0);
+                self.show_document(composed_uri, Some(Range::new(position, position)))
+                    .await;
+            }
+            None => {
                 self.client
-                    .publish_diagnostics(uri.clone(), diagnostics, None)
+                    .show_message(
+                        MessageType::WARNING,
+                        "The config could not be composed with its defaults list",
+                    )
                     .await;
             }
-            Err(e) => {
-                // Publish YAML syntax error as diagnostic
-                let diagnostic = Diagnostic {
-                    range: Range {
-                        start: Position {
-                            line: 0,
-                            character: 0,
-                        },
-                        end: Position {
-                            line: 0,
-                            character: 0,
-                        },
-                    },
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: Some(tower_lsp::lsp_types::NumberOrString::String(
-                        "yaml-syntax-error".to_string(),
-                    )),
-                    source: Some("hydra-lsp".to_string()),
-                    message: format!("YAML syntax error: {}", e),
-                    ..Default::default()
+        }
+    }
+
+    /// Ask the client to open a document, logging clients that can't
+    async fn show_document(&self, uri: Url, selection: Option<Range>) {
+        let params = ShowDocumentParams {
+            uri,
+            external: Some(false),
+            take_focus: Some(true),
+            selection,
+        };
+        if let Err(error) = self.client.show_document(params).await {
+            self.client
+                .log_message(
+                    MessageType::ERROR,
+                    format!("Failed to open document: {}", error),
+                )
+                .await;
+        }
+    }
+
+    /// Tell whether a target is a class or a function, `None` if it can't be resolved
+    fn target_symbol_kind(
+        target: &TargetInfo,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Option<SymbolKind> {
+        match PythonAnalyzer::extract_definition_info(
+            target.target_path(),
+            workspace_root,
+            python_interpreter,
+        )
+        .ok()?
+        {
+            DefinitionInfo::Function(_) => Some(SymbolKind::FUNCTION),
+            DefinitionInfo::Class(_) => Some(SymbolKind::CLASS),
+        }
+    }
+
+    /// Find where a target is defined, following re-exports: the file and line of its name
+    fn definition_location(
+        target: &str,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Option<(PathBuf, u32)> {
+        let chain = PythonAnalyzer::reexport_chain(target, workspace_root, python_interpreter);
+        let defining = chain.last()?;
+        let (file_path, line) = match PythonAnalyzer::extract_definition_info(
+            defining,
+            workspace_root,
+            python_interpreter,
+        )
+        .ok()?
+        {
+            DefinitionInfo::Function(sig) => (sig.file_path, sig.line),
+            DefinitionInfo::Class(class_info) => (class_info.file_path, class_info.line),
+        };
+        Some((fs::canonicalize(&file_path).unwrap_or(file_path), line))
+    }
+
+    /// Ask the client to tell us about changes to files we read from disk
+    async fn register_file_watchers(&self) {
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String(format!("**/{}", PROJECT_CONFIG_FILE)),
+                    kind: None,
+                },
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/*.{yaml,yml}".to_string()),
+                    kind: None,
+                },
+            ],
+        };
+        let registration = Registration {
+            id: "hydra-lsp-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            *self.watching_files.write() = false;
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!(
+                        "Could not watch files, they are read again when needed: {}",
+                        e
+                    ),
+                )
+                .await;
+        }
+    }
+
+    /// Index the workspace folders on first use
+    /// Walking the workspace blocks on the file system, so keep it off the async runtime
+    async fn ensure_indexed(&self) {
+        if self.workspace_index.is_indexed() {
+            return;
+        }
+        let folders = self.workspace_folders.read().clone();
+        let workspace_index = self.workspace_index.clone();
+        if let Err(error) =
+            tokio::task::spawn_blocking(move || workspace_index.ensure_indexed(&folders)).await
+        {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Failed to index workspace: {}", error),
+                )
+                .await;
+        }
+    }
+
+    /// Get the rule settings for a document, and its path relative to its workspace folder
+    /// The project config file of the workspace folder is layered over the initialization options
+    async fn rule_settings_for(&self, uri: &Url) -> (RuleSettings, Option<PathBuf>) {
+        let mut settings = self.rule_settings.read().clone();
+        let Ok(path) = uri.to_file_path() else {
+            return (settings, None);
+        };
+        let folder = self
+            .workspace_folders
+            .read()
+            .iter()
+            .filter(|folder| path.starts_with(folder))
+            .max_by_key(|folder| folder.components().count())
+            .cloned();
+        let Some(folder) = folder else {
+            return (settings, None);
+        };
+
+        let cached = self
+            .project_settings
+            .get(&folder)
+            .map(|project| project.clone());
+        let project = match cached {
+            Some(project) => project,
+            None => {
+                let project = match RuleSettings::load_project(&folder) {
+                    Ok(project) => project,
+                    Err(e) => {
+                        self.client
+                            .log_message(
+                                MessageType::ERROR,
+                                format!("Invalid project config in {}: {}", folder.display(), e),
+                            )
+                            .await;
+                        None
+                    }
                 };
+                if *self.watching_files.read() {
+                    self.project_settings
+                        .insert(folder.clone(), project.clone());
+                }
+                project
+            }
+        };
+        if let Some(project) = project {
+            settings.merge(project);
+        }
 
-                self.client
-                    .publish_diagnostics(uri.clone(), vec![diagnostic], None)
-                    .await;
+        let relative_path = path.strip_prefix(&folder).ok().map(|p| p.to_path_buf());
+        (settings, relative_path)
+    }
+
+    /// Publish diagnostics for a document
+    async fn publish_diagnostics_for_document(&self, uri: &Url, content: &str) {
+        let workspace_root = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.parent().map(|p| p.to_path_buf()));
+
+        // Get the python interpreter path
+        let python_interpreter = self.python_interpreter.read().clone();
+
+        // Syntax errors are reported alongside diagnostics for the parts that still parse
+        let (mut targets, syntax_errors) = YamlParser::parse_with_recovery(content);
+        let document_path = uri.to_file_path().ok();
+        if interpolation::has_interpolation(content) {
+            let resolver = InterpolationResolver::new(content, document_path.as_deref());
+            for target in &mut targets {
+                resolver.resolve_target(target);
             }
         }
+        let mut diagnostics = diagnostics::syntax_error_diagnostics(&syntax_errors);
+
+        // The security rule set is opt-in, so targets are only followed through re-exports for it
+        let (rule_settings, relative_path) = self.rule_settings_for(uri).await;
+        let security_enabled = [&rules::DANGEROUS_TARGET, &rules::UNTRUSTED_TARGET_MODULE]
+            .into_iter()
+            .any(|rule| {
+                rule_settings.severity(rule, relative_path.as_deref()) != RuleSeverity::Off
+            });
+        if security_enabled {
+            diagnostics.extend(diagnostics::validate_security(
+                &targets,
+                &rule_settings.security.clone().unwrap_or_default(),
+                workspace_root.as_deref(),
+                python_interpreter.as_deref(),
+            ));
+        }
+
+        diagnostics.extend(diagnostics::validate_document(
+            targets,
+            workspace_root.as_deref(),
+            python_interpreter.as_deref(),
+        ));
+        diagnostics.extend(diagnostics::validate_convert_values(content));
+        diagnostics.extend(diagnostics::validate_mandatory_values(
+            content,
+            document_path.as_deref(),
+        ));
+        diagnostics::sort_by_position(&mut diagnostics);
+
+        // Drop anything silenced by `# hydra-lsp: ignore` comments, then apply configured severities
+        let diagnostics = diagnostics::apply_suppressions(diagnostics, content);
+        let diagnostics = rule_settings.apply(diagnostics, relative_path.as_deref());
+        self.client
+            .publish_diagnostics(uri.clone(), diagnostics, None)
+            .await;
     }
 }
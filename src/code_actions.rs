@@ -0,0 +1,618 @@
+use crate::interpolation;
+use crate::rules;
+use crate::yaml_parser::{OutlineEntry, YamlParser, ARGS_KEY, TARGET_KEY};
+use std::collections::HashMap;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, NumberOrString, Position, Range,
+    TextEdit, Url, WorkspaceEdit,
+};
+
+/// A fix's title and edits, and whether it is the one to apply for the diagnostic
+type Fix = (String, Vec<TextEdit>, bool);
+
+/// Build quick fixes for the diagnostics sent with a code action request
+/// `unresolved` finds the first segment of a module path that doesn't resolve, and `suggest`
+/// gives the names that could be meant for a segment of a target that doesn't resolve
+pub fn quick_fixes(
+    content: &str,
+    uri: &Url,
+    diagnostics: &[Diagnostic],
+    unresolved: impl Fn(&str) -> Option<usize>,
+    suggest: impl Fn(&str, usize) -> Vec<String>,
+) -> Vec<CodeActionOrCommand> {
+    let outline = YamlParser::outline(content);
+    diagnostics
+        .iter()
+        .flat_map(|diagnostic| {
+            let code = match &diagnostic.code {
+                Some(NumberOrString::String(code)) => code.as_str(),
+                _ => "",
+            };
+            let fixes: Vec<Fix> = if code == rules::POSITIONAL_ONLY_PARAMETER.code {
+                move_to_args(content, &outline, diagnostic)
+                    .map(|(title, edits)| (title, edits, true))
+                    .into_iter()
+                    .collect()
+            } else if code == rules::MISSING_PARAMETER.code {
+                add_missing_parameter(content, &outline, diagnostic)
+                    .into_iter()
+                    .collect()
+            } else if code == rules::UNKNOWN_PARAMETER.code {
+                fix_unknown_parameter(content, &outline, diagnostic)
+            } else if code == rules::SYMBOL_NOT_FOUND.code || code == rules::MODULE_NOT_FOUND.code {
+                replace_segment(content, diagnostic, &unresolved, &suggest)
+            } else {
+                Vec::new()
+            };
+            fixes.into_iter().map(|(title, edits, is_preferred)| {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), edits)])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(is_preferred),
+                    ..Default::default()
+                })
+            })
+        })
+        .collect()
+}
+
+/// Get the names a diagnostic suggests in its data, closest first
+fn candidates(diagnostic: &Diagnostic) -> Vec<String> {
+    diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| data.get("candidates"))
+        .and_then(|candidates| serde_json::from_value(candidates.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Create an edit that replaces a range
+fn edit(line: u32, start: u32, end_line: u32, end: u32, new_text: String) -> TextEdit {
+    TextEdit {
+        range: Range {
+            start: Position {
+                line,
+                character: start,
+            },
+            end: Position {
+                line: end_line,
+                character: end,
+            },
+        },
+        new_text,
+    }
+}
+
+/// Add parameters at the end of a `_target_` block, at the indentation of its keys
+/// Each parameter is a key and the YAML text of its value
+pub fn insert_parameters(
+    content: &str,
+    outline: &[OutlineEntry],
+    block: &[String],
+    parameters: &[(String, String)],
+) -> Option<TextEdit> {
+    let mut target_path = block.to_vec();
+    target_path.push(TARGET_KEY.to_string());
+    let target = outline.iter().find(|entry| entry.path == target_path)?;
+    let indent = target.key_start as usize;
+
+    // After the block's last entry, and any block scalar lines that continue it
+    let lines: Vec<&str> = content.lines().collect();
+    let last = outline
+        .iter()
+        .filter(|entry| entry.path.len() > block.len() && entry.path.starts_with(block))
+        .map(|entry| entry.line as usize)
+        .max()?;
+    let mut insert_line = last + 1;
+    while lines.get(insert_line).is_some_and(|line| {
+        !line.trim().is_empty() && line.len() - line.trim_start().len() > indent
+    }) {
+        insert_line += 1;
+    }
+
+    let new_text: String = parameters
+        .iter()
+        .map(|(key, value)| format!("{}{}: {}\n", " ".repeat(indent), key, value))
+        .collect();
+    if insert_line >= lines.len() && !content.ends_with('\n') {
+        // The last line has no line break to insert after
+        let end = lines.last().map_or(0, |line| line.len()) as u32;
+        let line = lines.len().saturating_sub(1) as u32;
+        let new_text = format!("\n{}", new_text.trim_end_matches('\n'));
+        return Some(edit(line, end, line, end, new_text));
+    }
+    let line = insert_line as u32;
+    Some(edit(line, 0, line, 0, new_text))
+}
+
+/// Move a positional-only parameter given by keyword into `_args_`
+/// Only offered when the value belongs at the end of `_args_`, since earlier positions are taken
+fn move_to_args(
+    content: &str,
+    outline: &[OutlineEntry],
+    diagnostic: &Diagnostic,
+) -> Option<(String, Vec<TextEdit>)> {
+    let start = diagnostic.range.start;
+    let entry = outline
+        .iter()
+        .find(|entry| entry.line == start.line && entry.key_start == start.character)?;
+    // Nested blocks and block scalars span several lines
+    if entry.value.is_empty() || entry.value.starts_with('|') || entry.value.starts_with('>') {
+        return None;
+    }
+    let position = diagnostic.data.as_ref()?.get("position")?.as_u64()? as usize;
+    let key = entry.path.last()?;
+    let title = format!("Move '{}' to _args_", key);
+
+    let mut args_path = entry.path[..entry.path.len() - 1].to_vec();
+    args_path.push(ARGS_KEY.to_string());
+    let Some(args) = outline.iter().find(|e| e.path == args_path) else {
+        // Without `_args_`, the key itself becomes `_args_` when this is the first position
+        if position != 0 {
+            return None;
+        }
+        let end = entry.value_start + entry.value.len() as u32;
+        let new_text = format!("{}: [{}]", ARGS_KEY, entry.value);
+        return Some((
+            title,
+            vec![edit(entry.line, entry.key_start, entry.line, end, new_text)],
+        ));
+    };
+
+    // The parameter's line is removed, unless it also holds a sequence item marker
+    let line = content.lines().nth(entry.line as usize)?;
+    if !line[..entry.key_start as usize].trim().is_empty() {
+        return None;
+    }
+    let remove_line = edit(entry.line, 0, entry.line + 1, 0, String::new());
+
+    if args.value.starts_with('[') {
+        // Flow sequence: `_args_: [a, b]`
+        let count = serde_yaml::from_str::<Vec<serde_yaml::Value>>(&args.value)
+            .ok()?
+            .len();
+        let close = args.value_start + args.value.rfind(']')? as u32;
+        if position != count {
+            return None;
+        }
+        let new_text = if count == 0 {
+            entry.value.clone()
+        } else {
+            format!(", {}", entry.value)
+        };
+        return Some((
+            title,
+            vec![
+                edit(args.line, close, args.line, close, new_text),
+                remove_line,
+            ],
+        ));
+    }
+
+    // Block sequence: a new `- value` item after the last one
+    let items: Vec<&OutlineEntry> = outline
+        .iter()
+        .filter(|e| e.path.len() == args_path.len() + 1 && e.path.starts_with(&args_path))
+        .collect();
+    let last = items.last()?;
+    if position != items.len() {
+        return None;
+    }
+    let insert_line = outline
+        .iter()
+        .find(|e| e.line > last.line && !e.path.starts_with(&last.path))
+        .map_or(content.lines().count() as u32, |e| e.line);
+    let item = format!("{}- {}\n", " ".repeat(last.key_start as usize), entry.value);
+    let edits = if insert_line == entry.line {
+        vec![edit(entry.line, 0, entry.line + 1, 0, item)]
+    } else {
+        vec![edit(insert_line, 0, insert_line, 0, item), remove_line]
+    };
+    Some((title, edits))
+}
+
+/// Insert a missing required parameter at the end of its target's block
+fn add_missing_parameter(
+    content: &str,
+    outline: &[OutlineEntry],
+    diagnostic: &Diagnostic,
+) -> Option<Fix> {
+    let data = diagnostic.data.as_ref()?;
+    let parameter = data.get("parameter")?.as_str()?;
+    let value = data.get("value")?.as_str()?;
+    // The diagnostic is on the target's `_target_` value
+    let target = outline.iter().find(|entry| {
+        entry.line == diagnostic.range.start.line
+            && entry.path.last().map(String::as_str) == Some(TARGET_KEY)
+    })?;
+    let block = &target.path[..target.path.len() - 1];
+    let edit = insert_parameters(
+        content,
+        outline,
+        block,
+        &[(parameter.to_string(), value.to_string())],
+    )?;
+    Some((
+        format!("Add missing parameter '{}'", parameter),
+        vec![edit],
+        true,
+    ))
+}
+
+/// Rename an unknown parameter to each suggested one, or remove it
+fn fix_unknown_parameter(
+    content: &str,
+    outline: &[OutlineEntry],
+    diagnostic: &Diagnostic,
+) -> Vec<Fix> {
+    let start = diagnostic.range.start;
+    let Some(index) = outline
+        .iter()
+        .position(|entry| entry.line == start.line && entry.key_start == start.character)
+    else {
+        return Vec::new();
+    };
+    let entry = &outline[index];
+    let key = entry.path.last().map(String::as_str).unwrap_or_default();
+
+    let mut fixes: Vec<Fix> = candidates(diagnostic)
+        .into_iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let rename = edit(
+                entry.line,
+                entry.key_start,
+                entry.line,
+                entry.key_end,
+                candidate.clone(),
+            );
+            (
+                format!("Rename '{}' to '{}'", key, candidate),
+                vec![rename],
+                i == 0,
+            )
+        })
+        .collect();
+    if let Some(remove) = remove_entry(content, outline, index) {
+        fixes.push((format!("Remove parameter '{}'", key), vec![remove], false));
+    }
+    fixes
+}
+
+/// Delete an entry's lines, with the entries nested under it and the lines continuing its value
+/// Entries sharing their line with a sequence item marker aren't removed, it would empty the item
+fn remove_entry(content: &str, outline: &[OutlineEntry], index: usize) -> Option<TextEdit> {
+    let entry = &outline[index];
+    let lines: Vec<&str> = content.lines().collect();
+    let line = lines.get(entry.line as usize)?;
+    if !line[..entry.key_start as usize].trim().is_empty() {
+        return None;
+    }
+    let last = outline[index + 1..]
+        .iter()
+        .take_while(|e| e.path.len() > entry.path.len() && e.path.starts_with(&entry.path))
+        .last()
+        .map_or(entry.line, |e| e.line) as usize;
+    let mut end_line = last + 1;
+    while lines.get(end_line).is_some_and(|line| {
+        !line.trim().is_empty() && line.len() - line.trim_start().len() > entry.key_start as usize
+    }) {
+        end_line += 1;
+    }
+
+    if end_line >= lines.len() && !content.ends_with('\n') && entry.line > 0 {
+        // The last line has no line break to remove, so the one before it goes instead
+        let start_line = entry.line - 1;
+        let start = lines[start_line as usize].len() as u32;
+        let end = lines.last().map_or(0, |line| line.len()) as u32;
+        let last_line = lines.len() as u32 - 1;
+        return Some(edit(start_line, start, last_line, end, String::new()));
+    }
+    Some(edit(entry.line, 0, end_line as u32, 0, String::new()))
+}
+
+/// Replace the segment of a target that doesn't resolve with each suggested name
+/// `symbol-not-found` is about the last segment, `module-not-found` about the first segment of
+/// the module path that doesn't resolve
+fn replace_segment(
+    content: &str,
+    diagnostic: &Diagnostic,
+    unresolved: impl Fn(&str) -> Option<usize>,
+    suggest: impl Fn(&str, usize) -> Vec<String>,
+) -> Vec<Fix> {
+    let range = diagnostic.range;
+    let Some(value) = content
+        .lines()
+        .nth(range.start.line as usize)
+        .and_then(|line| line.get(range.start.character as usize..range.end.character as usize))
+    else {
+        return Vec::new();
+    };
+    // Segments of interpolated targets aren't in the text
+    if interpolation::has_interpolation(value) {
+        return Vec::new();
+    }
+    let segments: Vec<&str> = value.split('.').collect();
+    let index = match &diagnostic.code {
+        Some(NumberOrString::String(code)) if code == rules::MODULE_NOT_FOUND.code => value
+            .rsplit_once('.')
+            .and_then(|(module_path, _)| unresolved(module_path)),
+        _ => segments.len().checked_sub(1),
+    };
+    let Some(index) = index.filter(|&index| index < segments.len()) else {
+        return Vec::new();
+    };
+    let offset: usize = segments[..index].iter().map(|s| s.len() + 1).sum();
+    let start = range.start.character + offset as u32;
+    let end = start + segments[index].len() as u32;
+
+    suggest(value, index)
+        .into_iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            (
+                format!("Change '{}' to '{}'", segments[index], candidate),
+                vec![edit(
+                    range.start.line,
+                    start,
+                    range.start.line,
+                    end,
+                    candidate,
+                )],
+                i == 0,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positional_only_diagnostic(line: u32, start: u32, key: &str, position: usize) -> Diagnostic {
+        Diagnostic {
+            range: Range {
+                start: Position {
+                    line,
+                    character: start,
+                },
+                end: Position {
+                    line,
+                    character: start + key.len() as u32,
+                },
+            },
+            code: Some(NumberOrString::String(
+                rules::POSITIONAL_ONLY_PARAMETER.code.to_string(),
+            )),
+            data: Some(serde_json::json!({ "position": position })),
+            ..Default::default()
+        }
+    }
+
+    fn fix_edits(content: &str, diagnostic: Diagnostic) -> Option<Vec<TextEdit>> {
+        let uri = Url::parse("file:///config.yaml").unwrap();
+        let action =
+            quick_fixes(content, &uri, &[diagnostic], |_| None, |_, _| Vec::new()).pop()?;
+        let CodeActionOrCommand::CodeAction(action) = action else {
+            return None;
+        };
+        action.edit?.changes?.remove(&uri)
+    }
+
+    fn diagnostic(
+        rule: &rules::Rule,
+        line: u32,
+        (start, end): (u32, u32),
+        data: serde_json::Value,
+    ) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            code: Some(NumberOrString::String(rule.code.to_string())),
+            data: Some(data),
+            ..Default::default()
+        }
+    }
+
+    /// Get each fix's title, edits and whether it is preferred
+    fn fixes(content: &str, diagnostic: Diagnostic) -> Vec<(String, Vec<TextEdit>, bool)> {
+        fixes_suggesting(content, diagnostic, |_| None, |_, _| Vec::new())
+    }
+
+    /// Like `fixes`, with the unresolved segments of module paths and the names suggested for
+    /// segments of targets
+    fn fixes_suggesting(
+        content: &str,
+        diagnostic: Diagnostic,
+        unresolved: impl Fn(&str) -> Option<usize>,
+        suggest: impl Fn(&str, usize) -> Vec<String>,
+    ) -> Vec<(String, Vec<TextEdit>, bool)> {
+        let uri = Url::parse("file:///config.yaml").unwrap();
+        quick_fixes(content, &uri, &[diagnostic], unresolved, suggest)
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some((
+                    action.title,
+                    action.edit?.changes?.remove(&uri)?,
+                    action.is_preferred?,
+                )),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_add_missing_parameter() {
+        let content = "model:\n  _target_: my.func\n  x: 1\nother: 2\n";
+        let data = serde_json::json!({ "parameter": "y", "value": "???" });
+        assert_eq!(
+            fixes(
+                content,
+                diagnostic(&rules::MISSING_PARAMETER, 1, (12, 19), data)
+            ),
+            vec![(
+                "Add missing parameter 'y'".to_string(),
+                vec![edit(3, 0, 3, 0, "  y: ???\n".to_string())],
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn test_fix_unknown_parameter() {
+        let content =
+            "model:\n  _target_: my.func\n  lerning_rate: 1\n  nested:\n    a: 1\nother: 2\n";
+        let data = serde_json::json!({ "candidates": ["learning_rate"] });
+        assert_eq!(
+            fixes(
+                content,
+                diagnostic(&rules::UNKNOWN_PARAMETER, 2, (2, 14), data)
+            ),
+            vec![
+                (
+                    "Rename 'lerning_rate' to 'learning_rate'".to_string(),
+                    vec![edit(2, 2, 2, 14, "learning_rate".to_string())],
+                    true
+                ),
+                (
+                    "Remove parameter 'lerning_rate'".to_string(),
+                    vec![edit(2, 0, 3, 0, String::new())],
+                    false
+                ),
+            ]
+        );
+
+        // Nested entries go with their key
+        let data = serde_json::json!({ "candidates": [] });
+        let fixes_for = |content: &str, line: u32| {
+            fixes(
+                content,
+                diagnostic(&rules::UNKNOWN_PARAMETER, line, (2, 8), data.clone()),
+            )
+        };
+        assert_eq!(
+            fixes_for(content, 3)[0].1,
+            vec![edit(3, 0, 5, 0, String::new())]
+        );
+
+        // Without a trailing line break, the one before the entry is removed
+        let content = "model:\n  _target_: my.func\n  extra: 1";
+        assert_eq!(
+            fixes_for(content, 2)[0].1,
+            vec![edit(1, 19, 2, 10, String::new())]
+        );
+    }
+
+    #[test]
+    fn test_replace_segment() {
+        let content = "model:\n  _target_: pkg.modls.Model\n";
+        let module_fixes = fixes_suggesting(
+            content,
+            diagnostic(
+                &rules::MODULE_NOT_FOUND,
+                1,
+                (12, 27),
+                serde_json::Value::Null,
+            ),
+            |module_path| {
+                assert_eq!(module_path, "pkg.modls");
+                Some(1)
+            },
+            |target, segment| {
+                assert_eq!((target, segment), ("pkg.modls.Model", 1));
+                vec!["models".to_string(), "modules".to_string()]
+            },
+        );
+        assert_eq!(
+            module_fixes
+                .iter()
+                .map(|(title, _, preferred)| (title.as_str(), *preferred))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Change 'modls' to 'models'", true),
+                ("Change 'modls' to 'modules'", false),
+            ]
+        );
+        assert_eq!(
+            module_fixes[0].1,
+            vec![edit(1, 16, 1, 21, "models".to_string())]
+        );
+
+        let content = "model:\n  _target_: pkg.models.Modle\n";
+        let symbol_fixes = fixes_suggesting(
+            content,
+            diagnostic(
+                &rules::SYMBOL_NOT_FOUND,
+                1,
+                (12, 28),
+                serde_json::Value::Null,
+            ),
+            |_| panic!("symbol-not-found is about the last segment"),
+            |target, segment| {
+                assert_eq!((target, segment), ("pkg.models.Modle", 2));
+                vec!["Model".to_string()]
+            },
+        );
+        assert_eq!(
+            symbol_fixes[0].1,
+            vec![edit(1, 23, 1, 28, "Model".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_move_to_new_args() {
+        let content = "model:\n  _target_: my.func\n  x: 1\n";
+        let edits = fix_edits(content, positional_only_diagnostic(2, 2, "x", 0)).unwrap();
+        assert_eq!(edits, vec![edit(2, 2, 2, 6, "_args_: [1]".to_string())]);
+
+        // Later positions can't be filled without the earlier ones
+        assert!(fix_edits(content, positional_only_diagnostic(2, 2, "x", 1)).is_none());
+    }
+
+    #[test]
+    fn test_move_to_existing_args() {
+        let flow = "model:\n  _target_: my.func\n  _args_: [1]\n  y: 2\n";
+        let edits = fix_edits(flow, positional_only_diagnostic(3, 2, "y", 1)).unwrap();
+        assert_eq!(
+            edits,
+            vec![
+                edit(2, 12, 2, 12, ", 2".to_string()),
+                edit(3, 0, 4, 0, String::new()),
+            ]
+        );
+
+        let block = "model:\n  _target_: my.func\n  _args_:\n    - 1\n  y: 2\n";
+        let edits = fix_edits(block, positional_only_diagnostic(4, 2, "y", 1)).unwrap();
+        assert_eq!(edits, vec![edit(4, 0, 5, 0, "    - 2\n".to_string())]);
+    }
+
+    #[test]
+    fn test_insert_parameters() {
+        let parameters = [
+            ("x".to_string(), "???".to_string()),
+            ("y".to_string(), "2".to_string()),
+        ];
+        let insert = |content: &str, block: &[&str]| {
+            let outline = YamlParser::outline(content);
+            let block: Vec<String> = block.iter().map(|key| key.to_string()).collect();
+            insert_parameters(content, &outline, &block, &parameters)
+        };
+
+        let content = "model:\n  _target_: my.func\n  doc: |\n    text\nother: 1\n";
+        assert_eq!(
+            insert(content, &["model"]),
+            Some(edit(4, 0, 4, 0, "  x: ???\n  y: 2\n".to_string()))
+        );
+
+        // Items are indented past their `-` marker
+        let content = "callbacks:\n  - _target_: my.func";
+        assert_eq!(
+            insert(content, &["callbacks", "0"]),
+            Some(edit(1, 21, 1, 21, "\n    x: ???\n    y: 2".to_string()))
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::composition;
+use crate::python_analyzer::{DefinitionInfo, FunctionSignature, ParameterInfo};
+use crate::yaml_parser::{OutlineEntry, TargetInfo, YamlParser, ARGS_KEY};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Position, Range, Url};
+
+/// Open a Python definition, with its `Location` as argument
+pub const OPEN_DEFINITION_COMMAND: &str = "hydra-lsp.openDefinition";
+/// Add a target's missing required parameters, with the document URI and target line as arguments
+pub const INSERT_MISSING_PARAMETERS_COMMAND: &str = "hydra-lsp.insertMissingParameters";
+/// Open the composed config at a target's block, with the document URI and target line as arguments
+pub const SHOW_COMPOSED_CONFIG_COMMAND: &str = "hydra-lsp.showComposedConfig";
+
+/// Commands the server executes, see `execute_command`
+pub const COMMANDS: [&str; 3] = [
+    OPEN_DEFINITION_COMMAND,
+    INSERT_MISSING_PARAMETERS_COMMAND,
+    SHOW_COMPOSED_CONFIG_COMMAND,
+];
+
+/// Which of a target's lenses a lens is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LensKind {
+    /// Where the target resolves to, opening it when clicked
+    Definition,
+    /// How many required parameters are set, inserting the missing ones when clicked
+    Parameters,
+    /// Opens the config composed with its defaults list
+    ComposedConfig,
+}
+
+/// What an unresolved lens needs to be resolved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LensData {
+    pub uri: Url,
+    /// Line of the `_target_` key
+    pub line: u32,
+    pub kind: LensKind,
+}
+
+/// Create the lenses above each `_target_`, without commands
+/// Resolving a target is expensive, so each lens is resolved when it is shown, see `LensData`
+pub fn code_lenses(content: &str, uri: &Url) -> Vec<CodeLens> {
+    let outline = YamlParser::outline(content);
+    let has_defaults = serde_yaml::from_str::<Value>(content)
+        .ok()
+        .as_ref()
+        .and_then(composition::defaults_list)
+        .is_some();
+
+    let mut lenses = Vec::new();
+    for (_, target) in YamlParser::outline_targets(&outline) {
+        let range = Range::new(
+            Position::new(target.line, target.key_start),
+            Position::new(target.line, target.value_end()),
+        );
+        let mut kinds = vec![LensKind::Definition, LensKind::Parameters];
+        if has_defaults {
+            kinds.push(LensKind::ComposedConfig);
+        }
+        for kind in kinds {
+            let data = LensData {
+                uri: uri.clone(),
+                line: target.line,
+                kind,
+            };
+            lenses.push(CodeLens {
+                range,
+                command: None,
+                data: serde_json::to_value(data).ok(),
+            });
+        }
+    }
+    lenses
+}
+
+/// Find the target whose `_target_` key is on a line, with the path of its block
+pub fn target_at_line(outline: &[OutlineEntry], line: u32) -> Option<(&[String], TargetInfo)> {
+    YamlParser::outline_targets(outline)
+        .into_iter()
+        .find(|(_, target)| target.line == line)
+}
+
+/// Get the number of required parameters of a signature, and those a block doesn't set
+/// Parameters bound by `_args_` items are set, in order
+pub fn missing_parameters<'a>(
+    outline: &[OutlineEntry],
+    block: &[String],
+    signature: &'a FunctionSignature,
+) -> (usize, Vec<&'a ParameterInfo>) {
+    let keys = YamlParser::block_keys(outline, block);
+    let args = keys
+        .iter()
+        .find(|entry| entry.path.last().map(String::as_str) == Some(ARGS_KEY))
+        .map_or(0, |args| {
+            YamlParser::sequence_item_positions(outline, args).len()
+        });
+    let bound: Vec<&str> = signature
+        .parameters
+        .iter()
+        .filter(|param| param.is_positional())
+        .take(args)
+        .map(|param| param.name.as_str())
+        .collect();
+
+    let required: Vec<&ParameterInfo> = signature
+        .parameters
+        .iter()
+        .filter(|param| param.is_required())
+        .collect();
+    let missing = required
+        .iter()
+        .copied()
+        .filter(|param| !bound.contains(&param.name.as_str()))
+        .filter(|param| {
+            !keys
+                .iter()
+                .any(|entry| entry.path.last() == Some(&param.name))
+        })
+        .collect();
+    (required.len(), missing)
+}
+
+/// Describe where a target resolves to, e.g. "class in myproject/models.py", opening it when
+/// clicked. Paths in a workspace folder are shown relative to it
+pub fn definition_command(
+    definition: Option<&DefinitionInfo>,
+    workspace_folders: &[PathBuf],
+) -> Command {
+    let Some(definition) = definition else {
+        return label("unresolved target".to_string());
+    };
+    let (kind, file_path, line, column, end_column) = match definition {
+        DefinitionInfo::Function(sig) => (
+            "function",
+            &sig.file_path,
+            sig.line,
+            sig.column,
+            sig.end_column,
+        ),
+        DefinitionInfo::Class(class_info) => (
+            "class",
+            &class_info.file_path,
+            class_info.line,
+            class_info.column,
+            class_info.end_column,
+        ),
+    };
+    let shown = workspace_folders
+        .iter()
+        .find_map(|folder| file_path.strip_prefix(folder).ok())
+        .unwrap_or(file_path);
+    let title = format!("{} in {}", kind, shown.display());
+    let Ok(uri) = Url::from_file_path(file_path) else {
+        return label(title);
+    };
+    let location = Location {
+        uri,
+        range: Range::new(Position::new(line, column), Position::new(line, end_column)),
+    };
+    Command {
+        title,
+        command: OPEN_DEFINITION_COMMAND.to_string(),
+        arguments: serde_json::to_value(location).ok().map(|value| vec![value]),
+    }
+}
+
+/// Count the required parameters a target sets, offering to insert the missing ones
+/// `counts` is the number of required and missing parameters, `None` without a signature
+pub fn parameters_command(data: &LensData, counts: Option<(usize, usize)>) -> Command {
+    let Some((required, missing)) = counts else {
+        return label("parameters not checked".to_string());
+    };
+    let set = required - missing;
+    if missing == 0 {
+        return label(format!("{}/{} required parameters", set, required));
+    }
+    Command {
+        title: format!(
+            "{}/{} required parameters, insert {} missing",
+            set, required, missing
+        ),
+        command: INSERT_MISSING_PARAMETERS_COMMAND.to_string(),
+        arguments: Some(vec![
+            serde_json::json!(data.uri),
+            serde_json::json!(data.line),
+        ]),
+    }
+}
+
+/// Open the composed config at the target's block
+pub fn composed_config_command(data: &LensData) -> Command {
+    Command {
+        title: "Show composed config".to_string(),
+        command: SHOW_COMPOSED_CONFIG_COMMAND.to_string(),
+        arguments: Some(vec![
+            serde_json::json!(data.uri),
+            serde_json::json!(data.line),
+        ]),
+    }
+}
+
+/// A lens that only shows its title
+fn label(title: String) -> Command {
+    Command {
+        title,
+        command: String::new(),
+        arguments: None,
+    }
+}
+
+/// Compose a config with its defaults list, as YAML with the line of the block holding the
+/// `_target_` at `line`
+pub fn composed_config(content: &str, config_path: &Path, line: u32) -> Option<(String, u32)> {
+    let document: Value = serde_yaml::from_str(content).ok()?;
+    let composition = composition::compose(&document, config_path.parent()?)?;
+    let outline = YamlParser::outline(content);
+    let (block, _) = target_at_line(&outline, line)?;
+
+    let header = format!(
+        "# Composed from {}, regenerated on each request\n",
+        config_path.display()
+    );
+    let yaml = serde_yaml::to_string(&composition.config).ok()?;
+    let block_line = YamlParser::outline(&yaml)
+        .iter()
+        .find(|entry| entry.path == block)
+        .map_or(0, |entry| entry.line);
+    Some((format!("{}{}", header, yaml), block_line + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_lenses() {
+        let uri = Url::parse("file:///conf/config.yaml").unwrap();
+        let content = "model:\n  _target_: pkg.Model\n";
+        let lenses = code_lenses(content, &uri);
+        let kinds: Vec<LensKind> = lenses
+            .iter()
+            .map(|lens| {
+                serde_json::from_value::<LensData>(lens.data.clone().unwrap())
+                    .unwrap()
+                    .kind
+            })
+            .collect();
+        assert_eq!(kinds, vec![LensKind::Definition, LensKind::Parameters]);
+        assert_eq!(lenses[0].range.start, Position::new(1, 2));
+        assert_eq!(lenses[0].range.end, Position::new(1, 21));
+
+        // Only configs with a defaults list have a composed config to show
+        let content = "defaults:\n  - _self_\nmodel:\n  _target_: pkg.Model\n";
+        assert_eq!(code_lenses(content, &uri).len(), 3);
+    }
+
+    #[test]
+    fn test_missing_parameters() {
+        let parameter = |name: &str, has_default: bool| ParameterInfo {
+            name: name.to_string(),
+            has_default,
+            ..Default::default()
+        };
+        let signature = FunctionSignature {
+            parameters: vec![
+                parameter("self", false),
+                parameter("in_features", false),
+                parameter("out_features", false),
+                parameter("bias", true),
+                parameter("device", false),
+            ],
+            ..Default::default()
+        };
+        let content = "layer:\n  _target_: torch.nn.Linear\n  _args_: [10]\n  device: cpu\n";
+        let outline = YamlParser::outline(content);
+        let (block, _) = target_at_line(&outline, 1).unwrap();
+        let (required, missing) = missing_parameters(&outline, block, &signature);
+        assert_eq!(required, 3);
+        assert_eq!(
+            missing.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["out_features"]
+        );
+    }
+
+    #[test]
+    fn test_composed_config() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("model")).unwrap();
+        std::fs::write(dir.path().join("model/small.yaml"), "layers: 2\n").unwrap();
+        let content =
+            "defaults:\n  - model: small\n  - _self_\nseed: 1\nmodel:\n  _target_: pkg.Model\n";
+        let config_path = dir.path().join("config.yaml");
+
+        let (yaml, line) = composed_config(content, &config_path, 5).unwrap();
+        let lines: Vec<&str> = yaml.lines().collect();
+        assert!(lines[0].starts_with("# Composed from"));
+        assert_eq!(lines[line as usize], "model:");
+        assert!(yaml.contains("layers: 2"));
+    }
+}
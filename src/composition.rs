@@ -0,0 +1,539 @@
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::Path;
+
+/// Key of the defaults list in a Hydra config
+pub const DEFAULTS_KEY: &str = "defaults";
+
+/// OmegaConf's marker for a mandatory value that has not been set
+pub const MISSING_VALUE: &str = "???";
+
+/// Maximum nesting of defaults lists, to guard against cycles
+const MAX_DEPTH: usize = 10;
+
+/// An entry of a Hydra defaults list
+#[derive(Debug, Clone, PartialEq)]
+pub enum DefaultsEntry {
+    /// `_self_`, the position at which the config's own content is merged
+    SelfEntry,
+    /// A config path such as `base` or `/db/mysql`, merged into the containing package
+    Config { path: String, optional: bool },
+    /// A config group selection such as `optional db@backup: mysql`
+    Group {
+        group: String,
+        package: Option<String>,
+        /// Selected options, several for a multi-select list and none for `null`
+        options: Vec<String>,
+        optional: bool,
+        is_override: bool,
+    },
+}
+
+impl DefaultsEntry {
+    /// Parse a single defaults list item
+    /// Example: `{"override optimizer@trainer.opt": "adam"}` -> Group { is_override: true, .. }
+    pub fn parse(item: &Value) -> Option<Self> {
+        match item {
+            Value::String(name) if name == "_self_" => Some(DefaultsEntry::SelfEntry),
+            Value::String(path) => Some(DefaultsEntry::Config {
+                path: path.clone(),
+                optional: false,
+            }),
+            Value::Mapping(mapping) if mapping.len() == 1 => {
+                let (key, value) = mapping.iter().next()?;
+                let mut words: Vec<&str> = key.as_str()?.split_whitespace().collect();
+                let name = words.pop()?;
+                let optional = words.contains(&"optional");
+                let is_override = words.contains(&"override");
+
+                let (group, package) = match name.split_once('@') {
+                    Some((group, package)) => (group, Some(package.to_string())),
+                    None => (name, None),
+                };
+                let options = match value {
+                    Value::String(option) => vec![option.clone()],
+                    Value::Sequence(options) => options
+                        .iter()
+                        .filter_map(|o| o.as_str().map(|o| o.to_string()))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                Some(DefaultsEntry::Group {
+                    group: group.to_string(),
+                    package,
+                    options,
+                    optional,
+                    is_override,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Get the defaults list of a config, if it has one
+pub fn defaults_list(config: &Value) -> Option<Vec<DefaultsEntry>> {
+    let items = config.get(DEFAULTS_KEY)?.as_sequence()?;
+    Some(items.iter().filter_map(DefaultsEntry::parse).collect())
+}
+
+/// Check if a value is OmegaConf's `???` marker
+pub fn is_missing(value: &Value) -> bool {
+    value.as_str() == Some(MISSING_VALUE)
+}
+
+/// Look up a value by key path, where sequence items are addressed by index
+pub fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |node, segment| match node {
+        Value::Sequence(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => node.get(segment.as_str()),
+    })
+}
+
+/// Merge `src` into `dst` the way `OmegaConf.merge` does
+/// Mappings are merged key by key and anything else replaces the destination, except that `???`
+/// never overwrites a value that is already there
+pub fn merge(dst: &mut Value, src: &Value) {
+    if let (Value::Mapping(dst), Value::Mapping(src)) = (&mut *dst, src) {
+        for (key, value) in src {
+            match dst.get_mut(key) {
+                Some(existing) => merge(existing, value),
+                None => {
+                    dst.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        return;
+    }
+    if !is_missing(src) {
+        *dst = src.clone();
+    }
+}
+
+/// Merge `src` into `dst` under a package path, creating the intermediate mappings
+fn merge_at(dst: &mut Value, package: &[String], src: &Value) {
+    let mut node = dst;
+    for segment in package {
+        if !node.is_mapping() {
+            *node = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(mapping) = node else {
+            unreachable!();
+        };
+        node = mapping
+            .entry(Value::String(segment.clone()))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+    }
+    merge(node, src);
+}
+
+/// Collect the key paths of every `???` value in a config
+fn missing_paths(value: &Value, prefix: &mut Vec<String>, paths: &mut Vec<Vec<String>>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                prefix.push(key.to_string());
+                missing_paths(value, prefix, paths);
+                prefix.pop();
+            }
+        }
+        value if is_missing(value) => paths.push(prefix.clone()),
+        _ => {}
+    }
+}
+
+/// Get the package declared by a `# @package` header comment
+fn package_header(content: &str) -> Option<&str> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .find_map(|line| {
+            line.trim_start_matches('#')
+                .trim()
+                .strip_prefix("@package ")
+        })
+        .map(|package| package.trim())
+}
+
+/// Split a dotted package into its segments
+fn package_segments(package: &str) -> Vec<String> {
+    package
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect()
+}
+
+/// `???` values contributed by a config in the defaults list
+#[derive(Debug, Clone)]
+pub struct DefaultsSource {
+    /// Index of the primary config's defaults entry that loaded the config
+    pub entry: usize,
+    /// Config name relative to the config directory, e.g. "model/resnet"
+    pub name: String,
+    /// Key paths of the `???` values, from the root of the composed config
+    pub missing: Vec<Vec<String>>,
+}
+
+/// The result of composing a config with its defaults list
+#[derive(Debug, Clone)]
+pub struct Composition {
+    pub config: Value,
+    /// Whether every non-optional default was found, so the result is what Hydra would build
+    pub complete: bool,
+    pub sources: Vec<DefaultsSource>,
+}
+
+impl Composition {
+    /// Check if a key path is still `???` in the composed config
+    pub fn is_unset(&self, path: &[String]) -> bool {
+        lookup(&self.config, path).is_some_and(is_missing)
+    }
+}
+
+/// Compose a config with its defaults list, resolving config groups in `config_dir`
+/// Returns `None` if the config has no defaults list
+pub fn compose(config: &Value, config_dir: &Path) -> Option<Composition> {
+    let entries = defaults_list(config)?;
+    let mut composer = Composer {
+        config_dir,
+        overrides: Vec::new(),
+        composition: Composition {
+            config: Value::Mapping(Mapping::new()),
+            complete: true,
+            sources: Vec::new(),
+        },
+    };
+    composer.compose_config(config, &entries, "", &[], None, 0);
+    Some(composer.composition)
+}
+
+struct Composer<'a> {
+    config_dir: &'a Path,
+    /// Group selections from `override` entries, applied wherever the group is selected
+    overrides: Vec<(String, Vec<String>)>,
+    composition: Composition,
+}
+
+impl Composer<'_> {
+    /// Merge a config and its defaults into the composition
+    /// `entry` is the index of the primary's defaults entry this config was loaded through
+    fn compose_config(
+        &mut self,
+        config: &Value,
+        entries: &[DefaultsEntry],
+        group: &str,
+        package: &[String],
+        entry: Option<(usize, &str)>,
+        depth: usize,
+    ) {
+        for item in entries {
+            if let DefaultsEntry::Group {
+                group: name,
+                options,
+                is_override: true,
+                ..
+            } = item
+            {
+                self.overrides
+                    .push((Self::group_path(group, name), options.clone()));
+            }
+        }
+
+        // Without `_self_`, the config's own content is merged after its defaults
+        let self_index = entries
+            .iter()
+            .position(|e| *e == DefaultsEntry::SelfEntry)
+            .unwrap_or(entries.len());
+
+        for (index, item) in entries.iter().enumerate() {
+            if index == self_index {
+                self.merge_own_content(config, package, entry);
+            }
+            // Entries of nested configs are attributed to the primary entry that loaded them
+            let entry = entry.map(|(index, _)| index).unwrap_or(index);
+            match item {
+                DefaultsEntry::SelfEntry => {}
+                DefaultsEntry::Config { path, optional } => {
+                    let full_path = Self::group_path(group, path);
+                    let parent_group = full_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+                    let mut child_package = package.to_vec();
+                    if let Some((dir, _)) = path.trim_start_matches('/').rsplit_once('/') {
+                        child_package.extend(package_segments(&dir.replace('/', ".")));
+                    }
+                    self.load(
+                        &full_path,
+                        parent_group,
+                        child_package,
+                        *optional,
+                        entry,
+                        depth,
+                    );
+                }
+                DefaultsEntry::Group {
+                    group: name,
+                    package: package_override,
+                    options,
+                    optional,
+                    is_override,
+                } => {
+                    let full_group = Self::group_path(group, name);
+                    // Hydra's own groups are not part of the user's config directory
+                    if *is_override || full_group == "hydra" || full_group.starts_with("hydra/") {
+                        continue;
+                    }
+                    let options = self
+                        .overrides
+                        .iter()
+                        .rev()
+                        .find(|(overridden, _)| *overridden == full_group)
+                        .map_or(options, |(_, options)| options)
+                        .clone();
+
+                    let child_package = match package_override.as_deref() {
+                        Some(explicit) if explicit.starts_with("_global_") => {
+                            package_segments(explicit.trim_start_matches("_global_"))
+                        }
+                        Some(explicit) => {
+                            let mut child = package.to_vec();
+                            child.extend(package_segments(explicit));
+                            child
+                        }
+                        None => {
+                            let mut child = package.to_vec();
+                            child.extend(package_segments(
+                                &name.trim_start_matches('/').replace('/', "."),
+                            ));
+                            child
+                        }
+                    };
+                    for option in options {
+                        // A `???` selection must be made on the command line
+                        if option == MISSING_VALUE {
+                            self.composition.complete = false;
+                            continue;
+                        }
+                        self.load(
+                            &format!("{}/{}", full_group, option),
+                            &full_group,
+                            child_package.clone(),
+                            *optional,
+                            entry,
+                            depth,
+                        );
+                    }
+                }
+            }
+        }
+
+        if self_index == entries.len() {
+            self.merge_own_content(config, package, entry);
+        }
+    }
+
+    /// Load a config from the config directory and compose it into the package
+    fn load(
+        &mut self,
+        name: &str,
+        group: &str,
+        package: Vec<String>,
+        optional: bool,
+        entry: usize,
+        depth: usize,
+    ) {
+        let path = self.config_dir.join(format!("{}.yaml", name));
+        let loaded = fs::read_to_string(&path).ok().and_then(|content| {
+            let value = serde_yaml::from_str::<Value>(&content).ok()?;
+            Some((value, package_header(&content).map(|p| p.to_string())))
+        });
+        let Some((config, header)) = loaded else {
+            if !optional {
+                self.composition.complete = false;
+            }
+            return;
+        };
+        if depth >= MAX_DEPTH {
+            self.composition.complete = false;
+            return;
+        }
+
+        // An explicit `# @package` header replaces the package derived from the group
+        let package = match header.as_deref() {
+            Some("_global_") => Vec::new(),
+            Some("_group_") => package_segments(&group.replace('/', ".")),
+            Some(header) => package_segments(header),
+            None => package,
+        };
+        let entries = defaults_list(&config).unwrap_or_default();
+        self.compose_config(
+            &config,
+            &entries,
+            group,
+            &package,
+            Some((entry, name)),
+            depth + 1,
+        );
+    }
+
+    /// Merge a config's own content, everything but its defaults list, into its package
+    fn merge_own_content(
+        &mut self,
+        config: &Value,
+        package: &[String],
+        entry: Option<(usize, &str)>,
+    ) {
+        let mut own = config.clone();
+        if let Value::Mapping(mapping) = &mut own {
+            mapping.remove(DEFAULTS_KEY);
+        }
+
+        if let Some((index, name)) = entry {
+            let mut missing = Vec::new();
+            missing_paths(&own, &mut package.to_vec(), &mut missing);
+            if !missing.is_empty() {
+                self.composition.sources.push(DefaultsSource {
+                    entry: index,
+                    name: name.to_string(),
+                    missing,
+                });
+            }
+        }
+        merge_at(&mut self.composition.config, package, &own);
+    }
+
+    /// Resolve a group or config path relative to the group of the config that lists it
+    /// Example: ("model", "encoder") -> "model/encoder", ("model", "/db") -> "db"
+    fn group_path(group: &str, name: &str) -> String {
+        match name.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None if group.is_empty() => name.to_string(),
+            None => format!("{}/{}", group, name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_dir::TempDir;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    fn path(dotted: &str) -> Vec<String> {
+        package_segments(dotted)
+    }
+
+    #[test]
+    fn test_parse_defaults_entries() {
+        let entries = defaults_list(&yaml(
+            r#"
+defaults:
+  - _self_
+  - base
+  - optional db@backup: mysql
+  - override hydra/launcher: joblib
+  - callbacks: [early_stopping, checkpoint]
+  - logger: null
+"#,
+        ))
+        .unwrap();
+
+        assert_eq!(entries[0], DefaultsEntry::SelfEntry);
+        assert_eq!(
+            entries[1],
+            DefaultsEntry::Config {
+                path: "base".to_string(),
+                optional: false
+            }
+        );
+        assert_eq!(
+            entries[2],
+            DefaultsEntry::Group {
+                group: "db".to_string(),
+                package: Some("backup".to_string()),
+                options: vec!["mysql".to_string()],
+                optional: true,
+                is_override: false,
+            }
+        );
+        assert!(matches!(
+            &entries[3],
+            DefaultsEntry::Group { is_override: true, group, .. } if group == "hydra/launcher"
+        ));
+        assert!(matches!(&entries[4], DefaultsEntry::Group { options, .. } if options.len() == 2));
+        assert!(matches!(&entries[5], DefaultsEntry::Group { options, .. } if options.is_empty()));
+    }
+
+    #[test]
+    fn test_merge_keeps_values_over_missing() {
+        let mut dst = yaml("a: 1\nb: {c: 2}\nd: ???\n");
+        merge(&mut dst, &yaml("a: ???\nb: {c: '???', e: 3}\nd: 4\n"));
+        assert_eq!(dst, yaml("a: 1\nb: {c: 2, e: 3}\nd: 4\n"));
+    }
+
+    #[test]
+    fn test_compose_with_groups() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("model")).unwrap();
+        fs::write(
+            dir.path().join("model/resnet.yaml"),
+            "depth: ???\nwidth: 64\nname: ???\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join("trainer")).unwrap();
+        fs::write(
+            dir.path().join("trainer/default.yaml"),
+            "# @package _global_\nseed: 1\nmax_epochs: ???\n",
+        )
+        .unwrap();
+
+        let config = yaml(
+            r#"
+defaults:
+  - model: resnet
+  - trainer: default
+  - _self_
+model:
+  depth: 50
+lr: ???
+"#,
+        );
+        let composition = compose(&config, dir.path()).unwrap();
+        assert!(composition.complete);
+        assert!(!composition.is_unset(&path("model.depth")));
+        assert!(composition.is_unset(&path("model.name")));
+        assert!(composition.is_unset(&path("max_epochs")));
+        assert!(composition.is_unset(&path("lr")));
+        assert_eq!(
+            lookup(&composition.config, &path("seed")),
+            Some(&Value::from(1))
+        );
+
+        assert_eq!(composition.sources.len(), 2);
+        assert_eq!(composition.sources[0].entry, 0);
+        assert_eq!(composition.sources[0].name, "model/resnet");
+        assert_eq!(
+            composition.sources[0].missing,
+            vec![path("model.depth"), path("model.name")]
+        );
+        assert_eq!(composition.sources[1].missing, vec![path("max_epochs")]);
+    }
+
+    #[test]
+    fn test_compose_incomplete() {
+        let dir = TempDir::new().unwrap();
+        let config = yaml("defaults:\n  - model: missing\n  - optional db: none\n");
+        let composition = compose(&config, dir.path()).unwrap();
+        assert!(!composition.complete);
+
+        let config = yaml("defaults:\n  - optional db: none\n");
+        assert!(compose(&config, dir.path()).unwrap().complete);
+        assert!(compose(&yaml("a: 1\n"), dir.path()).is_none());
+    }
+}
@@ -1,14 +1,28 @@
-use crate::python_analyzer::{DefinitionInfo, FunctionSignature, PythonAnalyzer};
-use crate::yaml_parser::TargetInfo;
-use std::collections::HashSet;
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use crate::annotation::Annotation;
+use crate::composition::{self, Composition, MISSING_VALUE};
+use crate::python_analyzer::{
+    DefinitionInfo, FunctionSignature, ParameterInfo, PythonAnalyzer, SymbolKind,
+};
+use crate::rules::{self, Rule, SecuritySettings};
+use crate::yaml_parser::{
+    ConvertMode, OutlineEntry, ParameterKind, ParameterValue, Suppression, SyntaxError, TargetInfo,
+    YamlParser, CONVERT_KEY,
+};
+use serde_yaml::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticTag, Location, NumberOrString, Position,
+    Range, Url,
+};
 
+/// Create a diagnostic for a rule, with the rule's default severity
+/// Configured severities are applied later, see `RuleSettings::apply`
 fn create_diagnostic(
     line: u32,
     start_char: u32,
     end_char: u32,
-    severity: DiagnosticSeverity,
-    code: Option<&str>,
+    rule: &Rule,
     message: String,
 ) -> Diagnostic {
     Diagnostic {
@@ -22,14 +36,59 @@ fn create_diagnostic(
                 character: end_char,
             },
         },
-        severity: Some(severity),
-        code: code.map(|c| tower_lsp::lsp_types::NumberOrString::String(c.to_string())),
+        severity: rule.severity.to_lsp(),
+        code: Some(NumberOrString::String(rule.code.to_string())),
         source: Some("hydra-lsp".to_string()),
         message,
         ..Default::default()
     }
 }
 
+/// Point related information at a line of a Python source file
+fn python_related_information(
+    file_path: &Path,
+    line: u32,
+    message: String,
+) -> Option<DiagnosticRelatedInformation> {
+    let uri = Url::from_file_path(file_path).ok()?;
+    let position = Position { line, character: 0 };
+    Some(DiagnosticRelatedInformation {
+        location: Location {
+            uri,
+            range: Range {
+                start: position,
+                end: position,
+            },
+        },
+        message,
+    })
+}
+
+/// Link a diagnostic to the signature it was checked against, and the parameter involved
+fn with_signature_information(
+    mut diagnostic: Diagnostic,
+    signature: &FunctionSignature,
+    parameter: Option<&ParameterInfo>,
+) -> Diagnostic {
+    let mut related = Vec::new();
+    if let Some(param) = parameter {
+        related.extend(python_related_information(
+            &signature.file_path,
+            param.line,
+            format!("Parameter '{}' is declared here", param.name),
+        ));
+    }
+    related.extend(python_related_information(
+        &signature.file_path,
+        signature.line,
+        format!("'{}' is defined here", signature.name),
+    ));
+    if !related.is_empty() {
+        diagnostic.related_information = Some(related);
+    }
+    diagnostic
+}
+
 /// Validate a Hydra configuration and generate diagnostics
 fn validate_target(
     target_info: &TargetInfo,
@@ -38,8 +97,24 @@ fn validate_target(
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
+    // Interpolations that depend on runtime values can't be checked statically
+    if target_info.is_dynamic() {
+        diagnostics.push(create_diagnostic(
+            target_info.line,
+            target_info.value_start,
+            target_info.value_end(),
+            &rules::DYNAMIC_TARGET,
+            format!(
+                "Dynamic target '{}' is only known at runtime and can't be checked",
+                target_info.value
+            ),
+        ));
+        return diagnostics;
+    }
+
     // Split target to validate format
-    let (module_path, symbol_name) = match PythonAnalyzer::split_target(&target_info.value) {
+    let target = target_info.target_path();
+    let (module_path, symbol_name) = match PythonAnalyzer::split_target(target) {
         Ok(parts) => parts,
         Err(_) => {
             // Invalid target format
@@ -47,11 +122,10 @@ fn validate_target(
                 target_info.line,
                 target_info.value_start,
                 target_info.value_end(),
-                DiagnosticSeverity::ERROR,
-                Some("invalid-target"),
+                &rules::INVALID_TARGET,
                 format!(
                     "Invalid _target_ format: '{}'. Expected format: 'module.path.SymbolName'",
-                    target_info.value
+                    target
                 ),
             ));
             return diagnostics;
@@ -61,28 +135,69 @@ fn validate_target(
     // Try to resolve the module to check if it exists
     match PythonAnalyzer::resolve_module(&module_path, workspace_root, python_interpreter) {
         Ok(file_path) => {
-            // Module resolved successfully, now try to find the symbol
-            let symbol_found =
-                match PythonAnalyzer::extract_function_signature(&file_path, &symbol_name) {
-                    Ok(_) => true,
-                    Err(_) => {
-                        // Not a function, try as a class
-                        PythonAnalyzer::extract_class_info(&file_path, &symbol_name).is_ok()
-                    }
-                };
+            // Module resolved successfully, now check what the symbol is
+            let kind = PythonAnalyzer::classify_symbol(
+                &file_path,
+                &symbol_name,
+                workspace_root,
+                python_interpreter,
+            );
+            let problem = match kind {
+                Some(SymbolKind::Function | SymbolKind::Unknown) => None,
+                Some(SymbolKind::Class(class)) if class.is_protocol => Some((
+                    &rules::ABSTRACT_CLASS,
+                    format!("Cannot instantiate protocol class '{}'", symbol_name),
+                )),
+                Some(SymbolKind::Class(class)) if class.is_abstract() => {
+                    let methods: Vec<String> = class
+                        .abstract_methods
+                        .iter()
+                        .map(|method| format!("'{}'", method))
+                        .collect();
+                    Some((
+                        &rules::ABSTRACT_CLASS,
+                        format!(
+                            "Cannot instantiate abstract class '{}' with abstract methods {}",
+                            symbol_name,
+                            methods.join(", ")
+                        ),
+                    ))
+                }
+                Some(SymbolKind::Class(_)) => None,
+                Some(SymbolKind::Module(_)) => Some(module_target(target)),
+                Some(SymbolKind::Constant) => Some((
+                    &rules::NOT_CALLABLE,
+                    format!(
+                        "'{}' is a constant in module '{}', not a class or function",
+                        symbol_name, module_path
+                    ),
+                )),
+                // A submodule that isn't imported by its package still can't be a target
+                None if PythonAnalyzer::resolve_module(
+                    target,
+                    workspace_root,
+                    python_interpreter,
+                )
+                .is_ok() =>
+                {
+                    Some(module_target(target))
+                }
+                None => Some((
+                    &rules::SYMBOL_NOT_FOUND,
+                    format!(
+                        "Symbol '{}' not found in module '{}'",
+                        symbol_name, module_path
+                    ),
+                )),
+            };
 
-            if !symbol_found {
-                // Module exists but symbol not found
+            if let Some((rule, message)) = problem {
                 diagnostics.push(create_diagnostic(
                     target_info.line,
                     target_info.value_start,
                     target_info.value_end(),
-                    DiagnosticSeverity::ERROR,
-                    Some("symbol-not-found"),
-                    format!(
-                        "Symbol '{}' not found in module '{}'",
-                        symbol_name, module_path
-                    ),
+                    rule,
+                    message,
                 ));
             }
         }
@@ -92,8 +207,7 @@ fn validate_target(
                 target_info.line,
                 target_info.value_start,
                 target_info.value_end(),
-                DiagnosticSeverity::ERROR,
-                Some("module-not-found"),
+                &rules::MODULE_NOT_FOUND,
                 format!("Cannot resolve module '{}': {}", module_path, err),
             ));
         }
@@ -102,6 +216,90 @@ fn validate_target(
     diagnostics
 }
 
+/// Find the index of the first segment of a module path that doesn't resolve
+/// Only computed for quick fixes, since each segment is resolved in turn
+pub fn unresolved_segment(
+    module_path: &str,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Option<usize> {
+    let parts: Vec<&str> = module_path.split('.').collect();
+    (0..parts.len()).find(|&i| {
+        let prefix = parts[..=i].join(".");
+        PythonAnalyzer::resolve_module(&prefix, workspace_root, python_interpreter).is_err()
+    })
+}
+
+/// Get the names that could be meant for a segment of a target that doesn't resolve, closest
+/// first: the symbols its module defines for the last segment, the package's modules otherwise
+/// Only computed for quick fixes, since listing modules and symbols reads the file system
+pub fn segment_candidates(
+    target: &str,
+    segment: usize,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Vec<String> {
+    let parts: Vec<&str> = target.split('.').collect();
+    let Some(name) = parts.get(segment) else {
+        return Vec::new();
+    };
+    let package = (segment > 0).then(|| parts[..segment].join("."));
+    let names = if segment + 1 == parts.len() {
+        package
+            .and_then(|module| {
+                PythonAnalyzer::resolve_module(&module, workspace_root, python_interpreter).ok()
+            })
+            .map(|module_file| PythonAnalyzer::module_symbols(&module_file))
+            .unwrap_or_default()
+    } else {
+        PythonAnalyzer::submodules(package.as_deref(), workspace_root, python_interpreter)
+    };
+    similar_names(name, names.iter().map(String::as_str))
+}
+
+/// Pick the names close enough to a mistyped one to be what was meant, closest first
+fn similar_names<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<String> {
+    const MAX_SUGGESTIONS: usize = 3;
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut similar: Vec<(usize, &str)> = candidates
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Count the characters to insert, delete or substitute to turn one name into another,
+/// ignoring case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The problem reported for a `_target_` that names a module
+fn module_target(target: &str) -> (&'static Rule, String) {
+    (
+        &rules::TARGET_IS_MODULE,
+        format!("'{}' is a module, not a class or function", target),
+    )
+}
+
 /// Validate parameters against a function signature
 fn validate_parameters(target_info: &TargetInfo, signature: &FunctionSignature) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
@@ -124,33 +322,105 @@ fn validate_parameters(target_info: &TargetInfo, signature: &FunctionSignature)
     // Check if function accepts **kwargs
     let has_kwargs = signature.parameters.iter().any(|p| p.is_variadic_keyword);
 
+    // Values in `_args_` fill the positional parameters in order
+    let positional: Vec<&ParameterInfo> = signature
+        .parameters
+        .iter()
+        .filter(|p| p.is_positional())
+        .collect();
+    let given_by_args: HashSet<&str> = positional
+        .iter()
+        .take(target_info.args)
+        .map(|p| p.name.as_str())
+        .collect();
+
+    // Positional-only parameters given by keyword fail at instantiation
+    for param in &target_info.parameters {
+        let Some(position) = positional
+            .iter()
+            .position(|p| p.is_positional_only && p.name == param.key)
+        else {
+            continue;
+        };
+        let mut diagnostic = create_diagnostic(
+            param.line,
+            target_info.key_start,
+            target_info.key_start + param.key.len() as u32,
+            &rules::POSITIONAL_ONLY_PARAMETER,
+            format!(
+                "Parameter '{}' of '{}' is positional-only and can't be passed by keyword, use _args_",
+                param.key, signature.name
+            ),
+        );
+        // The quick fix needs the position to know where the value goes in `_args_`
+        diagnostic.data = Some(serde_json::json!({ "position": position }));
+        diagnostics.push(with_signature_information(
+            diagnostic,
+            signature,
+            Some(positional[position]),
+        ));
+    }
+
     // Check for unknown parameters
     for param in &target_info.parameters {
         if !expected_params.contains(&param.key) && !has_kwargs {
-            diagnostics.push(create_diagnostic(
+            let mut diagnostic = create_diagnostic(
                 param.line,
                 target_info.key_start,
                 param.key.len() as u32 + target_info.key_start,
-                DiagnosticSeverity::ERROR,
-                Some("unknown-parameter"),
+                &rules::UNKNOWN_PARAMETER,
                 format!("Unknown parameter '{}' for '{}'", param.key, signature.name),
-            ));
+            );
+            // The quick fix renames the key to a parameter that isn't set yet
+            let unset = signature
+                .parameters
+                .iter()
+                .map(|p| p.name.as_str())
+                .filter(|name| expected_params.contains(*name) && !param_names.contains(*name));
+            diagnostic.data =
+                Some(serde_json::json!({ "candidates": similar_names(&param.key, unset) }));
+            diagnostics.push(with_signature_information(diagnostic, signature, None));
         }
     }
 
     // Check for missing required parameters
     for param in &signature.parameters {
-        if param.is_required() && !param_names.contains(&param.name) {
-            diagnostics.push(create_diagnostic(
+        if param.is_required()
+            && !param_names.contains(&param.name)
+            && !given_by_args.contains(param.name.as_str())
+        {
+            if target_info.partial {
+                // With _partial_: true, Hydra returns a functools.partial, so the
+                // parameter is expected to be supplied when the partial is called
+                diagnostics.push(create_diagnostic(
+                    target_info.line,
+                    target_info.value_start,
+                    target_info.value_end(),
+                    &rules::PARTIAL_PARAMETER,
+                    format!(
+                        "Parameter '{}' for '{}' is supplied at call time (_partial_: true)",
+                        param.name, signature.name
+                    ),
+                ));
+                continue;
+            }
+            let mut diagnostic = create_diagnostic(
                 target_info.line,
                 target_info.value_start,
                 target_info.value_end(),
-                DiagnosticSeverity::ERROR,
-                Some("missing-parameter"),
+                &rules::MISSING_PARAMETER,
                 format!(
                     "Missing required parameter '{}' for '{}'",
                     param.name, signature.name
                 ),
+            );
+            // The quick fix inserts the parameter, left for the user to fill in
+            let value = param.default_value.as_deref().unwrap_or(MISSING_VALUE);
+            diagnostic.data = Some(serde_json::json!({ "parameter": param.name, "value": value }));
+            diagnostics.push(with_signature_information(
+                diagnostic,
+                signature,
+                Some(param),
             ));
         }
     }
@@ -159,22 +429,21 @@ fn validate_parameters(target_info: &TargetInfo, signature: &FunctionSignature)
     if has_kwargs && !param_names.is_subset(&expected_params) {
         let unknown: Vec<_> = param_names.difference(&expected_params).collect();
         if !unknown.is_empty() {
-            diagnostics.retain(|d| {
-                !matches!(&d.code, Some(tower_lsp::lsp_types::NumberOrString::String(code)) if code == "unknown-parameter")
-            });
+            diagnostics.retain(|d| diagnostic_code(d) != Some(rules::UNKNOWN_PARAMETER.code));
 
             for param_name in unknown {
                 if let Some(param_value) =
                     target_info.parameters.iter().find(|p| p.key == *param_name)
                 {
-                    diagnostics.push(create_diagnostic(
+                    let diagnostic = create_diagnostic(
                         param_value.line,
                         target_info.key_start,
                         target_info.key_start + param_value.key.len() as u32,
-                        DiagnosticSeverity::HINT,
-                        None,
+                        &rules::KWARGS_PARAMETER,
                         format!("Parameter '{}' will be passed via **kwargs", param_name),
-                    ));
+                    );
+                    let kwargs = signature.parameters.iter().find(|p| p.is_variadic_keyword);
+                    diagnostics.push(with_signature_information(diagnostic, signature, kwargs));
                 }
             }
         }
@@ -183,44 +452,565 @@ fn validate_parameters(target_info: &TargetInfo, signature: &FunctionSignature)
     diagnostics
 }
 
-/// Validate all targets in a document
-pub fn validate_document(
-    targets: Vec<TargetInfo>,
-    workspace_root: Option<&std::path::Path>,
+/// Describe the type of a YAML value for messages
+fn yaml_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "list",
+        Value::Mapping(_) => "mapping",
+        Value::Tagged(_) => "tagged value",
+    }
+}
+
+/// Validate plain parameter values against the annotations in a signature
+fn validate_parameter_types(
+    target_info: &TargetInfo,
+    signature: &FunctionSignature,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for param in &target_info.parameters {
+        let Some(value) = param.kind.value() else {
+            continue;
+        };
+        // Interpolations and `???` are only known once the config is composed
+        if let Value::String(text) = value {
+            if text.contains("${") || text == "???" {
+                continue;
+            }
+        }
+        let Some(param_info) = signature.parameters.iter().find(|p| p.name == param.key) else {
+            continue;
+        };
+        let Some(type_annotation) = &param_info.type_annotation else {
+            continue;
+        };
+        // A block that isn't instantiated arrives as a config, not as the object it describes
+        if matches!(&param.kind, ParameterKind::Mapping(mapping) if mapping.is_raw_target()) {
+            let as_dict = target_info.convert != ConvertMode::None;
+            if Annotation::parse(type_annotation).accepts_config(as_dict) == Some(false) {
+                let diagnostic = create_diagnostic(
+                    param.line,
+                    target_info.key_start,
+                    target_info.key_start + param.key.len() as u32,
+                    &rules::INCOMPATIBLE_NESTED_TARGET,
+                    format!(
+                        "'{}' receives its config as a {} because of _recursive_: false, but the parameter expects '{}'",
+                        param.key,
+                        if as_dict { "dict" } else { "DictConfig" },
+                        type_annotation
+                    ),
+                );
+                diagnostics.push(with_signature_information(
+                    diagnostic,
+                    signature,
+                    Some(param_info),
+                ));
+            }
+            continue;
+        }
+        // `x: int = None` is a common shorthand for an optional parameter
+        if value.is_null() && param_info.default_value.as_deref() == Some("None") {
+            continue;
+        }
+        let annotation = Annotation::parse(type_annotation);
+        if annotation.accepts(value) != Some(false) {
+            // Without `_convert_`, mappings and sequences arrive as OmegaConf containers
+            if target_info.convert == ConvertMode::None
+                && annotation.needs_primitive_container(value)
+            {
+                let (container, primitive) = match value {
+                    Value::Sequence(_) => ("ListConfig", "list"),
+                    _ => ("DictConfig", "dict"),
+                };
+                let diagnostic = convert_diagnostic(
+                    target_info,
+                    param,
+                    format!(
+                        "'{}' expects '{}' but receives a {} with _convert_: none, set _convert_: partial to pass a plain {}",
+                        param.key, type_annotation, container, primitive
+                    ),
+                );
+                diagnostics.push(with_signature_information(
+                    diagnostic,
+                    signature,
+                    Some(param_info),
+                ));
+            }
+            continue;
+        }
+
+        let diagnostic = create_diagnostic(
+            param.line,
+            target_info.key_start,
+            target_info.key_start + param.key.len() as u32,
+            &rules::TYPE_MISMATCH,
+            format!(
+                "Value for '{}' is a {} but the parameter expects '{}'",
+                param.key,
+                yaml_type_name(value),
+                type_annotation
+            ),
+        );
+        diagnostics.push(with_signature_information(
+            diagnostic,
+            signature,
+            Some(param_info),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Create a warning for a container that `_convert_` leaves as an OmegaConf container
+fn convert_diagnostic(
+    target_info: &TargetInfo,
+    param: &ParameterValue,
+    message: String,
+) -> Diagnostic {
+    create_diagnostic(
+        param.line,
+        target_info.key_start,
+        target_info.key_start + param.key.len() as u32,
+        &rules::CONVERT_MISMATCH,
+        message,
+    )
+}
+
+/// Validate plain mappings given to parameters annotated with a dataclass or TypedDict
+/// OmegaConf builds the structure from the mapping's keys, so its fields are checked like the
+/// parameters of a target
+fn validate_structured_parameters(
+    target_info: &TargetInfo,
+    signature: &FunctionSignature,
+    workspace_root: Option<&Path>,
     python_interpreter: Option<&str>,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
-    for target in &targets {
-        let target_diagnostics = validate_target(target, workspace_root, python_interpreter);
-        diagnostics.extend(target_diagnostics);
+    for param in &target_info.parameters {
+        let ParameterKind::Mapping(mapping) = &param.kind else {
+            continue;
+        };
+        if mapping.is_raw_target() {
+            continue;
+        }
+        let Some(annotation) = signature
+            .parameters
+            .iter()
+            .find(|p| p.name == param.key)
+            .and_then(|p| p.type_annotation.as_deref())
+        else {
+            continue;
+        };
+        // `Optional[Config]` and `Config | None` still take a mapping
+        let structure = Annotation::parse(annotation)
+            .union_members()
+            .into_iter()
+            .find_map(|member| {
+                let Annotation::Name(name) = member else {
+                    return None;
+                };
+                match PythonAnalyzer::classify_symbol(
+                    &signature.file_path,
+                    &name,
+                    workspace_root,
+                    python_interpreter,
+                )? {
+                    SymbolKind::Class(class) => {
+                        let is_typed_dict = class.is_subclass_of(&name, "TypedDict");
+                        Some((class.structure?, is_typed_dict))
+                    }
+                    _ => None,
+                }
+            });
+        let Some((structure, is_typed_dict)) = structure else {
+            continue;
+        };
+
+        // A TypedDict is a plain dict at runtime, while a dataclass is only built with `object`
+        let convert = target_info.convert;
+        let suggestion = match (is_typed_dict, convert) {
+            (true, ConvertMode::None) => Some("partial"),
+            (false, ConvertMode::None | ConvertMode::Partial) => Some("object"),
+            _ => None,
+        };
+        if let Some(suggestion) = suggestion {
+            let param_info = signature.parameters.iter().find(|p| p.name == param.key);
+            let diagnostic = convert_diagnostic(
+                target_info,
+                param,
+                format!(
+                    "'{}' expects '{}' but receives a DictConfig with _convert_: {}, set _convert_: {}",
+                    param.key,
+                    annotation,
+                    convert.name(),
+                    suggestion
+                ),
+            );
+            diagnostics.push(with_signature_information(
+                diagnostic, signature, param_info,
+            ));
+        }
+
+        // The mapping stands in for a target, with missing fields reported at its key
+        let fields = TargetInfo {
+            value: param.key.clone(),
+            parameters: mapping.fields.clone(),
+            line: param.line,
+            key_start: mapping.key_start,
+            value_start: target_info.key_start,
+            convert,
+            ..Default::default()
+        };
+        diagnostics.extend(validate_parameters(&fields, &structure));
+        diagnostics.extend(validate_parameter_types(&fields, &structure));
+        diagnostics.extend(validate_structured_parameters(
+            &fields,
+            &structure,
+            workspace_root,
+            python_interpreter,
+        ));
+    }
+
+    diagnostics
+}
+
+/// Hint at `???` values given for parameters that already have a default
+fn validate_mandatory_parameters(
+    target_info: &TargetInfo,
+    signature: &FunctionSignature,
+) -> Vec<Diagnostic> {
+    target_info
+        .parameters
+        .iter()
+        .filter(|param| matches!(&param.kind, ParameterKind::Value(value) if composition::is_missing(value)))
+        .filter_map(|param| {
+            let param_info = signature
+                .parameters
+                .iter()
+                .find(|p| p.name == param.key && p.has_default)?;
+            let diagnostic = create_diagnostic(
+                param.line,
+                target_info.key_start,
+                target_info.key_start + param.key.len() as u32,
+                &rules::MANDATORY_WITH_DEFAULT,
+                format!(
+                    "Parameter '{}' has a default ({}), so it can be left out instead of being set to '{}'",
+                    param.key,
+                    param_info.default_value.as_deref().unwrap_or("..."),
+                    MISSING_VALUE
+                ),
+            );
+            Some(with_signature_information(
+                diagnostic,
+                signature,
+                Some(param_info),
+            ))
+        })
+        .collect()
+}
+
+/// Validate that a partial target fits the `Callable` annotation of the parameter it feeds
+/// The parameters it leaves must fit the callable's, and what it produces its return type
+fn validate_partial_callable(
+    target_info: &TargetInfo,
+    definition: &DefinitionInfo,
+    parent_signature: &FunctionSignature,
+    parent_param: &ParameterInfo,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let Some(annotation_text) = parent_param.type_annotation.as_deref() else {
+        return diagnostics;
+    };
+    let members = Annotation::parse(annotation_text).union_members();
+    let Some((arity, returns)) = members
+        .iter()
+        .find_map(|member| member.callable_signature())
+    else {
+        return diagnostics;
+    };
+
+    if let Some(produced) = incompatible_product(
+        definition,
+        returns,
+        parent_signature,
+        workspace_root,
+        python_interpreter,
+    ) {
+        let diagnostic = create_diagnostic(
+            target_info.line,
+            target_info.value_start,
+            target_info.value_end(),
+            &rules::INCOMPATIBLE_NESTED_TARGET,
+            format!(
+                "Partial '{}' produces '{}', which is not compatible with '{}' expected by parameter '{}'",
+                target_info.target_path(),
+                produced,
+                annotation_text,
+                parent_param.name
+            ),
+        );
+        diagnostics.push(with_signature_information(
+            diagnostic,
+            parent_signature,
+            Some(parent_param),
+        ));
+    }
+
+    // Only `Callable[[A, B], R]` constrains the parameters, `Callable[..., R]` accepts anything
+    let (Some(arity), Some(signature)) = (arity, instantiation_signature(definition)) else {
+        return diagnostics;
+    };
+    // Values in `_args_` bind the leading positional parameters, like keys bind by name
+    let supplied: HashSet<&str> = signature
+        .parameters
+        .iter()
+        .filter(|p| p.is_positional())
+        .take(target_info.args)
+        .map(|p| p.name.as_str())
+        .chain(
+            target_info
+                .parameters
+                .iter()
+                .map(|param| param.key.as_str()),
+        )
+        .collect();
+    let remaining: Vec<_> = signature
+        .parameters
+        .iter()
+        .filter(|p| p.name != "self" && !p.is_variadic && !p.is_variadic_keyword)
+        .filter(|p| !supplied.contains(p.name.as_str()))
+        .collect();
+
+    // Positional arguments fill the remaining non keyword-only parameters in order
+    let positional = remaining.iter().filter(|p| !p.is_keyword_only).count();
+    let has_varargs = signature.parameters.iter().any(|p| p.is_variadic);
+    let too_many_required = remaining
+        .iter()
+        .enumerate()
+        .any(|(index, p)| p.is_required() && (p.is_keyword_only || index >= arity));
+    let too_few_accepted = positional < arity && !has_varargs;
+
+    if too_many_required || too_few_accepted {
+        let names: Vec<_> = remaining.iter().map(|p| p.name.as_str()).collect();
+        diagnostics.push(create_diagnostic(
+            target_info.line,
+            target_info.value_start,
+            target_info.value_end(),
+            &rules::PARTIAL_SIGNATURE_MISMATCH,
+            format!(
+                "Partial '{}' leaves parameters ({}) but parameter '{}' expects a callable taking {} argument(s)",
+                signature.name,
+                names.join(", "),
+                parent_param.name,
+                arity
+            ),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Check that what a nested target produces fits the annotation of the parameter it feeds
+fn validate_nested_target_type(
+    target_info: &TargetInfo,
+    definition: &DefinitionInfo,
+    parent_signature: &FunctionSignature,
+    parent_param: &ParameterInfo,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Option<Diagnostic> {
+    let annotation = parent_param.type_annotation.as_deref()?;
+    let produced = incompatible_product(
+        definition,
+        &Annotation::parse(annotation),
+        parent_signature,
+        workspace_root,
+        python_interpreter,
+    )?;
 
-        // Try to resolve the target and validate parameters
-        if let Ok(definition_info) = PythonAnalyzer::extract_definition_info(
-            &target.value,
+    let diagnostic = create_diagnostic(
+        target_info.line,
+        target_info.value_start,
+        target_info.value_end(),
+        &rules::INCOMPATIBLE_NESTED_TARGET,
+        format!(
+            "Target '{}' produces '{}', which is not compatible with '{}' expected by parameter '{}'",
+            target_info.target_path(),
+            produced,
+            annotation,
+            parent_param.name
+        ),
+    );
+    Some(with_signature_information(
+        diagnostic,
+        parent_signature,
+        Some(parent_param),
+    ))
+}
+
+/// Get what a target produces when it doesn't fit an expected type from `parent_signature`'s
+/// file, `None` when it fits or can't be judged
+/// Only classes whose whole hierarchy could be followed are checked, to avoid false positives
+fn incompatible_product(
+    definition: &DefinitionInfo,
+    expected: &Annotation,
+    parent_signature: &FunctionSignature,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Option<String> {
+    // A class target produces an instance, a function target whatever its return annotation says
+    let (produced, file_path) = match definition {
+        DefinitionInfo::Class(class) => (class.name.clone(), &class.file_path),
+        DefinitionInfo::Function(signature) => {
+            match Annotation::parse(signature.return_type.as_deref()?) {
+                Annotation::Name(name) => (name, &signature.file_path),
+                _ => return None,
+            }
+        }
+    };
+    let Some(SymbolKind::Class(class)) =
+        PythonAnalyzer::classify_symbol(file_path, &produced, workspace_root, python_interpreter)
+    else {
+        return None;
+    };
+    if class.unresolved_bases {
+        return None;
+    }
+    let produced_name = produced.rsplit('.').next().unwrap_or(&produced);
+
+    for member in expected.union_members() {
+        let (Annotation::Name(expected) | Annotation::Generic { base: expected, .. }) = &member
+        else {
+            return None;
+        };
+        let short_name = member.short_name()?;
+        if matches!(short_name, "Any" | "object" | "Callable" | "type" | "Type")
+            || class.is_subclass_of(produced_name, short_name)
+        {
+            return None;
+        }
+        // Protocols match structurally, and local names like TypeVars can't be judged
+        match PythonAnalyzer::classify_symbol(
+            &parent_signature.file_path,
+            expected,
             workspace_root,
             python_interpreter,
         ) {
-            let signature = match definition_info {
-                DefinitionInfo::Function(sig) => sig,
-                DefinitionInfo::Class(class_info) => {
-                    // For classes, use the __init__ signature if available
-                    if let Some(init_sig) = class_info.init_signature {
-                        init_sig
-                    } else {
-                        // Class with no __init__, no parameters to validate
-                        continue;
-                    }
-                }
-            };
+            Some(SymbolKind::Class(expected_class)) if !expected_class.is_protocol => {}
+            // Unbound names are builtins, and dotted names from modules we can't resolve are
+            // still compared by name
+            None => {}
+            Some(SymbolKind::Unknown) if expected.contains('.') => {}
+            _ => return None,
+        }
+    }
+    Some(produced)
+}
+
+/// Create a warning for a deprecated symbol, tagged so editors strike it through
+fn deprecated_diagnostic(
+    line: u32,
+    start_char: u32,
+    end_char: u32,
+    rule: &Rule,
+    message: String,
+    reason: &str,
+) -> Diagnostic {
+    let message = if reason.is_empty() {
+        message
+    } else {
+        format!("{}: {}", message, reason)
+    };
+    let mut diagnostic = create_diagnostic(line, start_char, end_char, rule, message);
+    diagnostic.tags = Some(vec![DiagnosticTag::DEPRECATED]);
+    diagnostic
+}
+
+/// Report a deprecated target and any deprecated parameters it sets
+fn validate_deprecations(target_info: &TargetInfo, definition: &DefinitionInfo) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    // A class counts as deprecated if either the class or its __init__ is marked
+    let signature = instantiation_signature(definition);
+    let target_deprecation = match definition {
+        DefinitionInfo::Function(sig) => sig.deprecated.as_deref(),
+        DefinitionInfo::Class(class_info) => class_info
+            .deprecated
+            .as_deref()
+            .or(signature.and_then(|sig| sig.deprecated.as_deref())),
+    };
+    if let Some(reason) = target_deprecation {
+        diagnostics.push(deprecated_diagnostic(
+            target_info.line,
+            target_info.value_start,
+            target_info.value_end(),
+            &rules::DEPRECATED_TARGET,
+            format!("'{}' is deprecated", target_info.target_path()),
+            reason,
+        ));
+    }
+
+    let Some(signature) = signature else {
+        return diagnostics;
+    };
+    for param in &target_info.parameters {
+        let Some(reason) = signature
+            .parameters
+            .iter()
+            .find(|p| p.name == param.key)
+            .and_then(|p| p.deprecated.as_deref())
+        else {
+            continue;
+        };
+        diagnostics.push(deprecated_diagnostic(
+            param.line,
+            target_info.key_start,
+            target_info.key_start + param.key.len() as u32,
+            &rules::DEPRECATED_PARAMETER,
+            format!(
+                "Parameter '{}' of '{}' is deprecated",
+                param.key, signature.name
+            ),
+            reason,
+        ));
+    }
+
+    diagnostics
+}
+
+/// Get the signature used to instantiate a definition, if it has one
+fn instantiation_signature(definition: &DefinitionInfo) -> Option<&FunctionSignature> {
+    match definition {
+        DefinitionInfo::Function(sig) => Some(sig),
+        // For classes, use the __init__ signature if available
+        DefinitionInfo::Class(class_info) => class_info.init_signature.as_ref(),
+    }
+}
 
-            let parameter_diagnostics = validate_parameters(target, &signature);
-            diagnostics.extend(parameter_diagnostics);
+/// Map each nested target index to its parent target index and the parameter it feeds
+fn nested_target_parents(targets: &[TargetInfo]) -> HashMap<usize, (usize, &str)> {
+    let mut parents = HashMap::new();
+    for (index, target) in targets.iter().enumerate() {
+        for param in &target.parameters {
+            if let ParameterKind::NestedTargetIndex(nested) = param.kind {
+                parents.insert(nested, (index, param.key.as_str()));
+            }
         }
-        // If Python analysis fails, we've already added a basic validation diagnostic above
     }
+    parents
+}
 
-    // Sort all diagnostics by position for consistent ordering
+/// Sort diagnostics by their start position
+pub fn sort_by_position(diagnostics: &mut [Diagnostic]) {
     diagnostics.sort_by(|a, b| {
         a.range
             .start
@@ -228,24 +1018,405 @@ pub fn validate_document(
             .cmp(&b.range.start.line)
             .then_with(|| a.range.start.character.cmp(&b.range.start.character))
     });
+}
 
-    diagnostics
+/// Create diagnostics for the syntax errors found while parsing a document
+pub fn syntax_error_diagnostics(errors: &[SyntaxError]) -> Vec<Diagnostic> {
+    errors
+        .iter()
+        .map(|error| {
+            create_diagnostic(
+                error.line,
+                error.start,
+                error.end,
+                &rules::YAML_SYNTAX_ERROR,
+                format!("YAML syntax error: {}", error.message),
+            )
+        })
+        .collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::python_analyzer::ParameterInfo;
-    use crate::yaml_parser::{ParameterKind, ParameterValue};
-    use std::path::PathBuf;
+/// Get the range of a defaults list item, from its `-` marker to the end of its value
+fn defaults_entry_range(outline: &[OutlineEntry], index: usize) -> Option<(u32, u32, u32)> {
+    let index = index.to_string();
+    let mut entries = outline.iter().filter(|entry| {
+        entry.path.len() >= 2
+            && entry.path[0] == composition::DEFAULTS_KEY
+            && entry.path[1] == index
+    });
+    let item = entries.next()?;
+    // `- model: resnet` ends with the value of its key, `- base` with its own value
+    let end = match entries.next() {
+        Some(key) if key.line == item.line => key.value_start + key.value.len() as u32,
+        _ => item.value_start + item.value.len() as u32,
+    };
+    Some((item.line, item.key_start, end))
+}
 
-    fn get_test_resources_dir() -> PathBuf {
-        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources")
-    }
+/// Check if an outline entry is a `???` value, quoted or not
+fn is_missing_entry(entry: &OutlineEntry) -> bool {
+    entry.value.trim_matches(|c| c == '"' || c == '\'') == MISSING_VALUE
+}
 
-    // ==================== validate_parameters tests ====================
+/// Report `???` mandatory values of a document
+/// When the config's defaults list can be composed from the document's directory, values left
+/// unset by the composition are warnings. Otherwise every `???` is an informational marker, as it
+/// may be set by a config or the command line that we can't see
+pub fn validate_mandatory_values(content: &str, document_path: Option<&Path>) -> Vec<Diagnostic> {
+    let outline = YamlParser::outline(content);
+    let config: Option<Value> = serde_yaml::from_str(content).ok();
+    let composition: Option<Composition> = config.as_ref().and_then(|config| {
+        let config_dir = document_path?.parent()?;
+        composition::compose(config, config_dir)
+    });
 
-    #[test]
+    let Some(composition) = composition.filter(|c| c.complete) else {
+        return outline
+            .iter()
+            .filter(|entry| is_missing_entry(entry))
+            .map(|entry| {
+                create_diagnostic(
+                    entry.line,
+                    entry.value_start,
+                    entry.value_start + entry.value.len() as u32,
+                    &rules::MANDATORY_VALUE,
+                    format!(
+                        "'{}' is a mandatory value and must be set when the config is used",
+                        entry.path.join(".")
+                    ),
+                )
+            })
+            .collect();
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = outline
+        .iter()
+        .filter(|entry| is_missing_entry(entry) && composition.is_unset(&entry.path))
+        .map(|entry| {
+            create_diagnostic(
+                entry.line,
+                entry.value_start,
+                entry.value_start + entry.value.len() as u32,
+                &rules::MISSING_MANDATORY_VALUE,
+                format!(
+                    "Mandatory value '{}' is not set by the defaults list",
+                    entry.path.join(".")
+                ),
+            )
+        })
+        .collect();
+
+    // `???` values from configs in the defaults list are reported at the entry that loads them
+    for source in &composition.sources {
+        let unset: Vec<String> = source
+            .missing
+            .iter()
+            .filter(|path| composition.is_unset(path))
+            .map(|path| format!("'{}'", path.join(".")))
+            .collect();
+        if unset.is_empty() {
+            continue;
+        }
+        let Some((line, start, end)) = defaults_entry_range(&outline, source.entry) else {
+            continue;
+        };
+        diagnostics.push(create_diagnostic(
+            line,
+            start,
+            end,
+            &rules::MISSING_MANDATORY_VALUE,
+            format!(
+                "'{}' leaves mandatory values unset: {}",
+                source.name,
+                unset.join(", ")
+            ),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Flag targets that resolve to dangerous callables, directly or through re-exports, and targets
+/// from modules outside the allowlist
+pub fn validate_security(
+    targets: &[TargetInfo],
+    settings: &SecuritySettings,
+    workspace_root: Option<&Path>,
+    python_interpreter: Option<&str>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for target in targets.iter().filter(|target| !target.is_dynamic()) {
+        let path = target.target_path();
+        let diagnostic = |rule: &Rule, message: String| {
+            create_diagnostic(
+                target.line,
+                target.value_start,
+                target.value_end(),
+                rule,
+                message,
+            )
+        };
+
+        if !settings.is_allowed(path) {
+            diagnostics.push(diagnostic(
+                &rules::UNTRUSTED_TARGET_MODULE,
+                format!(
+                    "'{}' is outside the allowed modules: {}",
+                    path,
+                    settings.allowed_modules.join(", ")
+                ),
+            ));
+        }
+
+        let chain = PythonAnalyzer::reexport_chain(path, workspace_root, python_interpreter);
+        let denied = chain
+            .iter()
+            .find_map(|name| Some((name, settings.denied(name)?)));
+        if let Some((name, entry)) = denied {
+            let message = if name == path {
+                format!("'{}' matches '{}' on the security denylist", path, entry)
+            } else {
+                format!(
+                    "'{}' resolves to '{}', which matches '{}' on the security denylist",
+                    path, name, entry
+                )
+            };
+            diagnostics.push(diagnostic(&rules::DANGEROUS_TARGET, message));
+        }
+    }
+
+    diagnostics
+}
+
+/// Report `_convert_` values that aren't one of Hydra's conversion modes
+pub fn validate_convert_values(content: &str) -> Vec<Diagnostic> {
+    YamlParser::outline(content)
+        .iter()
+        .filter(|entry| entry.path.last().map(String::as_str) == Some(CONVERT_KEY))
+        .filter_map(|entry| {
+            let value = entry.value.trim_matches(|c| c == '"' || c == '\'');
+            if value.is_empty() || ConvertMode::parse(value).is_some() {
+                return None;
+            }
+            Some(create_diagnostic(
+                entry.line,
+                entry.value_start,
+                entry.value_start + entry.value.len() as u32,
+                &rules::INVALID_CONVERT,
+                format!(
+                    "Unknown _convert_ mode '{}', expected one of {} (Hydra falls back to none)",
+                    value,
+                    ConvertMode::NAMES.join(", ")
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Validate all targets in a document
+pub fn validate_document(
+    targets: Vec<TargetInfo>,
+    workspace_root: Option<&std::path::Path>,
+    python_interpreter: Option<&str>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    // Resolve every target up front so nested targets can be checked against their parents
+    let definitions: Vec<Option<DefinitionInfo>> = targets
+        .iter()
+        .map(|target| {
+            if target.is_dynamic() {
+                return None;
+            }
+            PythonAnalyzer::extract_definition_info(
+                target.target_path(),
+                workspace_root,
+                python_interpreter,
+            )
+            .ok()
+        })
+        .collect();
+    let parents = nested_target_parents(&targets);
+
+    for (index, target) in targets.iter().enumerate() {
+        let target_diagnostics = validate_target(target, workspace_root, python_interpreter);
+        diagnostics.extend(target_diagnostics);
+
+        if let Some(definition) = &definitions[index] {
+            diagnostics.extend(validate_deprecations(target, definition));
+
+            // A nested target must produce something the parameter it feeds accepts,
+            let parent = parents
+                .get(&index)
+                .and_then(|&(parent_index, parent_param)| {
+                    let signature = definitions[parent_index]
+                        .as_ref()
+                        .and_then(instantiation_signature)?;
+                    let param = signature
+                        .parameters
+                        .iter()
+                        .find(|p| p.name == parent_param)?;
+                    Some((signature, param))
+                });
+            // and a partial feeding a `Callable` parameter must fit the callable
+            match parent {
+                Some((parent_signature, parent_param)) if target.partial => {
+                    diagnostics.extend(validate_partial_callable(
+                        target,
+                        definition,
+                        parent_signature,
+                        parent_param,
+                        workspace_root,
+                        python_interpreter,
+                    ));
+                }
+                Some((parent_signature, parent_param)) => {
+                    diagnostics.extend(validate_nested_target_type(
+                        target,
+                        definition,
+                        parent_signature,
+                        parent_param,
+                        workspace_root,
+                        python_interpreter,
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        // If Python analysis fails, we've already added a basic validation diagnostic above
+        // A class with no __init__ has no parameters to validate
+        let Some(signature) = definitions[index]
+            .as_ref()
+            .and_then(instantiation_signature)
+        else {
+            continue;
+        };
+
+        let parameter_diagnostics = validate_parameters(target, signature);
+        diagnostics.extend(parameter_diagnostics);
+        diagnostics.extend(validate_parameter_types(target, signature));
+        diagnostics.extend(validate_mandatory_parameters(target, signature));
+        diagnostics.extend(validate_structured_parameters(
+            target,
+            signature,
+            workspace_root,
+            python_interpreter,
+        ));
+    }
+
+    // Sort all diagnostics by position for consistent ordering
+    sort_by_position(&mut diagnostics);
+
+    diagnostics
+}
+
+/// Get the string code of a diagnostic, if it has one
+fn diagnostic_code(diagnostic: &Diagnostic) -> Option<&str> {
+    match diagnostic.code.as_ref()? {
+        NumberOrString::String(code) => Some(code),
+        NumberOrString::Number(_) => None,
+    }
+}
+
+/// Create a hint for a suppression comment that did not suppress anything
+fn unused_suppression(suppression: &Suppression, message: String) -> Diagnostic {
+    let mut diagnostic = create_diagnostic(
+        suppression.line,
+        suppression.start,
+        suppression.end,
+        &rules::UNUSED_SUPPRESSION,
+        message,
+    );
+    diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+    diagnostic
+}
+
+/// Apply `# hydra-lsp: ignore` comments in the document to its diagnostics
+/// Suppressions that match no diagnostic are reported as unused
+pub fn apply_suppressions(diagnostics: Vec<Diagnostic>, content: &str) -> Vec<Diagnostic> {
+    if let Some(ignore_file) = YamlParser::find_ignore_file_comment(content) {
+        if diagnostics.is_empty() {
+            return vec![unused_suppression(
+                &ignore_file,
+                "Unused suppression: no diagnostics in this file".to_string(),
+            )];
+        }
+        return Vec::new();
+    }
+
+    let suppressions = YamlParser::find_suppressions(content);
+    if suppressions.is_empty() {
+        return diagnostics;
+    }
+
+    let mut used: HashSet<(usize, Option<&str>)> = HashSet::new();
+    let mut result: Vec<Diagnostic> = diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            let code = diagnostic_code(diagnostic);
+            let lines = diagnostic.range.start.line..=diagnostic.range.end.line;
+            let matched = suppressions.iter().enumerate().find_map(|(index, s)| {
+                if !lines.contains(&s.line) {
+                    return None;
+                }
+                if s.codes.is_empty() {
+                    return Some((index, None));
+                }
+                s.codes
+                    .iter()
+                    .find(|c| Some(c.as_str()) == code)
+                    .map(|c| (index, Some(c.as_str())))
+            });
+            match matched {
+                Some(key) => {
+                    used.insert(key);
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect();
+
+    for (index, suppression) in suppressions.iter().enumerate() {
+        if suppression.codes.is_empty() {
+            if !used.contains(&(index, None)) {
+                result.push(unused_suppression(
+                    suppression,
+                    "Unused suppression: no diagnostics on this line".to_string(),
+                ));
+            }
+            continue;
+        }
+        for code in &suppression.codes {
+            if !used.contains(&(index, Some(code.as_str()))) {
+                result.push(unused_suppression(
+                    suppression,
+                    format!("Unused suppression: no '{}' diagnostic on this line", code),
+                ));
+            }
+        }
+    }
+
+    sort_by_position(&mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::{ParameterKind, ParameterValue};
+    use std::path::PathBuf;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    fn get_test_resources_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources")
+    }
+
+    // ==================== validate_parameters tests ====================
+
+    #[test]
     fn test_validate_missing_required_param() {
         let target_info = TargetInfo {
             value: "my.Class".to_string(),
@@ -253,6 +1424,7 @@ mod tests {
             line: 0,
             key_start: 0,
             value_start: 0,
+            ..Default::default()
         };
 
         let signature = FunctionSignature {
@@ -266,6 +1438,7 @@ mod tests {
                     is_variadic: false,
                     is_variadic_keyword: false,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
                 ParameterInfo {
                     name: "required_param".to_string(),
@@ -275,10 +1448,12 @@ mod tests {
                     is_variadic: false,
                     is_variadic_keyword: false,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
             ],
             return_type: None,
             docstring: None,
+            ..Default::default()
         };
 
         let diagnostics = validate_parameters(&target_info, &signature);
@@ -286,6 +1461,10 @@ mod tests {
         assert!(diagnostics[0]
             .message
             .contains("Missing required parameter"));
+        assert_eq!(
+            diagnostics[0].data,
+            Some(serde_json::json!({ "parameter": "required_param", "value": "???" }))
+        );
         assert_eq!(
             diagnostics[0].code,
             Some(tower_lsp::lsp_types::NumberOrString::String(
@@ -295,95 +1474,623 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_unknown_param_without_kwargs() {
-        let params = vec![ParameterValue {
-            kind: ParameterKind::Value(serde_yaml::Value::Null),
-            line: 1,
-            key: "unknown_param".to_string(),
-        }];
+    fn test_validate_unknown_param_without_kwargs() {
+        let params = vec![ParameterValue {
+            kind: ParameterKind::Value(serde_yaml::Value::Null),
+            line: 1,
+            key: "unknown_param".to_string(),
+        }];
+
+        let target_info = TargetInfo {
+            value: "my.Class".to_string(),
+            parameters: params,
+            line: 0,
+            key_start: 0,
+            value_start: 0,
+            ..Default::default()
+        };
+
+        let signature = FunctionSignature {
+            name: "Class".to_string(),
+            parameters: vec![ParameterInfo {
+                name: "self".to_string(),
+                type_annotation: None,
+                default_value: None,
+                has_default: false,
+                is_variadic: false,
+                is_variadic_keyword: false,
+                is_keyword_only: false,
+                ..Default::default()
+            }],
+            return_type: None,
+            docstring: None,
+            ..Default::default()
+        };
+
+        let diagnostics = validate_parameters(&target_info, &signature);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("Unknown parameter"));
+        assert_eq!(
+            diagnostics[0].code,
+            Some(tower_lsp::lsp_types::NumberOrString::String(
+                "unknown-parameter".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_param_with_kwargs() {
+        let params = vec![ParameterValue {
+            kind: ParameterKind::Value(serde_yaml::Value::Null),
+            line: 1,
+            key: "any_param".to_string(),
+        }];
+
+        let target_info = TargetInfo {
+            value: "my.Class".to_string(),
+            parameters: params,
+            line: 0,
+            key_start: 0,
+            value_start: 0,
+            ..Default::default()
+        };
+
+        let signature = FunctionSignature {
+            name: "Class".to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "self".to_string(),
+                    type_annotation: None,
+                    default_value: None,
+                    has_default: false,
+                    is_variadic: false,
+                    is_variadic_keyword: false,
+                    is_keyword_only: false,
+                    ..Default::default()
+                },
+                ParameterInfo {
+                    name: "**kwargs".to_string(),
+                    type_annotation: None,
+                    default_value: None,
+                    has_default: false,
+                    is_variadic: false,
+                    is_variadic_keyword: true,
+                    is_keyword_only: false,
+                    ..Default::default()
+                },
+            ],
+            return_type: None,
+            docstring: None,
+            ..Default::default()
+        };
+
+        let diagnostics = validate_parameters(&target_info, &signature);
+        // Should be a HINT, not ERROR
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.severity == Some(DiagnosticSeverity::HINT)));
+        assert!(diagnostics.iter().any(|d| d.message.contains("**kwargs")));
+    }
+
+    #[test]
+    fn test_validate_dynamic_target() {
+        let target_info = TargetInfo {
+            value: "myproject.models.${oc.env:MODEL}".to_string(),
+            value_start: 10,
+            ..Default::default()
+        };
+
+        let diagnostics = validate_target(&target_info, None, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("dynamic-target".to_string()))
+        );
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(DiagnosticSeverity::INFORMATION)
+        );
+    }
+
+    #[test]
+    fn test_validate_positional_only_params() {
+        let signature = FunctionSignature {
+            name: "function".to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "a".to_string(),
+                    is_positional_only: true,
+                    ..Default::default()
+                },
+                ParameterInfo {
+                    name: "b".to_string(),
+                    is_positional_only: true,
+                    ..Default::default()
+                },
+                ParameterInfo {
+                    name: "c".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        // `a` is filled by _args_, `b` is given by keyword and `c` is an ordinary parameter
+        let content = "_target_: my.function\n_args_: [1]\nb: 2\nc: 3\n";
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        assert_eq!(targets[0].args, 1);
+
+        let diagnostics = validate_parameters(&targets[0], &signature);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostic_code(&diagnostics[0]),
+            Some(rules::POSITIONAL_ONLY_PARAMETER.code)
+        );
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(
+            diagnostics[0].data,
+            Some(serde_json::json!({ "position": 1 }))
+        );
+    }
+
+    #[test]
+    fn test_validate_partial_missing_param_is_hint() {
+        let target_info = TargetInfo {
+            value: "my.function".to_string(),
+            partial: true,
+            ..Default::default()
+        };
+
+        let signature = FunctionSignature {
+            name: "function".to_string(),
+            parameters: vec![ParameterInfo {
+                name: "x".to_string(),
+                type_annotation: Some("int".to_string()),
+                default_value: None,
+                has_default: false,
+                is_variadic: false,
+                is_variadic_keyword: false,
+                is_keyword_only: false,
+                ..Default::default()
+            }],
+            return_type: None,
+            docstring: None,
+            ..Default::default()
+        };
+
+        let diagnostics = validate_parameters(&target_info, &signature);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert!(diagnostics[0].message.contains("supplied at call time"));
+        assert_eq!(
+            diagnostics[0].code,
+            Some(tower_lsp::lsp_types::NumberOrString::String(
+                "partial-parameter".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_partial_callable_arity() {
+        let target_info = TargetInfo {
+            value: "my.function".to_string(),
+            partial: true,
+            ..Default::default()
+        };
+
+        let required = |name: &str| ParameterInfo {
+            name: name.to_string(),
+            type_annotation: None,
+            default_value: None,
+            has_default: false,
+            is_variadic: false,
+            is_variadic_keyword: false,
+            is_keyword_only: false,
+            ..Default::default()
+        };
+        let signature = FunctionSignature {
+            name: "function".to_string(),
+            parameters: vec![required("a"), required("b")],
+            return_type: None,
+            docstring: None,
+            ..Default::default()
+        };
+
+        let definition = DefinitionInfo::Function(signature);
+        let validate = |annotation: &str| {
+            let parent_param = ParameterInfo {
+                name: "fn".to_string(),
+                type_annotation: Some(annotation.to_string()),
+                ..Default::default()
+            };
+            validate_partial_callable(
+                &target_info,
+                &definition,
+                &FunctionSignature::default(),
+                &parent_param,
+                None,
+                None,
+            )
+        };
+
+        // Two remaining parameters fit a callable taking two arguments
+        assert!(validate("Callable[[int, int], float]").is_empty());
+
+        // Any parameters fit `Callable[..., X]`
+        assert!(validate("Optional[Callable[..., float]]").is_empty());
+
+        // Two required parameters cannot be filled by a single argument
+        let diagnostics = validate("Callable[[int], float]");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert!(diagnostics[0].message.contains("(a, b)"));
+
+        // A value in `_args_` binds `a`, leaving one parameter for the single argument
+        let target_info = TargetInfo {
+            args: 1,
+            ..target_info
+        };
+        let parent_param = ParameterInfo {
+            name: "fn".to_string(),
+            type_annotation: Some("Callable[[int], float]".to_string()),
+            ..Default::default()
+        };
+        let diagnostics = validate_partial_callable(
+            &target_info,
+            &definition,
+            &FunctionSignature::default(),
+            &parent_param,
+            None,
+            None,
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_validate_document_partial_return_type() {
+        let content = r#"concrete:
+  _target_: test_targets.schedule
+  make:
+    _target_: test_targets.ConcreteModel
+    _partial_: true
+adam:
+  _target_: test_targets.schedule
+  make:
+    _target_: test_targets.Adam
+    _partial_: true
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let resources_dir = get_test_resources_dir();
+        let diagnostics = validate_document(targets, Some(&resources_dir), None);
+        let incompatible: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| diagnostic_code(d) == Some("incompatible-nested-target"))
+            .collect();
+
+        // `Callable[..., Optimizer]` takes any parameters, but must return an `Optimizer`
+        assert_eq!(
+            incompatible.len(),
+            1,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(incompatible[0].range.start.line, 3);
+        assert_eq!(
+            incompatible[0].message,
+            "Partial 'test_targets.ConcreteModel' produces 'ConcreteModel', which is not compatible with 'Callable[..., Optimizer]' expected by parameter 'make'"
+        );
+    }
+
+    #[test]
+    fn test_validate_convert_mismatch() {
+        let signature = FunctionSignature {
+            name: "func".to_string(),
+            parameters: vec![
+                ParameterInfo {
+                    name: "weights".to_string(),
+                    type_annotation: Some("dict[str, float]".to_string()),
+                    ..Default::default()
+                },
+                ParameterInfo {
+                    name: "layers".to_string(),
+                    type_annotation: Some("Sequence[int]".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let content = "_target_: my.func\nweights: {a: 1.0}\nlayers: [1, 2]\n";
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let diagnostics = validate_parameter_types(&targets[0], &signature);
+        assert_eq!(
+            diagnostics.len(),
+            1,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(diagnostic_code(&diagnostics[0]), Some("convert-mismatch"));
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert!(diagnostics[0].message.contains("_convert_: partial"));
+
+        let converted = format!("_convert_: partial\n{}", content);
+        let (targets, _) = YamlParser::parse(&converted).unwrap();
+        assert!(validate_parameter_types(&targets[0], &signature).is_empty());
+    }
+
+    #[test]
+    fn test_validate_non_recursive_nested_target() {
+        let param = |name: &str, annotation: &str| ParameterInfo {
+            name: name.to_string(),
+            type_annotation: Some(annotation.to_string()),
+            ..Default::default()
+        };
+        let signature = FunctionSignature {
+            name: "Trainer".to_string(),
+            parameters: vec![
+                param("optimizer", "Optimizer"),
+                param("scheduler", "DictConfig | None"),
+            ],
+            ..Default::default()
+        };
+
+        let content = r#"_target_: my.Trainer
+_recursive_: false
+optimizer:
+  _target_: my.Adam
+scheduler:
+  _target_: my.Cosine
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let diagnostics = validate_parameter_types(&targets[0], &signature);
+        assert_eq!(
+            diagnostics.len(),
+            1,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(
+            diagnostic_code(&diagnostics[0]),
+            Some("incompatible-nested-target")
+        );
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(
+            diagnostics[0].message,
+            "'optimizer' receives its config as a DictConfig because of _recursive_: false, but the parameter expects 'Optimizer'"
+        );
+    }
+
+    #[test]
+    fn test_validate_security() {
+        let settings: SecuritySettings =
+            serde_yaml::from_str("allowed_modules: [myproject, os]").unwrap();
+        let content = r#"cmd:
+  _target_: os.system
+  command: ls
+model:
+  _target_: myproject.Model
+other:
+  _target_: thirdparty.Thing
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let summary: Vec<_> = validate_security(&targets, &settings, None, None)
+            .iter()
+            .map(|d| (d.range.start.line, diagnostic_code(d).unwrap().to_string()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "dangerous-target".to_string()),
+                (6, "untrusted-target-module".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_convert_values() {
+        let content =
+            "model:\n  _target_: my.func\n  _convert_: dict\nother:\n  _convert_: 'ALL'\n";
+        let diagnostics = validate_convert_values(content);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostic_code(&diagnostics[0]), Some("invalid-convert"));
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(diagnostics[0].range.start.character, 13);
+    }
 
+    #[test]
+    fn test_validate_parameter_types() {
+        let value = |text: &str| ParameterKind::Value(serde_yaml::from_str(text).unwrap());
         let target_info = TargetInfo {
-            value: "my.Class".to_string(),
-            parameters: params,
+            value: "my.func".to_string(),
+            parameters: vec![
+                ParameterValue {
+                    key: "lr".to_string(),
+                    kind: value("fast"),
+                    line: 1,
+                },
+                ParameterValue {
+                    key: "layers".to_string(),
+                    kind: value("${model.layers}"),
+                    line: 2,
+                },
+                ParameterValue {
+                    key: "name".to_string(),
+                    kind: value("???"),
+                    line: 3,
+                },
+                ParameterValue {
+                    key: "seed".to_string(),
+                    kind: value("null"),
+                    line: 4,
+                },
+            ],
             line: 0,
-            key_start: 0,
-            value_start: 0,
+            key_start: 2,
+            value_start: 12,
+            ..Default::default()
         };
 
+        let param = |name: &str, annotation: &str, default: Option<&str>| ParameterInfo {
+            name: name.to_string(),
+            type_annotation: Some(annotation.to_string()),
+            default_value: default.map(|d| d.to_string()),
+            has_default: default.is_some(),
+            ..Default::default()
+        };
         let signature = FunctionSignature {
-            name: "Class".to_string(),
-            parameters: vec![ParameterInfo {
-                name: "self".to_string(),
-                type_annotation: None,
-                default_value: None,
-                has_default: false,
-                is_variadic: false,
-                is_variadic_keyword: false,
-                is_keyword_only: false,
-            }],
-            return_type: None,
-            docstring: None,
+            name: "func".to_string(),
+            parameters: vec![
+                param("lr", "float", None),
+                param("layers", "int", None),
+                param("name", "str", None),
+                param("seed", "int", Some("None")),
+            ],
+            ..Default::default()
         };
 
-        let diagnostics = validate_parameters(&target_info, &signature);
-        assert_eq!(diagnostics.len(), 1);
-        assert!(diagnostics[0].message.contains("Unknown parameter"));
+        let diagnostics = validate_parameter_types(&target_info, &signature);
         assert_eq!(
-            diagnostics[0].code,
-            Some(tower_lsp::lsp_types::NumberOrString::String(
-                "unknown-parameter".to_string()
-            ))
+            diagnostics.len(),
+            1,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic_code(&diagnostics[0]), Some("type-mismatch"));
+        assert_eq!(
+            diagnostics[0].message,
+            "Value for 'lr' is a string but the parameter expects 'float'"
         );
     }
 
     #[test]
-    fn test_validate_unknown_param_with_kwargs() {
-        let params = vec![ParameterValue {
-            kind: ParameterKind::Value(serde_yaml::Value::Null),
-            line: 1,
-            key: "any_param".to_string(),
-        }];
-
+    fn test_validate_mandatory_parameters() {
+        let value = |text: &str| ParameterKind::Value(serde_yaml::from_str(text).unwrap());
         let target_info = TargetInfo {
-            value: "my.Class".to_string(),
-            parameters: params,
-            line: 0,
-            key_start: 0,
-            value_start: 0,
+            value: "my.func".to_string(),
+            parameters: vec![
+                ParameterValue {
+                    key: "name".to_string(),
+                    kind: value("???"),
+                    line: 1,
+                },
+                ParameterValue {
+                    key: "value".to_string(),
+                    kind: value("???"),
+                    line: 2,
+                },
+            ],
+            key_start: 2,
+            ..Default::default()
         };
-
         let signature = FunctionSignature {
-            name: "Class".to_string(),
+            name: "func".to_string(),
             parameters: vec![
                 ParameterInfo {
-                    name: "self".to_string(),
-                    type_annotation: None,
-                    default_value: None,
-                    has_default: false,
-                    is_variadic: false,
-                    is_variadic_keyword: false,
-                    is_keyword_only: false,
+                    name: "name".to_string(),
+                    ..Default::default()
                 },
                 ParameterInfo {
-                    name: "**kwargs".to_string(),
-                    type_annotation: None,
-                    default_value: None,
-                    has_default: false,
-                    is_variadic: false,
-                    is_variadic_keyword: true,
-                    is_keyword_only: false,
+                    name: "value".to_string(),
+                    default_value: Some("0".to_string()),
+                    has_default: true,
+                    ..Default::default()
                 },
             ],
-            return_type: None,
-            docstring: None,
+            ..Default::default()
         };
 
-        let diagnostics = validate_parameters(&target_info, &signature);
-        // Should be a HINT, not ERROR
-        assert!(diagnostics
+        let diagnostics = validate_mandatory_parameters(&target_info, &signature);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(
+            diagnostic_code(&diagnostics[0]),
+            Some("mandatory-with-default")
+        );
+    }
+
+    // ==================== validate_mandatory_values tests ====================
+
+    #[test]
+    fn test_validate_mandatory_values_standalone() {
+        let content = "model:\n  depth: ???\n  name: \"???\"\nlr: 0.1\n";
+        let diagnostics = validate_mandatory_values(content, None);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(diagnostics[0].range.start.character, 9);
+        assert_eq!(diagnostics[0].range.end.character, 12);
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(DiagnosticSeverity::INFORMATION)
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "'model.depth' is a mandatory value and must be set when the config is used"
+        );
+        assert_eq!(diagnostic_code(&diagnostics[1]), Some("mandatory-value"));
+    }
+
+    #[test]
+    fn test_validate_mandatory_values_composed() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("model")).unwrap();
+        std::fs::write(
+            dir.path().join("model/resnet.yaml"),
+            "depth: ???\nname: ???\n",
+        )
+        .unwrap();
+        let content = r#"defaults:
+  - model: resnet
+  - _self_
+
+model:
+  depth: 50
+lr: ???
+"#;
+        let document_path = dir.path().join("config.yaml");
+        let diagnostics = validate_mandatory_values(content, Some(&document_path));
+
+        assert_eq!(
+            diagnostics.len(),
+            2,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        let lr = diagnostics
             .iter()
-            .any(|d| d.severity == Some(DiagnosticSeverity::HINT)));
-        assert!(diagnostics.iter().any(|d| d.message.contains("**kwargs")));
+            .find(|d| d.range.start.line == 6)
+            .unwrap();
+        assert_eq!(lr.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(
+            lr.message,
+            "Mandatory value 'lr' is not set by the defaults list"
+        );
+
+        let entry = diagnostics
+            .iter()
+            .find(|d| d.range.start.line == 1)
+            .unwrap();
+        assert_eq!(entry.range.start.character, 2);
+        assert_eq!(entry.range.end.character, 17);
+        assert_eq!(diagnostic_code(entry), Some("missing-mandatory-value"));
+        assert_eq!(
+            entry.message,
+            "'model/resnet' leaves mandatory values unset: 'model.name'"
+        );
+    }
+
+    #[test]
+    fn test_validate_mandatory_values_incomplete_composition() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let content = "defaults:\n  - model: missing\nlr: ???\n";
+        let document_path = dir.path().join("config.yaml");
+        let diagnostics = validate_mandatory_values(content, Some(&document_path));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostic_code(&diagnostics[0]), Some("mandatory-value"));
     }
 
     // ==================== validate_target tests ====================
@@ -396,6 +2103,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         };
 
         let diagnostics = validate_target(&target_info, None, None);
@@ -418,6 +2126,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         };
 
         let diagnostics = validate_target(&target_info, Some(&get_test_resources_dir()), None);
@@ -440,6 +2149,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         };
 
         let resources_dir = get_test_resources_dir();
@@ -456,6 +2166,105 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unresolved_segment() {
+        let resources_dir = get_test_resources_dir();
+        let unresolved =
+            |module_path: &str| unresolved_segment(module_path, Some(&resources_dir), None);
+        assert_eq!(unresolved("test_module"), None);
+        assert_eq!(unresolved("test_modul"), Some(0));
+        assert_eq!(unresolved("test_module.missing"), Some(1));
+    }
+
+    #[test]
+    fn test_segment_candidates() {
+        let resources_dir = get_test_resources_dir();
+        let candidates =
+            |target: &str, segment| segment_candidates(target, segment, Some(&resources_dir), None);
+        assert_eq!(candidates("test_module.SimpleClas", 1), vec!["SimpleClass"]);
+        assert_eq!(candidates("test_modul.SimpleClass", 0), vec!["test_module"]);
+        assert!(candidates("test_module.SimpleClass", 2).is_empty());
+    }
+
+    #[test]
+    fn test_similar_names() {
+        let names = [
+            "learning_rate",
+            "lr",
+            "momentum",
+            "Momentum",
+            "weight_decay",
+        ];
+        assert_eq!(
+            similar_names("learning_rat", names.into_iter()),
+            vec!["learning_rate"]
+        );
+        // Case is ignored, and the closest come first
+        assert_eq!(
+            similar_names("momentun", names.into_iter()),
+            vec!["Momentum", "momentum"]
+        );
+        assert!(similar_names("batch_size", names.into_iter()).is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_validate_target_not_instantiable() {
+        let resources_dir = get_test_resources_dir();
+        let validate = |target: &str| {
+            let target_info = TargetInfo {
+                value: target.to_string(),
+                value_start: 10,
+                ..Default::default()
+            };
+            validate_target(&target_info, Some(&resources_dir), None)
+        };
+        let code = |target: &str| {
+            let diagnostics = validate(target);
+            assert!(
+                diagnostics.len() <= 1,
+                "Unexpected diagnostics: {:?}",
+                diagnostics
+            );
+            diagnostics
+                .first()
+                .and_then(diagnostic_code)
+                .map(|code| code.to_string())
+        };
+
+        assert_eq!(code("test_targets.ConcreteModel"), None);
+        assert_eq!(code("test_targets.ModelAlias"), None);
+        assert_eq!(
+            code("test_targets.PartialModel").as_deref(),
+            Some("abstract-class")
+        );
+        assert_eq!(
+            code("test_targets.Encoder").as_deref(),
+            Some("abstract-class")
+        );
+        assert_eq!(
+            code("test_targets.DEFAULT_LR").as_deref(),
+            Some("not-callable")
+        );
+        assert_eq!(
+            code("test_targets.submodule").as_deref(),
+            Some("target-is-module")
+        );
+        assert_eq!(
+            code("test_targets.BrokenReexport").as_deref(),
+            Some("symbol-not-found")
+        );
+        assert_eq!(
+            code("test_package.submodule").as_deref(),
+            Some("target-is-module")
+        );
+
+        assert_eq!(
+            validate("test_targets.BaseModel")[0].message,
+            "Cannot instantiate abstract class 'BaseModel' with abstract methods 'forward', 'loss'"
+        );
+    }
+
     #[test]
     fn test_validate_target_valid_class() {
         let target_info = TargetInfo {
@@ -464,6 +2273,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         };
 
         let resources_dir = get_test_resources_dir();
@@ -492,6 +2302,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         };
 
         let resources_dir = get_test_resources_dir();
@@ -523,6 +2334,7 @@ mod tests {
                 line: 0,
                 key_start: 10,
                 value_start: 10 + "_target_:".len() as u32 + 1,
+                ..Default::default()
             },
             TargetInfo {
                 value: "InvalidTarget".to_string(),
@@ -530,6 +2342,7 @@ mod tests {
                 line: 2,
                 key_start: 10,
                 value_start: 10 + "_target_:".len() as u32 + 1,
+                ..Default::default()
             },
             TargetInfo {
                 value: "nonexistent.Module".to_string(),
@@ -537,6 +2350,7 @@ mod tests {
                 line: 4,
                 key_start: 10,
                 value_start: 10 + "_target_:".len() as u32 + 1,
+                ..Default::default()
             },
         ];
 
@@ -573,6 +2387,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         }];
 
         let resources_dir = get_test_resources_dir();
@@ -610,6 +2425,7 @@ mod tests {
             line: 0,
             key_start: 10,
             value_start: 10 + "_target_:".len() as u32 + 1,
+            ..Default::default()
         }];
 
         let resources_dir = get_test_resources_dir();
@@ -629,4 +2445,265 @@ mod tests {
             "Should not have symbol not found error"
         );
     }
+
+    #[test]
+    fn test_validate_document_nested_target_types() {
+        let content = r#"trainer:
+  _target_: test_targets.Trainer
+  optimizer:
+    _target_: test_targets.ConcreteModel
+  encoder:
+    _target_: test_targets.ConcreteModel
+other:
+  _target_: test_targets.Trainer
+  optimizer:
+    _target_: test_targets.make_optimizer
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let resources_dir = get_test_resources_dir();
+        let diagnostics = validate_document(targets, Some(&resources_dir), None);
+        let incompatible: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| diagnostic_code(d) == Some("incompatible-nested-target"))
+            .collect();
+
+        // The protocol-typed `encoder` accepts anything, and `make_optimizer` returns an `Adam`
+        assert_eq!(
+            incompatible.len(),
+            1,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(incompatible[0].range.start.line, 3);
+        assert_eq!(incompatible[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(
+            incompatible[0].message,
+            "Target 'test_targets.ConcreteModel' produces 'ConcreteModel', which is not compatible with 'Optimizer' expected by parameter 'optimizer'"
+        );
+    }
+
+    #[test]
+    fn test_validate_document_structured_parameters() {
+        let content = r#"build:
+  _target_: test_targets.build
+  _convert_: object
+  optim:
+    momentum: fast
+    betas: [0.9]
+    nesterov: true
+  data: {path: data/, batch_size: 8}
+  scheduler:
+    lr: 0.1
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let resources_dir = get_test_resources_dir();
+        let diagnostics = validate_document(targets, Some(&resources_dir), None);
+        let summary: Vec<_> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.range.start.line,
+                    d.range.start.character,
+                    diagnostic_code(d).unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (3, 2, "missing-parameter"),
+                (4, 4, "type-mismatch"),
+                (6, 4, "unknown-parameter"),
+            ],
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Missing required parameter 'lr' for 'OptimConfig'"
+        );
+        assert_eq!(
+            diagnostics[2].message,
+            "Unknown parameter 'nesterov' for 'OptimConfig'"
+        );
+
+        // Without `_convert_: object` the mappings and their lists arrive as OmegaConf containers
+        let content = content.replace("  _convert_: object\n", "");
+        let (targets, _) = YamlParser::parse(&content).unwrap();
+        let mut converted: Vec<_> = validate_document(targets, Some(&resources_dir), None)
+            .iter()
+            .filter(|d| diagnostic_code(d) == Some("convert-mismatch"))
+            .map(|d| (d.range.start.line, d.range.start.character))
+            .collect();
+        converted.sort();
+        assert_eq!(converted, vec![(2, 2), (4, 4), (6, 2), (7, 2)]);
+    }
+
+    #[test]
+    fn test_validate_document_deprecations() {
+        let targets = vec![
+            TargetInfo {
+                value: "test_module.function_with_deprecated_param".to_string(),
+                parameters: vec![
+                    ParameterValue {
+                        key: "value".to_string(),
+                        line: 1,
+                        ..Default::default()
+                    },
+                    ParameterValue {
+                        key: "old_value".to_string(),
+                        line: 2,
+                        ..Default::default()
+                    },
+                ],
+                line: 0,
+                key_start: 2,
+                value_start: 12,
+                ..Default::default()
+            },
+            TargetInfo {
+                value: "test_module.DeprecatedClass".to_string(),
+                parameters: vec![ParameterValue {
+                    key: "name".to_string(),
+                    line: 4,
+                    ..Default::default()
+                }],
+                line: 3,
+                key_start: 2,
+                value_start: 12,
+                ..Default::default()
+            },
+        ];
+
+        let resources_dir = get_test_resources_dir();
+        let diagnostics = validate_document(targets, Some(&resources_dir), None);
+
+        assert_eq!(
+            diagnostics.len(),
+            2,
+            "Unexpected diagnostics: {:?}",
+            diagnostics
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.tags == Some(vec![DiagnosticTag::DEPRECATED])
+                && d.severity == Some(DiagnosticSeverity::WARNING)));
+
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(
+            diagnostic_code(&diagnostics[0]),
+            Some("deprecated-parameter")
+        );
+        assert!(diagnostics[0]
+            .message
+            .ends_with("is deprecated: old_value is deprecated, use value"));
+
+        assert_eq!(diagnostics[1].range.start.line, 3);
+        assert_eq!(diagnostic_code(&diagnostics[1]), Some("deprecated-target"));
+        assert_eq!(
+            diagnostics[1].message,
+            "'test_module.DeprecatedClass' is deprecated: Use ClassWithInit instead"
+        );
+    }
+
+    #[test]
+    fn test_validate_document_related_information() {
+        let targets = vec![TargetInfo {
+            value: "test_module.function_with_params".to_string(),
+            parameters: vec![
+                ParameterValue {
+                    key: "arg2".to_string(),
+                    kind: ParameterKind::Value(Value::String("two".to_string())),
+                    line: 1,
+                },
+                ParameterValue {
+                    key: "extra".to_string(),
+                    line: 2,
+                    ..Default::default()
+                },
+            ],
+            line: 0,
+            key_start: 2,
+            value_start: 12,
+            ..Default::default()
+        }];
+
+        let resources_dir = get_test_resources_dir();
+        let diagnostics = validate_document(targets, Some(&resources_dir), None);
+        let module_uri = Url::from_file_path(resources_dir.join("test_module.py")).unwrap();
+
+        for code in ["missing-parameter", "type-mismatch", "unknown-parameter"] {
+            let diagnostic = diagnostics
+                .iter()
+                .find(|d| diagnostic_code(d) == Some(code))
+                .unwrap_or_else(|| panic!("Should have a {} diagnostic", code));
+            let related = diagnostic.related_information.as_ref().unwrap();
+
+            // The definition is always linked, after the parameter when one is involved
+            let definition = related.last().unwrap();
+            assert_eq!(definition.location.uri, module_uri);
+            assert_eq!(definition.location.range.start.line, 12);
+            let expected_len = if code == "unknown-parameter" { 1 } else { 2 };
+            assert_eq!(related.len(), expected_len);
+        }
+
+        let mismatch = diagnostics
+            .iter()
+            .find(|d| diagnostic_code(d) == Some("type-mismatch"))
+            .unwrap();
+        assert!(mismatch.related_information.as_ref().unwrap()[0]
+            .message
+            .contains("'arg2'"));
+    }
+
+    // ==================== apply_suppressions tests ====================
+
+    fn code_diagnostic(line: u32, code: &str) -> Diagnostic {
+        let rule = rules::find_rule(code).unwrap();
+        create_diagnostic(line, 2, 6, rule, "test".to_string())
+    }
+
+    #[test]
+    fn test_apply_suppressions_inline() {
+        let content = "model:\n  _target_: my.Model  # hydra-lsp: ignore\n  size: 1  # hydra-lsp: ignore[unknown-parameter]\n  rate: 2\n";
+        let diagnostics = vec![
+            code_diagnostic(1, "symbol-not-found"),
+            code_diagnostic(2, "unknown-parameter"),
+            code_diagnostic(3, "unknown-parameter"),
+        ];
+
+        let result = apply_suppressions(diagnostics, content);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].range.start.line, 3);
+    }
+
+    #[test]
+    fn test_apply_suppressions_reports_unused() {
+        let content = "model:\n  _target_: my.Model  # hydra-lsp: ignore\n  size: 1  # hydra-lsp: ignore[unknown-parameter, missing-parameter]\n";
+        let diagnostics = vec![code_diagnostic(2, "unknown-parameter")];
+
+        let result = apply_suppressions(diagnostics, content);
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|d| {
+            d.severity == Some(DiagnosticSeverity::HINT)
+                && diagnostic_code(d) == Some("unused-suppression")
+                && d.tags == Some(vec![DiagnosticTag::UNNECESSARY])
+        }));
+        assert_eq!(result[0].range.start.line, 1);
+        assert_eq!(result[1].range.start.line, 2);
+        assert!(result[1].message.contains("missing-parameter"));
+    }
+
+    #[test]
+    fn test_apply_suppressions_ignore_file() {
+        let content = "# @hydra\n# hydra-lsp: ignore-file\nmodel:\n  _target_: my.Model\n";
+
+        let result = apply_suppressions(vec![code_diagnostic(3, "symbol-not-found")], content);
+        assert!(result.is_empty());
+
+        let result = apply_suppressions(Vec::new(), content);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].range.start.line, 1);
+    }
 }
@@ -42,6 +42,10 @@ impl DocumentStore {
             .map(|doc| Document::new(doc.content.clone(), doc.version))
     }
 
+    pub fn uris(&self) -> Vec<Url> {
+        self.documents.iter().map(|doc| doc.key().clone()).collect()
+    }
+
     pub fn remove(&self, uri: &Url) {
         self.documents.remove(uri);
     }
@@ -0,0 +1,343 @@
+use crate::composition::DEFAULTS_KEY;
+use crate::interpolation::InterpolationResolver;
+use crate::yaml_parser::{is_reserved_key, OutlineEntry, TargetInfo, YamlParser, TARGET_KEY};
+use std::collections::HashMap;
+use std::path::Path;
+use tower_lsp::lsp_types::{
+    DocumentSymbol, Location, Position, Range, SymbolInformation, SymbolKind,
+};
+
+/// Build the outline of a config: top-level keys, defaults entries and `_target_` blocks
+/// Keys leading to a nested target are kept, so it sits under the parameter it feeds
+/// `symbol_kind` tells classes from functions, targets it can't resolve are shown as objects
+pub fn document_symbols(
+    content: &str,
+    document_path: Option<&Path>,
+    symbol_kind: impl Fn(&TargetInfo) -> Option<SymbolKind>,
+) -> Vec<DocumentSymbol> {
+    let outline = YamlParser::outline(content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Each block holding a `_target_` key, with its target
+    let resolver = InterpolationResolver::new(content, document_path);
+    let blocks: HashMap<&[String], TargetInfo> = YamlParser::outline_targets(&outline)
+        .into_iter()
+        .map(|(block, mut target)| {
+            resolver.resolve_target(&mut target);
+            (block, target)
+        })
+        .collect();
+
+    let included = |entry: &OutlineEntry| {
+        let key = entry.path.last().map(String::as_str).unwrap_or_default();
+        if is_reserved_key(key) {
+            return false;
+        }
+        entry.path.len() == 1
+            || (entry.path.len() == 2 && entry.path[0] == DEFAULTS_KEY)
+            || blocks
+                .keys()
+                .any(|block| block.len() >= entry.path.len() && block.starts_with(&entry.path))
+    };
+
+    // Entries come in document order, so a stack of open symbols rebuilds the nesting
+    let mut roots = Vec::new();
+    let mut stack: Vec<(&[String], DocumentSymbol)> = Vec::new();
+    for (index, entry) in outline.iter().enumerate() {
+        if !included(entry) {
+            continue;
+        }
+        close_symbols(&mut stack, &mut roots, &entry.path);
+        let symbol = entry_symbol(
+            &outline,
+            index,
+            &lines,
+            blocks.get(entry.path.as_slice()),
+            &symbol_kind,
+        );
+        stack.push((&entry.path, symbol));
+    }
+    close_symbols(&mut stack, &mut roots, &[]);
+
+    // A config that is a target as a whole, like a group option, gets a symbol for the file
+    let Some(target) = blocks.get([].as_slice()) else {
+        return roots;
+    };
+    let name = document_path.and_then(Path::file_stem).map_or_else(
+        || short_name(target),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    let end = Position {
+        line: lines.len().saturating_sub(1) as u32,
+        character: lines
+            .last()
+            .map_or(0, |line| line.trim_end().encode_utf16().count() as u32),
+    };
+    let mut root = document_symbol(
+        name,
+        Some(short_name(target)),
+        symbol_kind(target).unwrap_or(SymbolKind::OBJECT),
+        Range {
+            start: Position::new(0, 0),
+            end,
+        },
+        Range {
+            start: Position::new(target.line, target.key_start),
+            end: Position::new(target.line, target.key_start + TARGET_KEY.len() as u32),
+        },
+    );
+    root.children = (!roots.is_empty()).then_some(roots);
+    vec![root]
+}
+
+/// Get the name a target is shown with, the last segment of its path
+fn short_name(target: &TargetInfo) -> String {
+    let path = target.target_path();
+    path.rsplit('.').next().unwrap_or(path).to_string()
+}
+
+/// Close the open symbols that don't contain `path`, adding each to its parent
+fn close_symbols(
+    stack: &mut Vec<(&[String], DocumentSymbol)>,
+    roots: &mut Vec<DocumentSymbol>,
+    path: &[String],
+) {
+    while let Some((open, _)) = stack.last() {
+        if path.len() > open.len() && path.starts_with(open) {
+            break;
+        }
+        let (_, symbol) = stack.pop().unwrap();
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.get_or_insert_with(Vec::new).push(symbol),
+            None => roots.push(symbol),
+        }
+    }
+}
+
+/// Create the symbol for an outline entry, spanning the lines of everything under it
+fn entry_symbol(
+    outline: &[OutlineEntry],
+    index: usize,
+    lines: &[&str],
+    target: Option<&TargetInfo>,
+    symbol_kind: impl Fn(&TargetInfo) -> Option<SymbolKind>,
+) -> DocumentSymbol {
+    let entry = &outline[index];
+    let descendants = outline[index + 1..]
+        .iter()
+        .take_while(|e| e.path.len() > entry.path.len() && e.path.starts_with(&entry.path));
+    let end_line = descendants.clone().last().map_or(entry.line, |e| e.line);
+    let end_character = lines
+        .get(end_line as usize)
+        .map_or(0, |line| line.trim_end().encode_utf16().count() as u32);
+
+    let key = entry.path.last().map(String::as_str).unwrap_or_default();
+    let entry_is_item = entry.is_item();
+    let in_defaults = entry.path.len() == 2 && entry.path[0] == DEFAULTS_KEY;
+    let first_child = descendants.clone().next();
+
+    let name = if in_defaults {
+        // Defaults entries are named by what they select, e.g. `model: resnet` or `_self_`
+        match first_child.filter(|child| child.line == entry.line) {
+            Some(child) if child.value.is_empty() => child.path.last().cloned().unwrap_or_default(),
+            Some(child) => format!("{}: {}", child.path.last().unwrap(), child.value),
+            None => entry.value.clone(),
+        }
+    } else if entry_is_item {
+        // Items are named after their list, e.g. `callbacks[0]`
+        let list = entry
+            .path
+            .len()
+            .checked_sub(2)
+            .map_or("", |i| entry.path[i].as_str());
+        format!("{}[{}]", list, key)
+    } else {
+        key.to_string()
+    };
+
+    let (kind, detail) = match target {
+        Some(target) => (
+            symbol_kind(target).unwrap_or(SymbolKind::OBJECT),
+            Some(short_name(target)),
+        ),
+        None if in_defaults => (SymbolKind::MODULE, None),
+        None => match first_child {
+            Some(child) if child.is_item() => (SymbolKind::ARRAY, None),
+            Some(_) => (SymbolKind::OBJECT, None),
+            None => (
+                SymbolKind::PROPERTY,
+                (!entry.value.is_empty()).then(|| entry.value.clone()),
+            ),
+        },
+    };
+
+    document_symbol(
+        name,
+        detail,
+        kind,
+        Range {
+            start: Position {
+                line: entry.line,
+                character: entry.key_start,
+            },
+            end: Position {
+                line: end_line,
+                character: end_character.max(entry.key_end),
+            },
+        },
+        Range {
+            start: Position {
+                line: entry.line,
+                character: entry.key_start,
+            },
+            end: Position {
+                line: entry.line,
+                character: entry.key_end,
+            },
+        },
+    )
+}
+
+// Symbols are only built through these, since `deprecated` is superseded by `tags` but has no
+// default and has to be set anyway
+
+/// A symbol in the outline of a config, without children
+#[allow(deprecated)]
+fn document_symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: None,
+    }
+}
+
+/// A workspace symbol, found in a config file
+#[allow(deprecated)]
+pub fn symbol_information(
+    name: String,
+    kind: SymbolKind,
+    container_name: Option<String>,
+    location: Location,
+) -> SymbolInformation {
+    SymbolInformation {
+        name,
+        kind,
+        tags: None,
+        deprecated: None,
+        location,
+        container_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flatten symbols to `depth name (detail)` lines
+    fn describe(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            let detail = symbol
+                .detail
+                .as_ref()
+                .map(|detail| format!(" ({})", detail))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{}{} {:?}{}",
+                "  ".repeat(depth),
+                symbol.name,
+                symbol.kind,
+                detail
+            ));
+            describe(
+                symbol.children.as_deref().unwrap_or_default(),
+                depth + 1,
+                lines,
+            );
+        }
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let content = r#"defaults:
+  - model: resnet
+  - _self_
+
+seed: 42
+trainer:
+  _target_: lightning.Trainer
+  max_epochs: 10
+  callbacks:
+    - _target_: lightning.callbacks.EarlyStopping
+      patience: 3
+  logger:
+    _target_: lightning.loggers.make_logger
+optimizer:
+  lr: 0.001
+"#;
+        let symbols = document_symbols(content, None, |target| {
+            target
+                .target_path()
+                .starts_with("lightning.loggers")
+                .then_some(SymbolKind::FUNCTION)
+        });
+        let mut lines = Vec::new();
+        describe(&symbols, 0, &mut lines);
+        assert_eq!(
+            lines,
+            vec![
+                "defaults Array",
+                "  model: resnet Module",
+                "  _self_ Module",
+                "seed Property (42)",
+                "trainer Object (Trainer)",
+                "  callbacks Array",
+                "    callbacks[0] Object (EarlyStopping)",
+                "  logger Function (make_logger)",
+                "optimizer Object",
+            ]
+        );
+
+        // Blocks span their parameters, and select their key
+        let trainer = &symbols[2];
+        assert_eq!(trainer.range.start, Position::new(5, 0));
+        assert_eq!(trainer.range.end, Position::new(12, 43));
+        assert_eq!(trainer.selection_range.end, Position::new(5, 7));
+    }
+
+    #[test]
+    fn test_document_symbols_root_target() {
+        let content = r#"_target_: torch.optim.Adam
+lr: 0.001
+betas:
+  - 0.9
+  - 0.999
+"#;
+        let symbols = document_symbols(content, Some(Path::new("conf/optim/adam.yaml")), |_| {
+            Some(SymbolKind::CLASS)
+        });
+        let mut lines = Vec::new();
+        describe(&symbols, 0, &mut lines);
+        // The parameters of a config targeting a class sit under a symbol for the file
+        assert_eq!(
+            lines,
+            vec![
+                "adam Class (Adam)",
+                "  lr Property (0.001)",
+                "  betas Array"
+            ]
+        );
+        assert_eq!(symbols[0].range.end, Position::new(4, 9));
+        assert_eq!(symbols[0].selection_range.end, Position::new(0, 8));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::interpolation::InterpolationResolver;
+use crate::python_analyzer::{FunctionSignature, ParameterInfo};
+use crate::yaml_parser::{is_reserved_key, OutlineEntry, TargetInfo, YamlParser, ARGS_KEY};
+use std::path::Path;
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Position, Range};
+
+/// Build the inlay hints of the `_target_` blocks in a range of a config
+/// - After `_target_`, the parameters left at their defaults: `lr=0.001, eps=1e-08`
+/// - After parameter values, the annotation they are checked against: `: float`
+/// - Before `_args_` items, the positional parameter they bind to: `in_features=`
+///
+/// `signature` gets the signature a target is called with, targets without one get no hints
+pub fn inlay_hints(
+    content: &str,
+    document_path: Option<&Path>,
+    range: Range,
+    signature: impl Fn(&TargetInfo) -> Option<FunctionSignature>,
+) -> Vec<InlayHint> {
+    let outline = YamlParser::outline(content);
+    let resolver = InterpolationResolver::new(content, document_path);
+
+    let mut hints = Vec::new();
+    for (block, mut target) in YamlParser::outline_targets(&outline) {
+        resolver.resolve_target(&mut target);
+        let Some(signature) = signature(&target) else {
+            continue;
+        };
+        let keys = YamlParser::block_keys(&outline, block);
+        let args: Vec<Position> = keys
+            .iter()
+            .find(|entry| entry.path.last().map(String::as_str) == Some(ARGS_KEY))
+            .map(|args| YamlParser::sequence_item_positions(&outline, args))
+            .unwrap_or_default();
+        let positional: Vec<&ParameterInfo> = signature
+            .parameters
+            .iter()
+            .filter(|param| param.is_positional())
+            .collect();
+
+        // Parameters neither given by key nor bound by `_args_`
+        let omitted: Vec<String> = signature
+            .parameters
+            .iter()
+            .filter(|param| param.has_default && param.name != "self")
+            .filter(|param| {
+                !positional
+                    .iter()
+                    .take(args.len())
+                    .any(|p| p.name == param.name)
+            })
+            .filter(|param| {
+                !keys
+                    .iter()
+                    .any(|entry| entry.path.last() == Some(&param.name))
+            })
+            .map(|param| {
+                format!(
+                    "{}={}",
+                    param.name,
+                    param.default_value.as_deref().unwrap_or("...")
+                )
+            })
+            .collect();
+        if let Some(entry) = keys.iter().find(|entry| entry.line == target.line) {
+            if !omitted.is_empty() {
+                hints.push(hint(
+                    value_end(entry),
+                    omitted.join(", "),
+                    None,
+                    (true, false),
+                ));
+            }
+        }
+
+        for entry in &keys {
+            let key = entry.path.last().map(String::as_str).unwrap_or_default();
+            if is_reserved_key(key) || entry.value.is_empty() {
+                continue;
+            }
+            let annotation = signature
+                .keyword_parameter(key)
+                .filter(|param| !param.is_variadic_keyword)
+                .and_then(|param| param.type_annotation.as_deref());
+            if let Some(annotation) = annotation {
+                hints.push(hint(
+                    value_end(entry),
+                    format!(": {}", annotation),
+                    Some(InlayHintKind::TYPE),
+                    (false, false),
+                ));
+            }
+        }
+
+        for (position, param) in args.into_iter().zip(positional) {
+            hints.push(hint(
+                position,
+                format!("{}=", param.name),
+                Some(InlayHintKind::PARAMETER),
+                (false, false),
+            ));
+        }
+    }
+
+    hints.retain(|hint| range.start <= hint.position && hint.position <= range.end);
+    hints.sort_by_key(|hint| (hint.position.line, hint.position.character));
+    hints
+}
+
+/// Get the position after an entry's inline value, before any comment
+fn value_end(entry: &OutlineEntry) -> Position {
+    Position::new(entry.line, entry.value_start + entry.value.len() as u32)
+}
+
+/// Create a plain text hint, padded on the given sides
+fn hint(
+    position: Position,
+    label: String,
+    kind: Option<InlayHintKind>,
+    (padding_left, padding_right): (bool, bool),
+) -> InlayHint {
+    InlayHint {
+        position,
+        label: InlayHintLabel::String(label),
+        kind,
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(padding_left),
+        padding_right: Some(padding_right),
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(name: &str, annotation: Option<&str>, default: Option<&str>) -> ParameterInfo {
+        ParameterInfo {
+            name: name.to_string(),
+            type_annotation: annotation.map(|a| a.to_string()),
+            default_value: default.map(|d| d.to_string()),
+            has_default: default.is_some(),
+            ..Default::default()
+        }
+    }
+
+    fn describe(hints: &[InlayHint]) -> Vec<String> {
+        hints
+            .iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = &hint.label else {
+                    unreachable!()
+                };
+                format!(
+                    "{}:{} {}",
+                    hint.position.line, hint.position.character, label
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_inlay_hints() {
+        let content = r#"optimizer:
+  _target_: torch.optim.Adam  # adaptive
+  lr: 0.01
+  extra: 1
+layer:
+  _target_: torch.nn.Linear
+  _args_: [10, [1, 2]]
+model:
+  _target_: torch.nn.Linear
+  _args_:
+    - 10
+    - 5
+  bias: false
+"#;
+        let hints = inlay_hints(
+            content,
+            None,
+            Range::new(Position::new(0, 0), Position::new(20, 0)),
+            |target| {
+                let parameters = match target.target_path() {
+                    "torch.optim.Adam" => vec![
+                        parameter("self", None, None),
+                        parameter("params", None, None),
+                        parameter("lr", Some("float"), Some("0.001")),
+                        parameter("eps", Some("float"), Some("1e-08")),
+                        ParameterInfo {
+                            is_variadic_keyword: true,
+                            ..parameter("kwargs", Some("Any"), None)
+                        },
+                    ],
+                    "torch.nn.Linear" => vec![
+                        parameter("self", None, None),
+                        parameter("in_features", Some("int"), None),
+                        parameter("out_features", Some("int"), None),
+                        parameter("bias", Some("bool"), Some("True")),
+                    ],
+                    _ => return None,
+                };
+                Some(FunctionSignature {
+                    parameters,
+                    ..Default::default()
+                })
+            },
+        );
+        assert_eq!(
+            describe(&hints),
+            vec![
+                "1:28 eps=1e-08",
+                "2:10 : float",
+                "5:27 bias=True",
+                "6:11 in_features=",
+                "6:15 out_features=",
+                "10:6 in_features=",
+                "11:6 out_features=",
+                "12:13 : bool",
+            ]
+        );
+
+        // Only hints within the requested range are returned
+        let hints = inlay_hints(
+            content,
+            None,
+            Range::new(Position::new(2, 0), Position::new(2, 20)),
+            |_| {
+                Some(FunctionSignature {
+                    parameters: vec![parameter("lr", Some("float"), None)],
+                    ..Default::default()
+                })
+            },
+        );
+        assert_eq!(describe(&hints), vec!["2:10 : float"]);
+    }
+}
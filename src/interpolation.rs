@@ -0,0 +1,216 @@
+use crate::composition::{self, MISSING_VALUE};
+use crate::yaml_parser::{OutlineEntry, TargetInfo, YamlParser, TARGET_KEY};
+use serde_yaml::Value;
+use std::path::Path;
+
+/// Maximum number of interpolations followed through other interpolations
+const MAX_DEPTH: usize = 10;
+
+/// Check if a string contains an OmegaConf `${...}` interpolation
+pub fn has_interpolation(text: &str) -> bool {
+    text.contains("${")
+}
+
+/// Find the byte ranges of the outermost `${...}` interpolations in a string
+/// Example: "a_${x}_${y.${z}}" -> [2..6, 7..16], an unclosed interpolation runs to the end
+pub fn interpolation_spans(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut depth = 0;
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"${") {
+            if depth == 0 {
+                start = Some(i);
+            }
+            depth += 1;
+            i += 2;
+            continue;
+        }
+        if bytes[i] == b'}' && depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                spans.extend(start.take().map(|start| start..i + 1));
+            }
+        }
+        i += 1;
+    }
+    spans.extend(start.map(|start| start..text.len()));
+    spans
+}
+
+/// Resolves static interpolations such as `${model.cls}` or `${.name}` against a config
+/// Resolvers (`${oc.env:VAR}`) and values that aren't in the config are left unresolved
+pub struct InterpolationResolver {
+    config: Value,
+    outline: Vec<OutlineEntry>,
+}
+
+impl InterpolationResolver {
+    /// Create a resolver for a document, composed with its defaults list when it has one
+    pub fn new(content: &str, document_path: Option<&Path>) -> Self {
+        let document: Value = serde_yaml::from_str(content).unwrap_or(Value::Null);
+        let composed = document_path
+            .and_then(|path| path.parent())
+            .and_then(|config_dir| composition::compose(&document, config_dir))
+            .map(|composition| composition.config);
+        Self {
+            config: composed.unwrap_or(document),
+            outline: YamlParser::outline(content),
+        }
+    }
+
+    /// Set the resolved value of a target whose value is interpolated
+    pub fn resolve_target(&self, target: &mut TargetInfo) {
+        if !has_interpolation(&target.value) {
+            return;
+        }
+        // Relative interpolations are relative to the mapping holding `_target_`
+        let node: Vec<String> = self
+            .outline
+            .iter()
+            .find(|entry| {
+                entry.line == target.line
+                    && entry.path.last().map(String::as_str) == Some(TARGET_KEY)
+            })
+            .map(|entry| entry.path[..entry.path.len() - 1].to_vec())
+            .unwrap_or_default();
+        target.resolved = self.resolve(&target.value, &node, 0);
+    }
+
+    /// Substitute every interpolation in a string, or `None` if any can't be resolved
+    fn resolve(&self, text: &str, node: &[String], depth: usize) -> Option<String> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let inner_start = start + 2;
+            let end = inner_start + rest[inner_start..].find('}')?;
+            let key = rest[inner_start..end].trim();
+            // Nested interpolations and resolvers are only known at runtime
+            if key.contains("${") || key.contains(':') {
+                return None;
+            }
+
+            let path = Self::key_path(key, node)?;
+            let value = match composition::lookup(&self.config, &path)? {
+                Value::String(text) if text == MISSING_VALUE => return None,
+                // An interpolated value is relative to the mapping that holds it
+                Value::String(text) => self.resolve(text, &path[..path.len() - 1], depth + 1)?,
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => flag.to_string(),
+                _ => return None,
+            };
+            result.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        Some(result)
+    }
+
+    /// Turn an interpolation key into a path from the config root
+    /// Example: ".name" in node ["model"] -> ["model", "name"], "layers[0]" -> ["layers", "0"]
+    fn key_path(key: &str, node: &[String]) -> Option<Vec<String>> {
+        let relative = key.chars().take_while(|&c| c == '.').count();
+        let mut path = if relative == 0 {
+            Vec::new()
+        } else {
+            // One dot is the current node, every further dot goes up a level
+            node[..node.len().checked_sub(relative - 1)?].to_vec()
+        };
+        path.extend(
+            key[relative..]
+                .replace('[', ".")
+                .replace(']', "")
+                .split('.')
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.to_string()),
+        );
+        (!path.is_empty()).then_some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve_all(content: &str) -> Vec<Option<String>> {
+        let (mut targets, _) = YamlParser::parse(content).unwrap();
+        let resolver = InterpolationResolver::new(content, None);
+        targets
+            .iter_mut()
+            .map(|target| {
+                resolver.resolve_target(target);
+                target.resolved.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolation_spans() {
+        assert_eq!(interpolation_spans("a_${x}_${y.${z}}"), vec![2..6, 7..16]);
+        assert_eq!(interpolation_spans("${oc.env:HOME}/data"), vec![0..14]);
+        assert_eq!(interpolation_spans("plain"), vec![]);
+        assert_eq!(interpolation_spans("open ${x"), vec![5..8]);
+    }
+
+    #[test]
+    fn test_resolve_absolute_and_relative() {
+        let content = r#"model_name: ResNet
+classes:
+  - myproject.models.Encoder
+model:
+  cls: myproject.models.${model_name}
+  _target_: ${model.cls}
+encoder:
+  name: Encoder
+  _target_: myproject.models.${.name}
+decoder:
+  _target_: ${classes[0]}
+plain:
+  _target_: myproject.models.Plain
+"#;
+        assert_eq!(
+            resolve_all(content),
+            vec![
+                Some("myproject.models.ResNet".to_string()),
+                Some("myproject.models.Encoder".to_string()),
+                Some("myproject.models.Encoder".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unresolvable_interpolations() {
+        let content = r#"name: ???
+a:
+  _target_: ${oc.env:MODEL}
+b:
+  _target_: ${missing.key}
+c:
+  _target_: models.${name}
+"#;
+        assert_eq!(resolve_all(content), vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_resolve_composed_config() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("model")).unwrap();
+        std::fs::write(dir.path().join("model/resnet.yaml"), "cls: models.ResNet\n").unwrap();
+        let content = "defaults:\n  - model: resnet\nnet:\n  _target_: ${model.cls}\n";
+
+        let (mut targets, _) = YamlParser::parse(content).unwrap();
+        let document_path = dir.path().join("config.yaml");
+        InterpolationResolver::new(content, Some(&document_path)).resolve_target(&mut targets[0]);
+        assert_eq!(targets[0].resolved.as_deref(), Some("models.ResNet"));
+        assert_eq!(targets[0].target_path(), "models.ResNet");
+        assert!(!targets[0].is_dynamic());
+    }
+}
@@ -1,5 +1,15 @@
+mod annotation;
 pub mod backend;
+mod code_actions;
+mod code_lens;
+mod composition;
 mod diagnostics;
 mod document;
+mod document_symbols;
+mod inlay_hints;
+mod interpolation;
 mod python_analyzer;
+mod rules;
+mod semantic_tokens;
+mod workspace_index;
 mod yaml_parser;
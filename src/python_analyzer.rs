@@ -2,19 +2,40 @@ use anyhow::Result;
 use ruff_db::system::{OsSystem, SystemPath, SystemPathBuf};
 use ruff_python_ast::{self as ast, visitor::Visitor, Expr, Stmt};
 use ruff_python_parser::parse_module;
+use ruff_text_size::{Ranged, TextSize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use ty_python_semantic::{PythonEnvironment, SysPrefixPathOrigin};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct FunctionSignature {
     pub name: String,
     pub parameters: Vec<ParameterInfo>,
     pub return_type: Option<String>,
     pub docstring: Option<String>,
+    /// Deprecation message, empty if the deprecation gives no reason
+    pub deprecated: Option<String>,
+    /// File the function is defined in
+    pub file_path: PathBuf,
+    /// Line of the function name (0-based)
+    pub line: u32,
+    /// Columns of the function name, in UTF-16 code units like LSP positions
+    pub column: u32,
+    pub end_column: u32,
 }
 
-#[derive(Debug, Clone)]
+impl FunctionSignature {
+    /// Get the parameter a config key is passed to
+    /// Keys that don't name a parameter are absorbed by `**kwargs`, if there is one
+    pub fn keyword_parameter(&self, key: &str) -> Option<&ParameterInfo> {
+        self.parameters
+            .iter()
+            .find(|p| p.name == key && !p.is_variadic && !p.is_variadic_keyword)
+            .or_else(|| self.parameters.iter().find(|p| p.is_variadic_keyword))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ParameterInfo {
     pub name: String,
     pub type_annotation: Option<String>,
@@ -23,19 +44,43 @@ pub struct ParameterInfo {
     pub is_variadic: bool,         // *args
     pub is_variadic_keyword: bool, // **kwargs
     pub is_keyword_only: bool,
+    /// Declared before a `/`, so it can only be passed positionally
+    pub is_positional_only: bool,
+    pub deprecated: Option<String>,
+    /// Line of the parameter name (0-based)
+    pub line: u32,
+    /// Columns of the parameter name, in UTF-16 code units like LSP positions
+    pub column: u32,
+    pub end_column: u32,
 }
 
 impl ParameterInfo {
     pub fn is_required(&self) -> bool {
         !self.has_default && !self.is_variadic && !self.is_variadic_keyword && self.name != "self"
     }
+
+    /// Check if the parameter can be given by position, e.g. through `_args_`
+    pub fn is_positional(&self) -> bool {
+        !self.is_variadic
+            && !self.is_variadic_keyword
+            && !self.is_keyword_only
+            && self.name != "self"
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ClassInfo {
     pub name: String,
     pub docstring: Option<String>,
     pub init_signature: Option<FunctionSignature>,
+    pub deprecated: Option<String>,
+    /// File the class is defined in
+    pub file_path: PathBuf,
+    /// Line of the class name (0-based)
+    pub line: u32,
+    /// Columns of the class name, in UTF-16 code units like LSP positions
+    pub column: u32,
+    pub end_column: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +89,75 @@ pub enum DefinitionInfo {
     Class(ClassInfo),
 }
 
+/// What a module-level symbol is, as far as calling it from a `_target_` is concerned
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    Function,
+    Class(ClassKind),
+    /// A module bound to the name, e.g. a submodule imported into a package
+    Module(PathBuf),
+    /// A literal value such as a number, string or collection
+    Constant,
+    /// Anything else, such as the result of a call, which may or may not be callable
+    Unknown,
+}
+
+/// How a class can be instantiated, and what it derives from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClassKind {
+    /// Abstract methods without an implementation, including inherited ones
+    pub abstract_methods: Vec<String>,
+    /// Whether the class uses `ABCMeta`, which is what makes abstract methods binding
+    pub is_abc: bool,
+    /// Whether the class lists `Protocol` as a base, which can never be instantiated
+    pub is_protocol: bool,
+    /// Short names of every base class, direct or inherited
+    /// Example: ["PartialModel", "BaseModel", "ABC"]
+    pub ancestors: Vec<String>,
+    /// Whether some base class couldn't be followed, so `ancestors` may be incomplete
+    pub unresolved_bases: bool,
+    /// Fields of a dataclass or TypedDict, as the signature OmegaConf builds it with
+    pub structure: Option<FunctionSignature>,
+}
+
+impl ClassKind {
+    pub fn is_abstract(&self) -> bool {
+        self.is_abc && !self.abstract_methods.is_empty()
+    }
+
+    /// Check if the class is or derives from a class, compared by short name
+    pub fn is_subclass_of(&self, own_name: &str, class_name: &str) -> bool {
+        own_name == class_name || self.ancestors.iter().any(|a| a == class_name)
+    }
+}
+
+/// Builtin and typing base classes that end a class hierarchy
+const TERMINAL_BASES: &[&str] = &[
+    "object",
+    "int",
+    "float",
+    "complex",
+    "str",
+    "bytes",
+    "bool",
+    "list",
+    "tuple",
+    "dict",
+    "set",
+    "frozenset",
+    "type",
+    "Exception",
+    "BaseException",
+    "ABC",
+    "Protocol",
+    "Generic",
+    "NamedTuple",
+    "TypedDict",
+];
+
+/// Maximum number of imports and base classes followed when classifying a symbol
+const MAX_RESOLUTION_DEPTH: usize = 8;
+
 pub struct PythonAnalyzer;
 
 impl PythonAnalyzer {
@@ -107,20 +221,11 @@ impl PythonAnalyzer {
         Ok(site_packages_paths.into_vec())
     }
 
-    /// Resolve a Python module path to a file path using ty's sophisticated module resolution
-    ///
-    /// This implementation:
-    /// - Discovers Python environment (venv, conda, system)
-    /// - Uses proper site-packages resolution
-    /// - Handles package hierarchies correctly
-    /// - Supports .pyi stub files
-    pub fn resolve_module(
-        module_path: &str,
+    /// Get the directories modules are searched in: the workspace root, then site-packages
+    fn search_paths(
         workspace_root: Option<&Path>,
         python_interpreter: Option<&str>,
-    ) -> Result<PathBuf> {
-        let module_parts: Vec<&str> = module_path.split('.').collect();
-
+    ) -> Vec<PathBuf> {
         // Build search paths: workspace root + site-packages from ty
         let mut search_paths = Vec::new();
 
@@ -146,6 +251,23 @@ impl PythonAnalyzer {
                 eprintln!("Warning: Could not discover Python environment: {}", e);
             }
         }
+        search_paths
+    }
+
+    /// Resolve a Python module path to a file path using ty's sophisticated module resolution
+    ///
+    /// This implementation:
+    /// - Discovers Python environment (venv, conda, system)
+    /// - Uses proper site-packages resolution
+    /// - Handles package hierarchies correctly
+    /// - Supports .pyi stub files
+    pub fn resolve_module(
+        module_path: &str,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Result<PathBuf> {
+        let module_parts: Vec<&str> = module_path.split('.').collect();
+        let search_paths = Self::search_paths(workspace_root, python_interpreter);
 
         // Store the count before iterating
         let search_path_count = search_paths.len();
@@ -224,6 +346,7 @@ impl PythonAnalyzer {
 
         let mut visitor = FunctionExtractor {
             target_name: function_name.to_string(),
+            source_file: SourceFile::new(file_path, &source),
             result: None,
         };
 
@@ -245,6 +368,7 @@ impl PythonAnalyzer {
 
         let mut visitor = ClassExtractor {
             target_name: class_name.to_string(),
+            source_file: SourceFile::new(file_path, &source),
             result: None,
         };
 
@@ -281,38 +405,170 @@ impl PythonAnalyzer {
         anyhow::bail!("Symbol '{}' not found in module", symbol_name)
     }
 
-    /// Format a function signature for display (e.g., in hover)
-    pub fn format_signature(sig: &FunctionSignature) -> String {
-        let mut result = String::new();
-        result.push_str("```python\n");
-        result.push_str(&format!("def {}(", sig.name));
+    /// Get the names of the modules and packages directly in a package, or of the top-level
+    /// ones when `package` is `None`. A module that isn't a package has none
+    pub fn submodules(
+        package: Option<&str>,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Vec<String> {
+        let dirs = match package {
+            Some(package) => {
+                match Self::resolve_module(package, workspace_root, python_interpreter) {
+                    Ok(path) if path.file_stem().is_some_and(|stem| stem == "__init__") => {
+                        path.parent().map(Path::to_path_buf).into_iter().collect()
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            None => Self::search_paths(workspace_root, python_interpreter),
+        };
 
-        let param_strs: Vec<String> = sig
-            .parameters
+        let mut names = Vec::new();
+        for entry in dirs
             .iter()
-            .map(|p| {
-                let mut s = String::new();
-
-                // Add * or ** prefix for variadic parameters
-                if p.is_variadic {
-                    s.push('*');
-                } else if p.is_variadic_keyword {
-                    s.push_str("**");
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .flatten()
+        {
+            let path = entry.path();
+            let name = if path.is_dir() {
+                let is_package =
+                    path.join("__init__.py").exists() || path.join("__init__.pyi").exists();
+                is_package.then_some(path.file_name()).flatten()
+            } else {
+                matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("py" | "pyi")
+                )
+                .then_some(path.file_stem())
+                .flatten()
+            };
+            if let Some(name) = name.and_then(|name| name.to_str()) {
+                if name != "__init__" && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
                 }
+            }
+        }
+        names.sort();
+        names
+    }
 
-                s.push_str(&p.name);
+    /// Get the names bound at the top level of a Python file, by definitions, assignments and
+    /// imports, in the order they appear
+    pub fn module_symbols(file_path: &Path) -> Vec<String> {
+        let Ok(source) = fs::read_to_string(file_path) else {
+            return Vec::new();
+        };
+        let Ok(parsed) = parse_module(&source) else {
+            return Vec::new();
+        };
+        let mut names = Vec::new();
+        collect_bindings(parsed.suite(), &mut names);
+        names
+    }
 
-                if let Some(type_ann) = &p.type_annotation {
-                    s.push_str(&format!(": {}", type_ann));
+    /// Get the dotted module name of a Python file or package directory
+    /// The name starts at the first directory up the tree without an `__init__.py`
+    /// The path itself needn't exist, e.g. the new path of a file being moved
+    pub fn module_name(path: &Path) -> Option<String> {
+        let mut parts = Vec::new();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("py" | "pyi") => {
+                let stem = path.file_stem()?.to_str()?;
+                // A package's `__init__.py` is the package itself
+                if stem != "__init__" {
+                    parts.push(stem);
                 }
-                if let Some(default) = &p.default_value {
-                    s.push_str(&format!(" = {}", default));
-                }
-                s
-            })
-            .collect();
+            }
+            Some(_) if !path.is_dir() => return None,
+            _ => parts.push(path.file_name()?.to_str()?),
+        }
+
+        let mut dir = path.parent();
+        while let Some(package) = dir.filter(|dir| dir.join("__init__.py").exists()) {
+            parts.push(package.file_name()?.to_str()?);
+            dir = package.parent();
+        }
+        parts.reverse();
+        (!parts.is_empty()).then(|| parts.join("."))
+    }
+
+    /// Get the name of the module-level class or function whose name is at a position
+    /// Returns the name with the line it's on
+    pub fn definition_at_position(
+        source: &str,
+        line: u32,
+        character: u32,
+    ) -> Option<(String, u32)> {
+        let parsed = parse_module(source).ok()?;
+        let source_file = SourceFile::new(Path::new(""), source);
+        parsed.suite().iter().find_map(|stmt| {
+            let name = match stmt {
+                Stmt::FunctionDef(func_def) => &func_def.name,
+                Stmt::ClassDef(class_def) => &class_def.name,
+                _ => return None,
+            };
+            let start = source_file.column(name.start());
+            let end = source_file.column(name.end());
+            (source_file.line(name.start()) == line && (start..=end).contains(&character))
+                .then(|| (name.to_string(), line))
+        })
+    }
+
+    /// Follow a target through the imports that re-export it, up to the module defining it
+    /// Example: "mypkg.run" with `from subprocess import run` -> ["mypkg.run", "subprocess.run"]
+    pub fn reexport_chain(
+        target: &str,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Vec<String> {
+        let classifier = SymbolClassifier {
+            workspace_root,
+            python_interpreter,
+        };
+        let mut chain = vec![target.to_string()];
+        while chain.len() <= MAX_RESOLUTION_DEPTH {
+            let Some(next) = chain
+                .last()
+                .and_then(|name| Self::split_target(name).ok())
+                .and_then(|(module, name)| classifier.reexport(&module, &name))
+            else {
+                break;
+            };
+            if chain.contains(&next) {
+                break;
+            }
+            chain.push(next);
+        }
+        chain
+    }
+
+    /// Classify a module-level symbol, following imports and aliases to their definition
+    /// The name can be dotted to go through an imported module, e.g. `nn.Module`
+    /// Returns `None` if the module doesn't bind the name
+    pub fn classify_symbol(
+        file_path: &Path,
+        symbol_name: &str,
+        workspace_root: Option<&Path>,
+        python_interpreter: Option<&str>,
+    ) -> Option<SymbolKind> {
+        let classifier = SymbolClassifier {
+            workspace_root,
+            python_interpreter,
+        };
+        classifier.classify_dotted(file_path, symbol_name, 0)
+    }
 
-        result.push_str(&param_strs.join(", "));
+    /// Format a function signature for display (e.g., in hover)
+    pub fn format_signature(sig: &FunctionSignature) -> String {
+        let mut result = String::new();
+        result.push_str("```python\n");
+        result.push_str(&format!("def {}(", sig.name));
+
+        let param_strs = sig.parameters.iter().map(|p| (p, format_declaration(p)));
+
+        result.push_str(&Self::join_parameters(param_strs));
         result.push(')');
 
         if let Some(ret_type) = &sig.return_type {
@@ -320,6 +576,7 @@ impl PythonAnalyzer {
         }
 
         result.push_str("\n```");
+        push_deprecation(&mut result, sig.deprecated.as_deref());
 
         if let Some(docstring) = &sig.docstring {
             result.push_str("\n\n---\n\n");
@@ -337,7 +594,7 @@ impl PythonAnalyzer {
 
         if let Some(init_sig) = &class.init_signature {
             result.push('(');
-            let param_strs: Vec<String> = init_sig
+            let param_strs = init_sig
                 .parameters
                 .iter()
                 .filter(|p| p.name != "self") // Skip self parameter
@@ -349,14 +606,18 @@ impl PythonAnalyzer {
                     if let Some(default) = &p.default_value {
                         s.push_str(&format!(" = {}", default));
                     }
-                    s
-                })
-                .collect();
-            result.push_str(&param_strs.join(", "));
+                    (p, s)
+                });
+            result.push_str(&Self::join_parameters(param_strs));
             result.push(')');
         }
 
         result.push_str("\n```");
+        let init_deprecated = class
+            .init_signature
+            .as_ref()
+            .and_then(|sig| sig.deprecated.as_deref());
+        push_deprecation(&mut result, class.deprecated.as_deref().or(init_deprecated));
 
         if let Some(docstring) = &class.docstring {
             result.push_str("\n\n---\n\n");
@@ -365,11 +626,210 @@ impl PythonAnalyzer {
 
         result
     }
+
+    /// Format a parameter for display (e.g., in hover on a config key)
+    pub fn format_parameter(param: &ParameterInfo, documentation: Option<&str>) -> String {
+        let mut result = String::new();
+        result.push_str("```python\n(parameter) ");
+        result.push_str(&format_declaration(param));
+        result.push_str("\n```");
+        push_deprecation(&mut result, param.deprecated.as_deref());
+
+        if let Some(documentation) = documentation {
+            result.push_str("\n\n---\n\n");
+            result.push_str(documentation);
+        }
+
+        result
+    }
+
+    /// Find the documentation of a parameter in a Google, NumPy or Sphinx style docstring
+    pub fn parameter_doc(docstring: &str, name: &str) -> Option<String> {
+        let lines: Vec<&str> = docstring.lines().collect();
+        // The open parameter section: its header's indent, whether it is NumPy style, and the
+        // indent of its entries once known
+        let mut section: Option<(usize, bool, Option<usize>)> = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+
+            // Sphinx: `:param name: text` or `:param int name: text`
+            if let Some(rest) = trimmed.strip_prefix(":param ") {
+                if let Some((head, text)) = rest.split_once(':') {
+                    if head.split_whitespace().last() == Some(name) {
+                        return doc_block(text, &lines[index + 1..], indent);
+                    }
+                }
+                continue;
+            }
+
+            // NumPy headers are underlined: `Parameters` then `----------`
+            if lines
+                .get(index + 1)
+                .is_some_and(|next| next.trim().starts_with("---"))
+            {
+                section = matches!(trimmed, "Parameters" | "Other Parameters" | "Attributes")
+                    .then_some((indent, true, Some(indent)));
+                continue;
+            }
+            if trimmed.starts_with("---") {
+                continue;
+            }
+
+            // Google headers end with a colon: `Args:`
+            let google_header = matches!(
+                trimmed,
+                "Args:"
+                    | "Arguments:"
+                    | "Parameters:"
+                    | "Keyword Args:"
+                    | "Keyword Arguments:"
+                    | "Attributes:"
+            );
+            if google_header {
+                section = Some((indent, false, None));
+                continue;
+            }
+
+            let Some((header, numpy, entry_indent)) = section else {
+                continue;
+            };
+            // A Google section ends at the first line that isn't indented under its header
+            if !numpy && indent <= header {
+                section = None;
+                continue;
+            }
+            let entry_indent = entry_indent.unwrap_or(indent);
+            section = Some((header, numpy, Some(entry_indent)));
+            if indent != entry_indent {
+                continue;
+            }
+
+            // Google: `name (int): text` or `name: text`, NumPy: `name : int` or `a, b : int`
+            let (head, text) = trimmed.split_once(':').unwrap_or((trimmed, ""));
+            let matches = if numpy {
+                head.split(',')
+                    .any(|entry| entry.trim().trim_start_matches('*') == name)
+            } else {
+                head.split_whitespace()
+                    .next()
+                    .is_some_and(|entry| entry.trim_start_matches('*') == name)
+            };
+            if matches {
+                let text = if numpy { "" } else { text };
+                return doc_block(text, &lines[index + 1..], indent);
+            }
+        }
+        None
+    }
+
+    /// Join formatted parameters, with a `/` marker after the last positional-only one
+    pub fn join_parameters<'a>(
+        params: impl IntoIterator<Item = (&'a ParameterInfo, String)>,
+    ) -> String {
+        let mut parts = Vec::new();
+        let mut positional_only = false;
+        for (param, formatted) in params {
+            if positional_only && !param.is_positional_only {
+                parts.push("/".to_string());
+            }
+            positional_only = param.is_positional_only;
+            parts.push(formatted);
+        }
+        if positional_only {
+            parts.push("/".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Format a parameter as declared, e.g. `*args`, `hidden: int = 128`
+fn format_declaration(param: &ParameterInfo) -> String {
+    let mut s = String::new();
+
+    // Add * or ** prefix for variadic parameters
+    if param.is_variadic {
+        s.push('*');
+    } else if param.is_variadic_keyword {
+        s.push_str("**");
+    }
+
+    s.push_str(&param.name);
+
+    if let Some(type_ann) = &param.type_annotation {
+        s.push_str(&format!(": {}", type_ann));
+    }
+    if let Some(default) = &param.default_value {
+        s.push_str(&format!(" = {}", default));
+    }
+    s
+}
+
+/// Collect a docstring entry's text and the lines indented under it
+fn doc_block(first: &str, rest: &[&str], indent: usize) -> Option<String> {
+    let mut text = vec![first.trim()];
+    for line in rest {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && line.len() - line.trim_start().len() <= indent {
+            break;
+        }
+        text.push(trimmed);
+    }
+    let text = text.join("\n").trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Append a deprecation notice to hover markdown
+fn push_deprecation(result: &mut String, deprecated: Option<&str>) {
+    match deprecated {
+        Some("") => result.push_str("\n\n**Deprecated**"),
+        Some(message) => result.push_str(&format!("\n\n**Deprecated**: {}", message)),
+        None => {}
+    }
+}
+
+/// A parsed Python file, used to locate the definitions extracted from it
+struct SourceFile {
+    path: PathBuf,
+    source: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(path: &Path, source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            path: path.to_path_buf(),
+            source: source.to_string(),
+            line_starts,
+        }
+    }
+
+    /// Get the 0-based line containing a byte offset
+    fn line(&self, offset: TextSize) -> u32 {
+        let offset = usize::from(offset);
+        (self.line_starts.partition_point(|&start| start <= offset) - 1) as u32
+    }
+
+    /// Get the column of a byte offset in UTF-16 code units, which is how LSP counts characters
+    fn column(&self, offset: TextSize) -> u32 {
+        let line_start = self.line_starts[self.line(offset) as usize];
+        self.source[line_start..usize::from(offset)]
+            .encode_utf16()
+            .count() as u32
+    }
 }
 
 /// Visitor to extract function signatures from AST
 struct FunctionExtractor {
     target_name: String,
+    source_file: SourceFile,
     result: Option<FunctionSignature>,
 }
 
@@ -381,7 +841,10 @@ impl<'a> Visitor<'a> for FunctionExtractor {
 
         if let Stmt::FunctionDef(func_def) = stmt {
             if func_def.name.as_str() == self.target_name {
-                self.result = Some(extract_function_signature_from_def(func_def));
+                self.result = Some(extract_function_signature_from_def(
+                    func_def,
+                    &self.source_file,
+                ));
                 return;
             }
         }
@@ -394,6 +857,7 @@ impl<'a> Visitor<'a> for FunctionExtractor {
 /// Visitor to extract class information from AST
 struct ClassExtractor {
     target_name: String,
+    source_file: SourceFile,
     result: Option<ClassInfo>,
 }
 
@@ -405,7 +869,7 @@ impl<'a> Visitor<'a> for ClassExtractor {
 
         if let Stmt::ClassDef(class_def) = stmt {
             if class_def.name.as_str() == self.target_name {
-                self.result = Some(extract_class_info_from_def(class_def));
+                self.result = Some(extract_class_info_from_def(class_def, &self.source_file));
                 return;
             }
         }
@@ -415,29 +879,577 @@ impl<'a> Visitor<'a> for ClassExtractor {
     }
 }
 
+/// Classifies symbols across modules, see `PythonAnalyzer::classify_symbol`
+struct SymbolClassifier<'a> {
+    workspace_root: Option<&'a Path>,
+    python_interpreter: Option<&'a str>,
+}
+
+impl SymbolClassifier<'_> {
+    fn classify(&self, file_path: &Path, name: &str, depth: usize) -> Option<SymbolKind> {
+        if depth > MAX_RESOLUTION_DEPTH {
+            return Some(SymbolKind::Unknown);
+        }
+        let source = fs::read_to_string(file_path).ok()?;
+        let parsed = parse_module(&source).ok()?;
+
+        let kind = match find_binding(parsed.suite(), name)? {
+            Stmt::FunctionDef(_) => SymbolKind::Function,
+            Stmt::ClassDef(class_def) => {
+                let source_file = SourceFile::new(file_path, &source);
+                SymbolKind::Class(self.class_kind(class_def, &source_file, depth))
+            }
+            Stmt::Assign(assign) => self.classify_value(&assign.value, file_path, depth),
+            Stmt::AnnAssign(assign) => match &assign.value {
+                Some(value) => self.classify_value(value, file_path, depth),
+                None => SymbolKind::Unknown,
+            },
+            Stmt::Import(import) => {
+                // `import a.b` binds `a`, while `import a.b as c` binds `a.b`
+                let alias = import.names.iter().find(|alias| alias_binds(alias, name))?;
+                let module = match &alias.asname {
+                    Some(_) => alias.name.as_str(),
+                    None => name,
+                };
+                self.resolve_import(file_path, 0, module)
+                    .map_or(SymbolKind::Unknown, SymbolKind::Module)
+            }
+            Stmt::ImportFrom(import) => {
+                let alias = import.names.iter().find(|alias| alias_binds(alias, name))?;
+                let module = import.module.as_ref().map_or("", |m| m.as_str());
+                let imported = alias.name.as_str();
+
+                // The name is either defined in the module or is one of its submodules
+                let module_file = self.resolve_import(file_path, import.level, module);
+                let definition = module_file
+                    .as_ref()
+                    .and_then(|module_file| self.classify(module_file, imported, depth + 1));
+                let submodule = || {
+                    let submodule = if module.is_empty() {
+                        imported.to_string()
+                    } else {
+                        format!("{}.{}", module, imported)
+                    };
+                    self.resolve_import(file_path, import.level, &submodule)
+                        .map(SymbolKind::Module)
+                };
+                match (definition.or_else(submodule), module_file) {
+                    (Some(kind), _) => kind,
+                    // A module we can read that doesn't bind the name makes the import broken
+                    (None, Some(module_file)) if !binds_dynamically(&module_file) => return None,
+                    (None, _) => SymbolKind::Unknown,
+                }
+            }
+            _ => SymbolKind::Unknown,
+        };
+        Some(kind)
+    }
+
+    /// Get the qualified name a module-level name is imported or aliased from
+    /// Example: `from .models import Model as Net` in `pkg` -> "pkg.models.Model" for `Net`
+    fn reexport(&self, module: &str, name: &str) -> Option<String> {
+        let file_path =
+            PythonAnalyzer::resolve_module(module, self.workspace_root, self.python_interpreter)
+                .ok()?;
+        let source = fs::read_to_string(&file_path).ok()?;
+        let parsed = parse_module(&source).ok()?;
+
+        match find_binding(parsed.suite(), name)? {
+            Stmt::ImportFrom(import) => {
+                let alias = import.names.iter().find(|alias| alias_binds(alias, name))?;
+                let mut segments: Vec<&str> = Vec::new();
+                if import.level > 0 {
+                    // A package's `__init__` is relative to the package, a module to its parent
+                    segments.extend(module.split('.'));
+                    if file_path.file_stem().is_some_and(|stem| stem != "__init__") {
+                        segments.pop();
+                    }
+                    for _ in 1..import.level {
+                        segments.pop()?;
+                    }
+                }
+                if let Some(imported_from) = &import.module {
+                    segments.extend(imported_from.as_str().split('.'));
+                }
+                segments.push(alias.name.as_str());
+                Some(segments.join("."))
+            }
+            // `run = _run` aliases a name of the same module
+            Stmt::Assign(assign) => match assign.value.as_ref() {
+                Expr::Name(alias) => Some(format!("{}.{}", module, alias.id.as_str())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Classify a possibly dotted name, where each prefix must be a module
+    /// Example: "nn.Module" with `from torch import nn` -> the `Module` class of `torch.nn`
+    fn classify_dotted(&self, file_path: &Path, name: &str, depth: usize) -> Option<SymbolKind> {
+        let mut segments = name.split('.');
+        let mut kind = self.classify(file_path, segments.next()?, depth)?;
+        for segment in segments {
+            let SymbolKind::Module(module_file) = kind else {
+                return Some(SymbolKind::Unknown);
+            };
+            kind = self.classify(&module_file, segment, depth + 1)?;
+        }
+        Some(kind)
+    }
+
+    /// Classify the value assigned to a name
+    fn classify_value(&self, value: &Expr, file_path: &Path, depth: usize) -> SymbolKind {
+        match value {
+            Expr::StringLiteral(_)
+            | Expr::BytesLiteral(_)
+            | Expr::NumberLiteral(_)
+            | Expr::BooleanLiteral(_)
+            | Expr::NoneLiteral(_)
+            | Expr::FString(_)
+            | Expr::List(_)
+            | Expr::Tuple(_)
+            | Expr::Dict(_)
+            | Expr::Set(_) => SymbolKind::Constant,
+            Expr::Lambda(_) => SymbolKind::Function,
+            // `Alias = Model` is whatever it aliases
+            Expr::Name(alias) => self
+                .classify(file_path, alias.id.as_str(), depth + 1)
+                .unwrap_or(SymbolKind::Unknown),
+            _ => SymbolKind::Unknown,
+        }
+    }
+
+    /// Work out whether a class is abstract or a protocol, following base classes in reach
+    fn class_kind(
+        &self,
+        class_def: &ast::StmtClassDef,
+        source_file: &SourceFile,
+        depth: usize,
+    ) -> ClassKind {
+        let mut kind = ClassKind::default();
+        let mut inherited = Vec::new();
+        let mut inherited_fields: Option<Vec<ParameterInfo>> = None;
+        let mut is_typed_dict = false;
+
+        for base in class_def.bases() {
+            // `Protocol[T]` and `Generic[T]` are subscripted bases
+            let base = match base {
+                Expr::Subscript(subscript) => subscript.value.as_ref(),
+                base => base,
+            };
+            let base_name = expr_to_string(base);
+            let short_name = base_name.rsplit('.').next().unwrap_or_default();
+            kind.ancestors.push(short_name.to_string());
+            match short_name {
+                "ABC" => kind.is_abc = true,
+                "Protocol" => kind.is_protocol = true,
+                "TypedDict" => is_typed_dict = true,
+                name if TERMINAL_BASES.contains(&name) => {}
+                _ => match self.classify_dotted(&source_file.path, &base_name, depth + 1) {
+                    Some(SymbolKind::Class(base_kind)) => {
+                        kind.is_abc |= base_kind.is_abc || base_kind.is_protocol;
+                        kind.unresolved_bases |= base_kind.unresolved_bases;
+                        inherited.extend(base_kind.abstract_methods);
+                        if let Some(structure) = base_kind.structure {
+                            inherited_fields
+                                .get_or_insert_with(Vec::new)
+                                .extend(structure.parameters);
+                        }
+                        for ancestor in base_kind.ancestors {
+                            if !kind.ancestors.contains(&ancestor) {
+                                kind.ancestors.push(ancestor);
+                            }
+                        }
+                    }
+                    _ => kind.unresolved_bases = true,
+                },
+            }
+        }
+        kind.is_abc |= class_def.keywords().iter().any(|keyword| {
+            keyword
+                .arg
+                .as_ref()
+                .is_some_and(|arg| arg.as_str() == "metaclass")
+                && expr_to_string(&keyword.value).ends_with("ABCMeta")
+        });
+
+        // Methods defined here implement inherited abstract methods
+        let mut implemented = Vec::new();
+        for stmt in &class_def.body {
+            match stmt {
+                Stmt::FunctionDef(func_def) if is_abstract_method(func_def) => {
+                    kind.abstract_methods.push(func_def.name.to_string());
+                }
+                Stmt::FunctionDef(func_def) => implemented.push(func_def.name.to_string()),
+                Stmt::Assign(assign) => {
+                    implemented.extend(assign.targets.iter().map(expr_to_string))
+                }
+                Stmt::AnnAssign(assign) => implemented.push(expr_to_string(&assign.target)),
+                _ => {}
+            }
+        }
+        for method in inherited {
+            if !implemented.contains(&method) && !kind.abstract_methods.contains(&method) {
+                kind.abstract_methods.push(method);
+            }
+        }
+
+        // Dataclasses and TypedDicts, including subclasses of them, are built from their fields
+        if is_dataclass(class_def) || is_typed_dict || inherited_fields.is_some() {
+            let mut fields = inherited_fields.unwrap_or_default();
+            for field in structure_fields(class_def, source_file, is_typed_dict) {
+                match fields.iter_mut().find(|f| f.name == field.name) {
+                    Some(existing) => *existing = field,
+                    None => fields.push(field),
+                }
+            }
+            kind.structure = Some(FunctionSignature {
+                name: class_def.name.to_string(),
+                parameters: fields,
+                docstring: extract_docstring(&class_def.body),
+                file_path: source_file.path.clone(),
+                line: source_file.line(class_def.name.start()),
+                column: source_file.column(class_def.name.start()),
+                end_column: source_file.column(class_def.name.end()),
+                ..Default::default()
+            });
+        }
+
+        kind
+    }
+
+    /// Resolve the module of an import statement, which is relative to the file for `level > 0`
+    /// Example: `from ..models import x` in `pkg/sub/mod.py` -> level 2, module "models"
+    fn resolve_import(&self, file_path: &Path, level: u32, module: &str) -> Option<PathBuf> {
+        if level == 0 {
+            return PythonAnalyzer::resolve_module(
+                module,
+                self.workspace_root,
+                self.python_interpreter,
+            )
+            .ok();
+        }
+
+        let mut module_path = file_path.parent()?.to_path_buf();
+        for _ in 1..level {
+            module_path = module_path.parent()?.to_path_buf();
+        }
+        module_path.extend(module.split('.').filter(|part| !part.is_empty()));
+
+        [
+            module_path.join("__init__.pyi"),
+            module_path.join("__init__.py"),
+            module_path.with_extension("pyi"),
+            module_path.with_extension("py"),
+        ]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+    }
+}
+
+/// Check if a class is decorated with `@dataclass` or `@dataclass(...)`
+fn is_dataclass(class_def: &ast::StmtClassDef) -> bool {
+    class_def.decorator_list.iter().any(|decorator| {
+        let func = match &decorator.expression {
+            Expr::Call(call) => call.func.as_ref(),
+            expr => expr,
+        };
+        matches!(
+            expr_to_string(func).as_str(),
+            "dataclass" | "dataclasses.dataclass"
+        )
+    })
+}
+
+/// Extract the fields of a dataclass or TypedDict from the annotated names in its body
+/// TypedDict fields are optional with `total=False` or `NotRequired[...]`
+fn structure_fields(
+    class_def: &ast::StmtClassDef,
+    source_file: &SourceFile,
+    is_typed_dict: bool,
+) -> Vec<ParameterInfo> {
+    let total = !class_def.keywords().iter().any(|keyword| {
+        keyword
+            .arg
+            .as_ref()
+            .is_some_and(|arg| arg.as_str() == "total")
+            && matches!(&keyword.value, Expr::BooleanLiteral(b) if !b.value)
+    });
+
+    let mut fields = Vec::new();
+    for stmt in &class_def.body {
+        let Stmt::AnnAssign(assign) = stmt else {
+            continue;
+        };
+        let Expr::Name(name) = assign.target.as_ref() else {
+            continue;
+        };
+        let mut annotation = expr_to_string(&assign.annotation);
+        let wrapper = annotation.split('[').next().unwrap_or_default();
+        let wrapper = wrapper.rsplit('.').next().unwrap_or_default().to_string();
+        if matches!(wrapper.as_str(), "ClassVar" | "KW_ONLY" | "InitVar") {
+            continue;
+        }
+
+        let mut has_default = false;
+        let mut default_value = None;
+        if is_typed_dict {
+            // `Required[int]` and `NotRequired[int]` override the class's totality
+            if let Expr::Subscript(subscript) = assign.annotation.as_ref() {
+                if matches!(wrapper.as_str(), "Required" | "NotRequired") {
+                    annotation = expr_to_string(&subscript.slice);
+                }
+            }
+            has_default = match wrapper.as_str() {
+                "Required" => false,
+                "NotRequired" => true,
+                _ => !total,
+            };
+        } else if let Some(value) = &assign.value {
+            // `field(init=False)` is not a constructor argument, and `field()` has no default
+            if let Expr::Call(call) = value.as_ref() {
+                if expr_to_string(&call.func).ends_with("field") {
+                    let keyword = |name: &str| {
+                        call.arguments
+                            .keywords
+                            .iter()
+                            .find(|k| k.arg.as_ref().is_some_and(|arg| arg.as_str() == name))
+                    };
+                    if keyword("init")
+                        .is_some_and(|k| matches!(&k.value, Expr::BooleanLiteral(b) if !b.value))
+                    {
+                        continue;
+                    }
+                    if let Some(default) = keyword("default") {
+                        has_default = true;
+                        default_value = Some(expr_to_string(&default.value));
+                    } else if let Some(factory) = keyword("default_factory") {
+                        has_default = true;
+                        default_value = Some(format!("{}()", expr_to_string(&factory.value)));
+                    }
+                } else {
+                    has_default = true;
+                    default_value = Some(expr_to_string(value));
+                }
+            } else {
+                has_default = true;
+                default_value = Some(expr_to_string(value));
+            }
+        }
+
+        fields.push(ParameterInfo {
+            name: name.id.to_string(),
+            type_annotation: Some(annotation),
+            default_value,
+            has_default,
+            line: source_file.line(name.start()),
+            column: source_file.column(name.start()),
+            end_column: source_file.column(name.end()),
+            ..Default::default()
+        });
+    }
+    fields
+}
+
+/// Find the statement that binds a name at module level
+/// Bindings inside `if` and `try` blocks count, e.g. `try: import x except ImportError: x = None`
+fn find_binding<'a>(body: &'a [Stmt], name: &str) -> Option<&'a Stmt> {
+    body.iter().find_map(|stmt| {
+        let binds = match stmt {
+            Stmt::FunctionDef(func_def) => func_def.name.as_str() == name,
+            Stmt::ClassDef(class_def) => class_def.name.as_str() == name,
+            Stmt::Assign(assign) => assign
+                .targets
+                .iter()
+                .any(|target| matches!(target, Expr::Name(n) if n.id.as_str() == name)),
+            Stmt::AnnAssign(assign) => {
+                assign.value.is_some()
+                    && matches!(assign.target.as_ref(), Expr::Name(n) if n.id.as_str() == name)
+            }
+            Stmt::Import(import) => import.names.iter().any(|alias| alias_binds(alias, name)),
+            Stmt::ImportFrom(import) => import.names.iter().any(|alias| alias_binds(alias, name)),
+            Stmt::If(if_stmt) => {
+                return find_binding(&if_stmt.body, name).or_else(|| {
+                    if_stmt
+                        .elif_else_clauses
+                        .iter()
+                        .find_map(|clause| find_binding(&clause.body, name))
+                });
+            }
+            Stmt::Try(try_stmt) => {
+                return find_binding(&try_stmt.body, name)
+                    .or_else(|| {
+                        try_stmt.handlers.iter().find_map(|handler| {
+                            let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                            find_binding(&handler.body, name)
+                        })
+                    })
+                    .or_else(|| find_binding(&try_stmt.orelse, name))
+                    .or_else(|| find_binding(&try_stmt.finalbody, name));
+            }
+            _ => false,
+        };
+        binds.then_some(stmt)
+    })
+}
+
+/// Check if a module can bind names `find_binding` doesn't see, through star imports or a
+/// module-level `__getattr__`. Modules that can't be read or parsed are given the benefit of
+/// the doubt
+fn binds_dynamically(file_path: &Path) -> bool {
+    let Some(parsed) = fs::read_to_string(file_path)
+        .ok()
+        .and_then(|source| parse_module(&source).ok())
+    else {
+        return true;
+    };
+    parsed.suite().iter().any(|stmt| match stmt {
+        Stmt::ImportFrom(import) => import.names.iter().any(|alias| alias.name.as_str() == "*"),
+        Stmt::FunctionDef(func_def) => func_def.name.as_str() == "__getattr__",
+        _ => false,
+    })
+}
+
+/// Collect the names bound at module level, like `find_binding` looks them up
+fn collect_bindings(body: &[Stmt], names: &mut Vec<String>) {
+    for stmt in body {
+        let bound: Vec<&str> = match stmt {
+            Stmt::FunctionDef(func_def) => vec![func_def.name.as_str()],
+            Stmt::ClassDef(class_def) => vec![class_def.name.as_str()],
+            Stmt::Assign(assign) => assign
+                .targets
+                .iter()
+                .filter_map(|target| match target {
+                    Expr::Name(n) => Some(n.id.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            Stmt::AnnAssign(assign) if assign.value.is_some() => match assign.target.as_ref() {
+                Expr::Name(n) => vec![n.id.as_str()],
+                _ => Vec::new(),
+            },
+            Stmt::Import(import) => import
+                .names
+                .iter()
+                .map(|alias| match &alias.asname {
+                    Some(asname) => asname.as_str(),
+                    None => alias.name.as_str().split('.').next().unwrap_or_default(),
+                })
+                .collect(),
+            Stmt::ImportFrom(import) => import
+                .names
+                .iter()
+                .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name).as_str())
+                .collect(),
+            Stmt::If(if_stmt) => {
+                collect_bindings(&if_stmt.body, names);
+                for clause in &if_stmt.elif_else_clauses {
+                    collect_bindings(&clause.body, names);
+                }
+                continue;
+            }
+            Stmt::Try(try_stmt) => {
+                collect_bindings(&try_stmt.body, names);
+                for handler in &try_stmt.handlers {
+                    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+                    collect_bindings(&handler.body, names);
+                }
+                collect_bindings(&try_stmt.orelse, names);
+                collect_bindings(&try_stmt.finalbody, names);
+                continue;
+            }
+            _ => continue,
+        };
+        // Dunders like `__all__` and star imports aren't symbols to suggest
+        for name in bound {
+            if name != "*" && !name.starts_with("__") && !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+}
+
+/// Check if an import alias binds a name, e.g. `a.b` binds `a` and `x as y` binds `y`
+fn alias_binds(alias: &ast::Alias, name: &str) -> bool {
+    match &alias.asname {
+        Some(asname) => asname.as_str() == name,
+        None => alias.name.as_str().split('.').next() == Some(name),
+    }
+}
+
+/// Check if a method is decorated with `@abstractmethod` or one of its older variants
+fn is_abstract_method(func_def: &ast::StmtFunctionDef) -> bool {
+    func_def.decorator_list.iter().any(|decorator| {
+        matches!(
+            expr_to_string(&decorator.expression)
+                .rsplit('.')
+                .next()
+                .unwrap_or_default(),
+            "abstractmethod" | "abstractproperty" | "abstractclassmethod" | "abstractstaticmethod"
+        )
+    })
+}
+
 /// Extract function signature from a function definition node
-fn extract_function_signature_from_def(func_def: &ast::StmtFunctionDef) -> FunctionSignature {
-    let parameters = extract_parameters(&func_def.parameters);
+fn extract_function_signature_from_def(
+    func_def: &ast::StmtFunctionDef,
+    source_file: &SourceFile,
+) -> FunctionSignature {
+    let mut parameters = extract_parameters(&func_def.parameters, source_file);
     let return_type = func_def.returns.as_ref().map(|e| expr_to_string(e));
     let docstring = extract_docstring(&func_def.body);
+    let mut deprecated = deprecation_from_decorators(&func_def.decorator_list);
+
+    // Runtime deprecations are only recognised in the top level of the body
+    // The function itself warns up front, a warning after other code may never be reached
+    let body = &func_def.body[usize::from(docstring.is_some())..];
+    let mut leading = true;
+    for stmt in body {
+        if let Stmt::If(if_stmt) = stmt {
+            // `if param is not None: warnings.warn(..., DeprecationWarning)`
+            let Some(name) = guarded_parameter(&if_stmt.test) else {
+                leading = false;
+                continue;
+            };
+            let message = if_stmt.body.iter().find_map(deprecation_from_warning);
+            if let Some(param) = parameters.iter_mut().find(|p| p.name == name) {
+                param.deprecated = param.deprecated.take().or(message);
+            }
+        } else if let Some(message) = deprecation_from_warning(stmt) {
+            if leading {
+                deprecated = deprecated.or(Some(message));
+            }
+        } else if !matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_)) {
+            // `import warnings` may come first, anything else ends the leading statements
+            leading = false;
+        }
+    }
 
     FunctionSignature {
         name: func_def.name.to_string(),
         parameters,
         return_type,
         docstring,
+        deprecated,
+        file_path: source_file.path.clone(),
+        line: source_file.line(func_def.name.start()),
+        column: source_file.column(func_def.name.start()),
+        end_column: source_file.column(func_def.name.end()),
     }
 }
 
 /// Extract class info from a class definition node
-fn extract_class_info_from_def(class_def: &ast::StmtClassDef) -> ClassInfo {
+fn extract_class_info_from_def(
+    class_def: &ast::StmtClassDef,
+    source_file: &SourceFile,
+) -> ClassInfo {
     let docstring = extract_docstring(&class_def.body);
 
     // Look for __init__ method
     let init_signature = class_def.body.iter().find_map(|stmt| {
         if let Stmt::FunctionDef(func_def) = stmt {
             if func_def.name.as_str() == "__init__" {
-                return Some(extract_function_signature_from_def(func_def));
+                return Some(extract_function_signature_from_def(func_def, source_file));
             }
         }
         None
@@ -447,15 +1459,25 @@ fn extract_class_info_from_def(class_def: &ast::StmtClassDef) -> ClassInfo {
         name: class_def.name.to_string(),
         docstring,
         init_signature,
+        deprecated: deprecation_from_decorators(&class_def.decorator_list),
+        file_path: source_file.path.clone(),
+        line: source_file.line(class_def.name.start()),
+        column: source_file.column(class_def.name.start()),
+        end_column: source_file.column(class_def.name.end()),
     }
 }
 
 /// Extract parameters from function parameters
-fn extract_parameters(params: &ast::Parameters) -> Vec<ParameterInfo> {
+fn extract_parameters(params: &ast::Parameters, source_file: &SourceFile) -> Vec<ParameterInfo> {
     let mut result = Vec::new();
 
-    // Process regular parameters and positional-only
-    for param_with_default in params.posonlyargs.iter().chain(params.args.iter()) {
+    // Process positional-only and regular parameters
+    let positional = params
+        .posonlyargs
+        .iter()
+        .map(|param| (param, true))
+        .chain(params.args.iter().map(|param| (param, false)));
+    for (param_with_default, is_positional_only) in positional {
         let param = &param_with_default.parameter;
         result.push(ParameterInfo {
             name: param.name.to_string(),
@@ -468,6 +1490,11 @@ fn extract_parameters(params: &ast::Parameters) -> Vec<ParameterInfo> {
             is_variadic: false,
             is_variadic_keyword: false,
             is_keyword_only: false,
+            is_positional_only,
+            deprecated: None,
+            line: source_file.line(param.name.start()),
+            column: source_file.column(param.name.start()),
+            end_column: source_file.column(param.name.end()),
         });
     }
 
@@ -481,6 +1508,11 @@ fn extract_parameters(params: &ast::Parameters) -> Vec<ParameterInfo> {
             is_variadic: true,
             is_variadic_keyword: false,
             is_keyword_only: false,
+            is_positional_only: false,
+            deprecated: None,
+            line: source_file.line(vararg.name.start()),
+            column: source_file.column(vararg.name.start()),
+            end_column: source_file.column(vararg.name.end()),
         });
     }
 
@@ -498,6 +1530,11 @@ fn extract_parameters(params: &ast::Parameters) -> Vec<ParameterInfo> {
             is_variadic: false,
             is_variadic_keyword: false,
             is_keyword_only: true,
+            is_positional_only: false,
+            deprecated: None,
+            line: source_file.line(param.name.start()),
+            column: source_file.column(param.name.start()),
+            end_column: source_file.column(param.name.end()),
         });
     }
 
@@ -511,12 +1548,93 @@ fn extract_parameters(params: &ast::Parameters) -> Vec<ParameterInfo> {
             is_variadic: false,
             is_variadic_keyword: true,
             is_keyword_only: false,
+            is_positional_only: false,
+            deprecated: None,
+            line: source_file.line(kwarg.name.start()),
+            column: source_file.column(kwarg.name.start()),
+            end_column: source_file.column(kwarg.name.end()),
         });
     }
 
     result
 }
 
+/// Get the message of a `@deprecated("...")` decorator, if present
+/// Supports bare `deprecated` as well as `typing_extensions.deprecated` and `warnings.deprecated`
+fn deprecation_from_decorators(decorators: &[ast::Decorator]) -> Option<String> {
+    decorators.iter().find_map(|decorator| {
+        let (func, message) = match &decorator.expression {
+            Expr::Call(call) => (
+                call.func.as_ref(),
+                call.arguments.args.first().and_then(string_literal),
+            ),
+            expr => (expr, None),
+        };
+        matches!(
+            expr_to_string(func).as_str(),
+            "deprecated" | "typing_extensions.deprecated" | "warnings.deprecated"
+        )
+        .then(|| message.unwrap_or_default())
+    })
+}
+
+/// Get the message of a `warnings.warn("...", DeprecationWarning)` statement, if it is one
+/// `FutureWarning` is the category for deprecations aimed at end users
+fn deprecation_from_warning(stmt: &Stmt) -> Option<String> {
+    let Stmt::Expr(expr_stmt) = stmt else {
+        return None;
+    };
+    let Expr::Call(call) = expr_stmt.value.as_ref() else {
+        return None;
+    };
+    if !matches!(
+        expr_to_string(&call.func).as_str(),
+        "warnings.warn" | "warn"
+    ) {
+        return None;
+    }
+
+    let arguments = &call.arguments;
+    let category = arguments.args.get(1).or_else(|| {
+        arguments
+            .keywords
+            .iter()
+            .find(|k| k.arg.as_ref().is_some_and(|arg| arg.as_str() == "category"))
+            .map(|k| &k.value)
+    })?;
+    let category = expr_to_string(category);
+    matches!(
+        category.rsplit('.').next().unwrap_or_default(),
+        "DeprecationWarning" | "FutureWarning"
+    )
+    .then(|| {
+        arguments
+            .args
+            .first()
+            .and_then(string_literal)
+            .unwrap_or_default()
+    })
+}
+
+/// Get the parameter name tested by an `if param is not None` condition
+fn guarded_parameter(test: &Expr) -> Option<&str> {
+    let Expr::Compare(compare) = test else {
+        return None;
+    };
+    match (compare.left.as_ref(), &*compare.ops, &*compare.comparators) {
+        (Expr::Name(name), [ast::CmpOp::IsNot], [Expr::NoneLiteral(_)]) => Some(name.id.as_str()),
+        _ => None,
+    }
+}
+
+/// Get the value of a plain string literal expression
+fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::StringLiteral(s) => Some(s.value.to_string()),
+        _ => None,
+    }
+}
+
 /// Extract docstring from function or class body
 fn extract_docstring(body: &[Stmt]) -> Option<String> {
     if let Some(Stmt::Expr(expr_stmt)) = body.first() {
@@ -535,12 +1653,19 @@ fn expr_to_string(expr: &Expr) -> String {
             format!("{}.{}", expr_to_string(&attr.value), attr.attr)
         }
         Expr::Subscript(subscript) => {
-            format!(
-                "{}[{}]",
-                expr_to_string(&subscript.value),
-                expr_to_string(&subscript.slice)
-            )
+            // `dict[str, int]` has a tuple slice, which should not be rendered with parentheses
+            let slice = match subscript.slice.as_ref() {
+                Expr::Tuple(tuple) => tuple
+                    .elts
+                    .iter()
+                    .map(expr_to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                slice => expr_to_string(slice),
+            };
+            format!("{}[{}]", expr_to_string(&subscript.value), slice)
         }
+        Expr::EllipsisLiteral(_) => "...".to_string(),
         Expr::Tuple(tuple) => {
             let elements: Vec<String> = tuple.elts.iter().map(expr_to_string).collect();
             format!("({})", elements.join(", "))
@@ -740,9 +1865,108 @@ mod tests {
             .find(|p| p.name == "keyword_only")
             .unwrap();
         assert!(kw_only.is_keyword_only);
+        assert!(sig.parameters[0].is_positional_only);
+        assert!(!sig.parameters[1].is_positional_only);
+        assert!(PythonAnalyzer::format_signature(&sig).contains("(pos_only, /, regular, *args"));
 
-        // Check return type
-        assert!(sig.return_type.is_some());
+        // Check return type, rendered without tuple parentheses in the subscript
+        assert_eq!(sig.return_type.as_deref(), Some("dict[str, int]"));
+    }
+
+    #[test]
+    fn test_extract_function_location() {
+        let examples_dir = get_resources_dir();
+        let test_file = examples_dir.join("test_module.py");
+
+        let sig =
+            PythonAnalyzer::extract_function_signature(&test_file, "function_with_params").unwrap();
+        assert_eq!(sig.file_path, test_file);
+        assert_eq!(sig.line, 12);
+        assert_eq!((sig.column, sig.end_column), (4, 24));
+        assert!(sig.parameters.iter().all(|p| p.line == 12));
+
+        let class_info = PythonAnalyzer::extract_class_info(&test_file, "ClassWithInit").unwrap();
+        assert_eq!(class_info.line, 46);
+        assert_eq!((class_info.column, class_info.end_column), (6, 19));
+        let init_sig = class_info.init_signature.unwrap();
+        assert_eq!(init_sig.file_path, test_file);
+        assert_eq!(init_sig.line, 49);
+        assert_eq!((init_sig.column, init_sig.end_column), (8, 16));
+    }
+
+    #[test]
+    fn test_module_name() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let package = dir.path().join("src/myproject/models");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(dir.path().join("src/myproject/__init__.py"), "").unwrap();
+        fs::write(package.join("__init__.py"), "").unwrap();
+
+        let name = |path: &str| PythonAnalyzer::module_name(&dir.path().join(path));
+        assert_eq!(
+            name("src/myproject/models/encoder.py").as_deref(),
+            Some("myproject.models.encoder")
+        );
+        assert_eq!(
+            name("src/myproject/models/__init__.py").as_deref(),
+            Some("myproject.models")
+        );
+        assert_eq!(
+            name("src/myproject/models").as_deref(),
+            Some("myproject.models")
+        );
+        // New paths don't exist yet
+        assert_eq!(
+            name("src/myproject/layers").as_deref(),
+            Some("myproject.layers")
+        );
+        assert_eq!(name("src/script.py").as_deref(), Some("script"));
+    }
+
+    #[test]
+    fn test_submodules_and_symbols() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let package = dir.path().join("myproject/models");
+        fs::create_dir_all(&package).unwrap();
+        fs::create_dir_all(dir.path().join("myproject/data")).unwrap();
+        fs::write(dir.path().join("myproject/__init__.py"), "").unwrap();
+        fs::write(package.join("__init__.py"), "").unwrap();
+        fs::write(dir.path().join("myproject/callbacks.py"), "").unwrap();
+        fs::write(
+            package.join("encoder.py"),
+            "import torch.nn as nn\nfrom typing import Any\n\n__all__ = ['Encoder']\nSIZE = 3\n\ntry:\n    import numpy\nexcept ImportError:\n    pass\n\n\nclass Encoder:\n    hidden = 1\n\n\ndef build():\n    pass\n",
+        )
+        .unwrap();
+
+        let root = Some(dir.path());
+        // Directories without `__init__.py` aren't packages
+        assert_eq!(
+            PythonAnalyzer::submodules(Some("myproject"), root, None),
+            vec!["callbacks", "models"]
+        );
+        assert!(PythonAnalyzer::submodules(Some("myproject.callbacks"), root, None).is_empty());
+        assert!(PythonAnalyzer::submodules(None, root, None).contains(&"myproject".to_string()));
+
+        assert_eq!(
+            PythonAnalyzer::module_symbols(&package.join("encoder.py")),
+            vec!["nn", "Any", "SIZE", "numpy", "Encoder", "build"]
+        );
+    }
+
+    #[test]
+    fn test_definition_at_position() {
+        let source = "import os\n\n\nclass Model:\n    def forward(self):\n        pass\n\n\ndef build():\n    pass\n";
+        assert_eq!(
+            PythonAnalyzer::definition_at_position(source, 3, 8),
+            Some(("Model".to_string(), 3))
+        );
+        assert_eq!(
+            PythonAnalyzer::definition_at_position(source, 8, 4),
+            Some(("build".to_string(), 8))
+        );
+        // Methods and keywords aren't module-level definition names
+        assert_eq!(PythonAnalyzer::definition_at_position(source, 4, 10), None);
+        assert_eq!(PythonAnalyzer::definition_at_position(source, 3, 1), None);
     }
 
     #[test]
@@ -814,6 +2038,21 @@ mod tests {
         assert_eq!(init_sig.parameters.len(), 3);
     }
 
+    #[test]
+    fn test_extract_deprecated_class() {
+        let examples_dir = get_resources_dir();
+        let test_file = examples_dir.join("test_module.py");
+
+        let class_info = PythonAnalyzer::extract_class_info(&test_file, "DeprecatedClass").unwrap();
+        assert_eq!(
+            class_info.deprecated.as_deref(),
+            Some("Use ClassWithInit instead")
+        );
+
+        let formatted = PythonAnalyzer::format_class(&class_info);
+        assert!(formatted.contains("**Deprecated**: Use ClassWithInit instead"));
+    }
+
     #[test]
     fn test_extract_nonexistent_class() {
         let examples_dir = get_resources_dir();
@@ -823,6 +2062,61 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_extract_deprecated_function() {
+        let examples_dir = get_resources_dir();
+        let test_file = examples_dir.join("test_module.py");
+
+        let sig =
+            PythonAnalyzer::extract_function_signature(&test_file, "deprecated_function").unwrap();
+        assert_eq!(
+            sig.deprecated.as_deref(),
+            Some("Use function_with_return instead")
+        );
+
+        let sig =
+            PythonAnalyzer::extract_function_signature(&test_file, "warned_function").unwrap();
+        assert_eq!(
+            sig.deprecated.as_deref(),
+            Some("warned_function is deprecated")
+        );
+
+        let sig = PythonAnalyzer::extract_function_signature(&test_file, "future_warned_function")
+            .unwrap();
+        assert_eq!(
+            sig.deprecated.as_deref(),
+            Some("future_warned_function will be removed")
+        );
+
+        let sig =
+            PythonAnalyzer::extract_function_signature(&test_file, "simple_function").unwrap();
+        assert!(sig.deprecated.is_none());
+
+        // Only a warning before any other code deprecates the function, in the exact category
+        for name in ["late_warned_function", "pending_warned_function"] {
+            let sig = PythonAnalyzer::extract_function_signature(&test_file, name).unwrap();
+            assert!(sig.deprecated.is_none(), "{} is not deprecated", name);
+        }
+    }
+
+    #[test]
+    fn test_extract_deprecated_parameter() {
+        let examples_dir = get_resources_dir();
+        let test_file = examples_dir.join("test_module.py");
+
+        let sig = PythonAnalyzer::extract_function_signature(
+            &test_file,
+            "function_with_deprecated_param",
+        )
+        .unwrap();
+        assert!(sig.deprecated.is_none());
+        assert!(sig.parameters[0].deprecated.is_none());
+        assert_eq!(
+            sig.parameters[1].deprecated.as_deref(),
+            Some("old_value is deprecated, use value")
+        );
+    }
+
     // ==================== extract_definition_info tests ====================
 
     #[test]
@@ -894,6 +2188,145 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // ==================== classify_symbol tests ====================
+
+    fn classify(symbol_name: &str) -> Option<SymbolKind> {
+        let resources_dir = get_resources_dir();
+        PythonAnalyzer::classify_symbol(
+            &resources_dir.join("test_targets.py"),
+            symbol_name,
+            Some(&resources_dir),
+            None,
+        )
+    }
+
+    fn classify_class(symbol_name: &str) -> ClassKind {
+        match classify(symbol_name) {
+            Some(SymbolKind::Class(kind)) => kind,
+            other => panic!("Expected a class, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_classify_constants() {
+        assert_eq!(classify("DEFAULT_LR"), Some(SymbolKind::Constant));
+        assert_eq!(classify("LAYERS"), Some(SymbolKind::Constant));
+        assert_eq!(classify("Missing"), None);
+    }
+
+    #[test]
+    fn test_classify_imports() {
+        let resources_dir = get_resources_dir();
+        assert_eq!(
+            classify("submodule"),
+            Some(SymbolKind::Module(
+                resources_dir.join("test_package").join("submodule.py")
+            ))
+        );
+        assert_eq!(
+            classify("ReexportedClass"),
+            Some(SymbolKind::Class(ClassKind::default()))
+        );
+        assert_eq!(
+            classify("ModelAlias"),
+            Some(SymbolKind::Class(ClassKind::default()))
+        );
+
+        // Importing a name the module doesn't bind fails
+        assert_eq!(classify("BrokenReexport"), None);
+
+        // Relative re-export from a package `__init__.py`
+        let kind = PythonAnalyzer::classify_symbol(
+            &resources_dir.join("test_package").join("__init__.py"),
+            "SubmoduleClass",
+            Some(&resources_dir),
+            None,
+        );
+        assert_eq!(kind, Some(SymbolKind::Class(ClassKind::default())));
+    }
+
+    #[test]
+    fn test_reexport_chain() {
+        let resources_dir = get_resources_dir();
+        let chain =
+            |target: &str| PythonAnalyzer::reexport_chain(target, Some(&resources_dir), None);
+
+        assert_eq!(
+            chain("test_targets.run_command"),
+            vec!["test_targets.run_command", "subprocess.run"]
+        );
+        assert_eq!(
+            chain("test_targets.ModelAlias"),
+            vec![
+                "test_targets.ModelAlias",
+                "test_targets.ReexportedClass",
+                "test_package.submodule.SubmoduleClass"
+            ]
+        );
+        // Relative imports are resolved against the package
+        assert_eq!(
+            chain("test_package.SubmoduleClass"),
+            vec![
+                "test_package.SubmoduleClass",
+                "test_package.submodule.SubmoduleClass"
+            ]
+        );
+        assert_eq!(chain("test_targets.Adam"), vec!["test_targets.Adam"]);
+    }
+
+    #[test]
+    fn test_classify_abstract_classes() {
+        let base = classify_class("BaseModel");
+        assert!(base.is_abstract());
+        assert_eq!(base.abstract_methods, vec!["forward", "loss"]);
+
+        let partial = classify_class("PartialModel");
+        assert!(partial.is_abstract());
+        assert_eq!(partial.abstract_methods, vec!["loss"]);
+
+        let concrete = classify_class("ConcreteModel");
+        assert!(!concrete.is_abstract());
+        assert_eq!(concrete.ancestors, vec!["PartialModel", "BaseModel", "ABC"]);
+        assert!(!concrete.unresolved_bases);
+        assert!(classify_class("MetaBase").is_abstract());
+        assert!(classify_class("Encoder").is_protocol);
+
+        // `@abstractmethod` has no effect without ABCMeta
+        let not_enforced = classify_class("NotEnforced");
+        assert!(!not_enforced.is_abstract());
+        assert_eq!(not_enforced.abstract_methods, vec!["run"]);
+    }
+
+    #[test]
+    fn test_classify_structured_configs() {
+        let optim = classify_class("OptimConfig").structure.unwrap();
+        let fields: Vec<_> = optim
+            .parameters
+            .iter()
+            .map(|p| (p.name.as_str(), p.has_default))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("lr", false), ("momentum", true), ("betas", true)]
+        );
+        assert_eq!(optim.parameters[2].default_value.as_deref(), Some("list()"));
+
+        let scheduler = classify_class("SchedulerConfig").structure.unwrap();
+        let names: Vec<_> = scheduler
+            .parameters
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["lr", "momentum", "betas", "warmup"]);
+
+        let data = classify_class("DataOptions").structure.unwrap();
+        assert!(data.parameters[0].has_default);
+        assert!(!data.parameters[1].has_default);
+        assert_eq!(data.parameters[1].type_annotation.as_deref(), Some("str"));
+
+        assert!(classify_class("ConcreteModel").structure.is_none());
+    }
+
     // ==================== format_signature tests ====================
 
     #[test]
@@ -903,6 +2336,7 @@ mod tests {
             parameters: vec![],
             return_type: None,
             docstring: None,
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_signature(&sig);
@@ -924,6 +2358,7 @@ mod tests {
                     is_variadic: false,
                     is_variadic_keyword: false,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
                 ParameterInfo {
                     name: "y".to_string(),
@@ -933,10 +2368,12 @@ mod tests {
                     is_variadic: false,
                     is_variadic_keyword: false,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
             ],
             return_type: Some("bool".to_string()),
             docstring: Some("Test docstring".to_string()),
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_signature(&sig);
@@ -957,6 +2394,7 @@ mod tests {
                     is_variadic: true,
                     is_variadic_keyword: false,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
                 ParameterInfo {
                     name: "kwargs".to_string(),
@@ -966,10 +2404,12 @@ mod tests {
                     is_variadic: false,
                     is_variadic_keyword: true,
                     is_keyword_only: false,
+                    ..Default::default()
                 },
             ],
             return_type: None,
             docstring: None,
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_signature(&sig);
@@ -985,6 +2425,7 @@ mod tests {
             name: "TestClass".to_string(),
             docstring: Some("A test class".to_string()),
             init_signature: None,
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_class(&class_info);
@@ -1009,6 +2450,7 @@ mod tests {
                         is_variadic: false,
                         is_variadic_keyword: false,
                         is_keyword_only: false,
+                        ..Default::default()
                     },
                     ParameterInfo {
                         name: "value".to_string(),
@@ -1018,11 +2460,14 @@ mod tests {
                         is_variadic: false,
                         is_variadic_keyword: false,
                         is_keyword_only: false,
+                        ..Default::default()
                     },
                 ],
                 return_type: None,
                 docstring: None,
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_class(&class_info);
@@ -1047,6 +2492,7 @@ mod tests {
                         is_variadic: false,
                         is_variadic_keyword: false,
                         is_keyword_only: false,
+                        ..Default::default()
                     },
                     ParameterInfo {
                         name: "name".to_string(),
@@ -1056,17 +2502,82 @@ mod tests {
                         is_variadic: false,
                         is_variadic_keyword: false,
                         is_keyword_only: false,
+                        ..Default::default()
                     },
                 ],
                 return_type: None,
                 docstring: None,
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         let formatted = PythonAnalyzer::format_class(&class_info);
         assert!(formatted.contains("name: str = 'default'"));
     }
 
+    #[test]
+    fn test_format_parameter() {
+        let param = ParameterInfo {
+            name: "hidden".to_string(),
+            type_annotation: Some("int".to_string()),
+            default_value: Some("128".to_string()),
+            has_default: true,
+            ..Default::default()
+        };
+
+        let formatted = PythonAnalyzer::format_parameter(&param, Some("Hidden size"));
+        assert_eq!(
+            formatted,
+            "```python\n(parameter) hidden: int = 128\n```\n\n---\n\nHidden size"
+        );
+    }
+
+    #[test]
+    fn test_parameter_doc() {
+        let google = "Build a model.\n\n    Args:\n        dim (int): Input size\n            of the model\n        **kwargs: Extra options\n\n    Returns:\n        dim: Not a parameter\n    ";
+        assert_eq!(
+            PythonAnalyzer::parameter_doc(google, "dim").as_deref(),
+            Some("Input size\nof the model")
+        );
+        assert_eq!(
+            PythonAnalyzer::parameter_doc(google, "kwargs").as_deref(),
+            Some("Extra options")
+        );
+
+        let numpy = "Build a model.\n\nParameters\n----------\nx, dim : int\n    Input size\nrate : float\n\nReturns\n-------\nModel\n";
+        assert_eq!(
+            PythonAnalyzer::parameter_doc(numpy, "dim").as_deref(),
+            Some("Input size")
+        );
+        assert_eq!(PythonAnalyzer::parameter_doc(numpy, "rate"), None);
+
+        let sphinx = "Build a model.\n\n:param int dim: Input size\n:returns: A model\n";
+        assert_eq!(
+            PythonAnalyzer::parameter_doc(sphinx, "dim").as_deref(),
+            Some("Input size")
+        );
+        assert_eq!(PythonAnalyzer::parameter_doc(sphinx, "other"), None);
+    }
+
+    #[test]
+    fn test_keyword_parameter() {
+        let param = |name: &str, is_variadic_keyword: bool| ParameterInfo {
+            name: name.to_string(),
+            is_variadic_keyword,
+            ..Default::default()
+        };
+        let mut sig = FunctionSignature {
+            parameters: vec![param("dim", false)],
+            ..Default::default()
+        };
+        assert_eq!(sig.keyword_parameter("dim").unwrap().name, "dim");
+        assert!(sig.keyword_parameter("extra").is_none());
+
+        sig.parameters.push(param("options", true));
+        assert_eq!(sig.keyword_parameter("extra").unwrap().name, "options");
+    }
+
     // ==================== Environment discovery and module resolution tests ====================
 
     mod environment_tests {
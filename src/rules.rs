@@ -0,0 +1,434 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+/// Name of the project configuration file, looked up in each workspace folder
+pub const PROJECT_CONFIG_FILE: &str = ".hydra-lsp.yaml";
+
+/// Severity a diagnostic rule is reported with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+    Off,
+}
+
+impl RuleSeverity {
+    /// Get the LSP severity, or `None` if the rule is turned off
+    pub fn to_lsp(self) -> Option<DiagnosticSeverity> {
+        match self {
+            RuleSeverity::Error => Some(DiagnosticSeverity::ERROR),
+            RuleSeverity::Warning => Some(DiagnosticSeverity::WARNING),
+            RuleSeverity::Information => Some(DiagnosticSeverity::INFORMATION),
+            RuleSeverity::Hint => Some(DiagnosticSeverity::HINT),
+            RuleSeverity::Off => None,
+        }
+    }
+}
+
+/// A diagnostic rule, identified by the code it reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub code: &'static str,
+    /// Rule set the rule belongs to, which can be configured as a whole
+    pub rule_set: &'static str,
+    /// Severity used when the rule is not configured
+    pub severity: RuleSeverity,
+}
+
+const fn rule(code: &'static str, rule_set: &'static str, severity: RuleSeverity) -> Rule {
+    Rule {
+        code,
+        rule_set,
+        severity,
+    }
+}
+
+pub const YAML_SYNTAX_ERROR: Rule = rule("yaml-syntax-error", "syntax", RuleSeverity::Error);
+pub const INVALID_TARGET: Rule = rule("invalid-target", "resolution", RuleSeverity::Error);
+pub const MODULE_NOT_FOUND: Rule = rule("module-not-found", "resolution", RuleSeverity::Error);
+pub const SYMBOL_NOT_FOUND: Rule = rule("symbol-not-found", "resolution", RuleSeverity::Error);
+pub const DYNAMIC_TARGET: Rule = rule("dynamic-target", "resolution", RuleSeverity::Information);
+pub const TARGET_IS_MODULE: Rule = rule("target-is-module", "instantiation", RuleSeverity::Error);
+pub const NOT_CALLABLE: Rule = rule("not-callable", "instantiation", RuleSeverity::Error);
+pub const ABSTRACT_CLASS: Rule = rule("abstract-class", "instantiation", RuleSeverity::Error);
+pub const INVALID_CONVERT: Rule = rule("invalid-convert", "instantiation", RuleSeverity::Warning);
+pub const UNKNOWN_PARAMETER: Rule = rule("unknown-parameter", "parameters", RuleSeverity::Error);
+pub const MISSING_PARAMETER: Rule = rule("missing-parameter", "parameters", RuleSeverity::Error);
+pub const POSITIONAL_ONLY_PARAMETER: Rule = rule(
+    "positional-only-parameter",
+    "parameters",
+    RuleSeverity::Error,
+);
+pub const KWARGS_PARAMETER: Rule = rule("kwargs-parameter", "parameters", RuleSeverity::Hint);
+pub const PARTIAL_PARAMETER: Rule = rule("partial-parameter", "parameters", RuleSeverity::Hint);
+pub const PARTIAL_SIGNATURE_MISMATCH: Rule = rule(
+    "partial-signature-mismatch",
+    "parameters",
+    RuleSeverity::Error,
+);
+pub const TYPE_MISMATCH: Rule = rule("type-mismatch", "types", RuleSeverity::Warning);
+pub const CONVERT_MISMATCH: Rule = rule("convert-mismatch", "types", RuleSeverity::Warning);
+pub const INCOMPATIBLE_NESTED_TARGET: Rule =
+    rule("incompatible-nested-target", "types", RuleSeverity::Warning);
+pub const DEPRECATED_TARGET: Rule = rule("deprecated-target", "deprecation", RuleSeverity::Warning);
+pub const DEPRECATED_PARAMETER: Rule =
+    rule("deprecated-parameter", "deprecation", RuleSeverity::Warning);
+pub const MISSING_MANDATORY_VALUE: Rule =
+    rule("missing-mandatory-value", "values", RuleSeverity::Warning);
+pub const MANDATORY_VALUE: Rule = rule("mandatory-value", "values", RuleSeverity::Information);
+pub const MANDATORY_WITH_DEFAULT: Rule =
+    rule("mandatory-with-default", "values", RuleSeverity::Hint);
+pub const UNUSED_SUPPRESSION: Rule = rule("unused-suppression", "suppression", RuleSeverity::Hint);
+pub const DANGEROUS_TARGET: Rule = rule("dangerous-target", "security", RuleSeverity::Off);
+pub const UNTRUSTED_TARGET_MODULE: Rule =
+    rule("untrusted-target-module", "security", RuleSeverity::Off);
+
+/// Every rule reported by the server
+pub const RULES: &[Rule] = &[
+    YAML_SYNTAX_ERROR,
+    INVALID_TARGET,
+    MODULE_NOT_FOUND,
+    SYMBOL_NOT_FOUND,
+    DYNAMIC_TARGET,
+    TARGET_IS_MODULE,
+    NOT_CALLABLE,
+    ABSTRACT_CLASS,
+    INVALID_CONVERT,
+    UNKNOWN_PARAMETER,
+    MISSING_PARAMETER,
+    POSITIONAL_ONLY_PARAMETER,
+    KWARGS_PARAMETER,
+    PARTIAL_PARAMETER,
+    PARTIAL_SIGNATURE_MISMATCH,
+    TYPE_MISMATCH,
+    CONVERT_MISMATCH,
+    INCOMPATIBLE_NESTED_TARGET,
+    DEPRECATED_TARGET,
+    DEPRECATED_PARAMETER,
+    MISSING_MANDATORY_VALUE,
+    MANDATORY_VALUE,
+    MANDATORY_WITH_DEFAULT,
+    UNUSED_SUPPRESSION,
+    DANGEROUS_TARGET,
+    UNTRUSTED_TARGET_MODULE,
+];
+
+/// Find a rule by its diagnostic code
+pub fn find_rule(code: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.code == code)
+}
+
+/// Rule severities for the files under a directory
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RuleOverride {
+    /// Directory the override applies to, relative to the workspace folder
+    pub path: PathBuf,
+    /// Severity by rule code or rule set name
+    pub rules: HashMap<String, RuleSeverity>,
+}
+
+/// Callables that run commands, evaluate code or load pickled data when a config names them
+pub const DEFAULT_DENYLIST: &[&str] = &[
+    "builtins.eval",
+    "builtins.exec",
+    "builtins.compile",
+    "builtins.__import__",
+    "os.system",
+    "os.popen",
+    "os.exec*",
+    "os.spawn*",
+    "os.posix_spawn*",
+    "os.remove",
+    "os.unlink",
+    "os.rmdir",
+    "shutil.rmtree",
+    "subprocess.*",
+    "pty.spawn",
+    "pickle.load",
+    "pickle.loads",
+    "pickle.Unpickler",
+    "marshal.load",
+    "marshal.loads",
+    "shelve.open",
+    "importlib.import_module",
+    "runpy.run_module",
+    "runpy.run_path",
+];
+
+/// Settings of the opt-in `security` rule set
+/// Example:
+/// ```yaml
+/// rules:
+///   security: error
+/// security:
+///   denylist: [torch.load]
+///   allowed_modules: [myproject, torch]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecuritySettings {
+    /// Qualified names of callables targets may not resolve to, with a trailing `*` for prefixes
+    /// Added to the built-in denylist unless `default_denylist` is false
+    pub denylist: Vec<String>,
+    /// Whether `DEFAULT_DENYLIST` applies alongside `denylist`
+    pub default_denylist: bool,
+    /// Module prefixes targets must come from, or empty to allow any module
+    pub allowed_modules: Vec<String>,
+}
+
+impl Default for SecuritySettings {
+    fn default() -> Self {
+        Self {
+            denylist: Vec::new(),
+            default_denylist: true,
+            allowed_modules: Vec::new(),
+        }
+    }
+}
+
+impl SecuritySettings {
+    /// Find the denylist entry matching a qualified name
+    pub fn denied(&self, name: &str) -> Option<&str> {
+        let defaults: &[&str] = if self.default_denylist {
+            DEFAULT_DENYLIST
+        } else {
+            &[]
+        };
+        self.denylist
+            .iter()
+            .map(String::as_str)
+            .chain(defaults.iter().copied())
+            .find(|entry| match entry.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == *entry,
+            })
+    }
+
+    /// Check if a target comes from one of the allowed modules, or its submodules
+    pub fn is_allowed(&self, target: &str) -> bool {
+        self.allowed_modules.is_empty()
+            || self.allowed_modules.iter().any(|module| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+}
+
+/// Rule configuration, from initialization options or a project config file
+/// Example:
+/// ```yaml
+/// rules:
+///   module-not-found: warning
+///   deprecation: off
+/// overrides:
+///   - path: configs/third_party
+///     rules:
+///       resolution: off
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RuleSettings {
+    /// Severity by rule code or rule set name
+    pub rules: HashMap<String, RuleSeverity>,
+    pub overrides: Vec<RuleOverride>,
+    /// Denylist and module allowlist of the `security` rule set
+    pub security: Option<SecuritySettings>,
+}
+
+impl RuleSettings {
+    /// Load the project config file of a workspace folder, if there is one
+    pub fn load_project(folder: &Path) -> anyhow::Result<Option<Self>> {
+        let path = folder.join(PROJECT_CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        Ok(Some(serde_yaml::from_str(&content)?))
+    }
+
+    /// Layer other settings on top of these, e.g. a project config file over initialization options
+    pub fn merge(&mut self, other: RuleSettings) {
+        self.rules.extend(other.rules);
+        self.overrides.extend(other.overrides);
+        if other.security.is_some() {
+            self.security = other.security;
+        }
+    }
+
+    /// Resolve the severity of a rule for a file, given its path relative to the workspace folder
+    /// Rule sets are applied before individual rules, and deeper overrides win over shallower ones
+    pub fn severity(&self, rule: &Rule, relative_path: Option<&Path>) -> RuleSeverity {
+        let mut overrides: Vec<&RuleOverride> = self
+            .overrides
+            .iter()
+            .filter(|o| relative_path.is_some_and(|path| path.starts_with(&o.path)))
+            .collect();
+        overrides.sort_by_key(|o| o.path.components().count());
+
+        let mut severity = rule.severity;
+        for rules in std::iter::once(&self.rules).chain(overrides.into_iter().map(|o| &o.rules)) {
+            if let Some(configured) = rules.get(rule.rule_set) {
+                severity = *configured;
+            }
+            if let Some(configured) = rules.get(rule.code) {
+                severity = *configured;
+            }
+        }
+        severity
+    }
+
+    /// Apply the configured severities, dropping diagnostics of rules that are turned off
+    pub fn apply(
+        &self,
+        diagnostics: Vec<Diagnostic>,
+        relative_path: Option<&Path>,
+    ) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let rule = match &diagnostic.code {
+                    Some(NumberOrString::String(code)) => find_rule(code),
+                    _ => None,
+                };
+                if let Some(rule) = rule {
+                    diagnostic.severity = Some(self.severity(rule, relative_path).to_lsp()?);
+                }
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(yaml: &str) -> RuleSettings {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_default_severity() {
+        let settings = RuleSettings::default();
+        assert_eq!(
+            settings.severity(&MODULE_NOT_FOUND, None),
+            RuleSeverity::Error
+        );
+        assert_eq!(
+            settings.severity(&KWARGS_PARAMETER, None),
+            RuleSeverity::Hint
+        );
+    }
+
+    #[test]
+    fn test_rule_overrides_rule_set() {
+        let settings = settings(
+            r#"
+rules:
+  resolution: warning
+  symbol-not-found: off
+"#,
+        );
+        assert_eq!(
+            settings.severity(&MODULE_NOT_FOUND, None),
+            RuleSeverity::Warning
+        );
+        assert_eq!(
+            settings.severity(&SYMBOL_NOT_FOUND, None),
+            RuleSeverity::Off
+        );
+        assert_eq!(
+            settings.severity(&UNKNOWN_PARAMETER, None),
+            RuleSeverity::Error
+        );
+    }
+
+    #[test]
+    fn test_directory_overrides() {
+        let settings = settings(
+            r#"
+rules:
+  module-not-found: warning
+overrides:
+  - path: configs/vendor/inner
+    rules:
+      module-not-found: error
+  - path: configs/vendor
+    rules:
+      resolution: off
+"#,
+        );
+        let severity = |path: &str| settings.severity(&MODULE_NOT_FOUND, Some(Path::new(path)));
+
+        assert_eq!(severity("configs/model.yaml"), RuleSeverity::Warning);
+        assert_eq!(severity("configs/vendor/model.yaml"), RuleSeverity::Off);
+        assert_eq!(
+            severity("configs/vendor/inner/model.yaml"),
+            RuleSeverity::Error
+        );
+    }
+
+    #[test]
+    fn test_security_settings() {
+        // The security rule set is opt-in
+        let defaults = RuleSettings::default();
+        assert_eq!(
+            defaults.severity(&DANGEROUS_TARGET, None),
+            RuleSeverity::Off
+        );
+
+        let security = SecuritySettings::default();
+        assert_eq!(security.denied("os.system"), Some("os.system"));
+        assert_eq!(security.denied("subprocess.Popen"), Some("subprocess.*"));
+        assert_eq!(security.denied("os.path.join"), None);
+        assert!(security.is_allowed("anything.Goes"));
+
+        let settings = settings(
+            r#"
+rules:
+  security: warning
+security:
+  denylist: [torch.load]
+  allowed_modules: [myproject]
+"#,
+        );
+        let security = settings.security.unwrap();
+        assert_eq!(security.denied("os.system"), Some("os.system"));
+        assert_eq!(security.denied("torch.load"), Some("torch.load"));
+        assert!(security.is_allowed("myproject.models.Net"));
+        assert!(!security.is_allowed("myproject2.Net"));
+
+        // The built-in denylist can be replaced rather than extended
+        let security: SecuritySettings =
+            serde_yaml::from_str("denylist: [torch.load]\ndefault_denylist: false\n").unwrap();
+        assert_eq!(security.denied("os.system"), None);
+        assert_eq!(security.denied("torch.load"), Some("torch.load"));
+    }
+
+    #[test]
+    fn test_apply_drops_disabled_rules() {
+        let settings = settings("rules:\n  parameters: warning\n  missing-parameter: off\n");
+        let diagnostic = |code: &str| Diagnostic {
+            code: Some(NumberOrString::String(code.to_string())),
+            severity: Some(DiagnosticSeverity::ERROR),
+            ..Default::default()
+        };
+
+        let result = settings.apply(
+            vec![
+                diagnostic("unknown-parameter"),
+                diagnostic("missing-parameter"),
+            ],
+            None,
+        );
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].severity, Some(DiagnosticSeverity::WARNING));
+    }
+}
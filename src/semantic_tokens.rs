@@ -0,0 +1,380 @@
+use crate::interpolation;
+use crate::yaml_parser::{is_reserved_key, TargetInfo, YamlParser, TARGET_KEY};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tower_lsp::lsp_types::{
+    Position, Range, SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensDelta,
+    SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensLegend, SymbolKind, Url,
+};
+
+/// Token types in legend order, a token's type is its index here
+/// Keys outside `_target_` blocks are properties, and interpolations are variables
+pub const TOKEN_TYPES: [SemanticTokenType; 7] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::KEYWORD,
+];
+
+/// Get the legend the server's tokens are encoded with
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: Vec::new(),
+    }
+}
+
+/// A semantic token at an absolute position, before LSP's relative encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    /// Index into `TOKEN_TYPES`
+    pub token_type: u32,
+}
+
+impl Token {
+    fn new(line: u32, start: u32, length: u32, token_type: SemanticTokenType) -> Self {
+        Self {
+            line,
+            start,
+            length,
+            token_type: TOKEN_TYPES
+                .iter()
+                .position(|t| *t == token_type)
+                .expect("token type is in the legend") as u32,
+        }
+    }
+
+    /// Check if the token starts within a range
+    pub fn starts_in(&self, range: &Range) -> bool {
+        let start = Position::new(self.line, self.start);
+        range.start <= start && start < range.end
+    }
+}
+
+/// Find the semantic tokens of a Hydra config, in document order
+/// Target values are split into module segments and the class or function they name
+/// `symbol_kind` tells classes from functions, names it can't resolve go by Python's naming
+/// convention. With a range, only the tokens starting in it are found, and only the targets on
+/// its lines are passed to `symbol_kind`
+pub fn tokens(
+    content: &str,
+    range: Option<&Range>,
+    mut symbol_kind: impl FnMut(&TargetInfo) -> Option<SymbolKind>,
+) -> Vec<Token> {
+    let outline = YamlParser::outline(content);
+    let targets: HashMap<&[String], TargetInfo> =
+        YamlParser::outline_targets(&outline).into_iter().collect();
+
+    let mut tokens = Vec::new();
+    for entry in &outline {
+        if range.is_some_and(|range| entry.line < range.start.line || entry.line > range.end.line) {
+            continue;
+        }
+        let key = entry.path.last().map(String::as_str).unwrap_or_default();
+        if !entry.is_item() {
+            let block = &entry.path[..entry.path.len() - 1];
+            let token_type = if is_reserved_key(key) {
+                SemanticTokenType::KEYWORD
+            } else if targets.contains_key(block) {
+                SemanticTokenType::PARAMETER
+            } else {
+                SemanticTokenType::PROPERTY
+            };
+            tokens.push(Token::new(
+                entry.line,
+                entry.key_start,
+                entry.key_end - entry.key_start,
+                token_type,
+            ));
+        }
+
+        let target = targets
+            .get(&entry.path[..entry.path.len().saturating_sub(1)])
+            .filter(|target| key == TARGET_KEY && target.line == entry.line);
+        match target {
+            Some(target) if !interpolation::has_interpolation(&target.value) => {
+                tokens.extend(target_tokens(target, &mut symbol_kind));
+            }
+            _ => {
+                for span in interpolation::interpolation_spans(&entry.value) {
+                    tokens.push(Token::new(
+                        entry.line,
+                        entry.value_start + span.start as u32,
+                        span.len() as u32,
+                        SemanticTokenType::VARIABLE,
+                    ));
+                }
+            }
+        }
+    }
+    if let Some(range) = range {
+        tokens.retain(|token| token.starts_in(range));
+    }
+    tokens
+}
+
+/// Split a dotted path into namespace tokens for its modules and one for the symbol it names
+fn target_tokens(
+    target: &TargetInfo,
+    symbol_kind: &mut impl FnMut(&TargetInfo) -> Option<SymbolKind>,
+) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = target.value_start;
+    let mut segments = target.value.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let length = segment.len() as u32;
+        let token_type = if segments.peek().is_some() {
+            SemanticTokenType::NAMESPACE
+        } else {
+            match symbol_kind(target) {
+                Some(SymbolKind::CLASS) => SemanticTokenType::CLASS,
+                Some(_) => SemanticTokenType::FUNCTION,
+                None if segment.starts_with(char::is_uppercase) => SemanticTokenType::CLASS,
+                None => SemanticTokenType::FUNCTION,
+            }
+        };
+        if length > 0 {
+            tokens.push(Token::new(target.line, start, length, token_type));
+        }
+        start += length + 1;
+    }
+    tokens
+}
+
+/// Encode tokens in document order relative to the previous one, as LSP sends them
+pub fn encode(tokens: &[Token]) -> Vec<SemanticToken> {
+    let mut previous = Position::new(0, 0);
+    tokens
+        .iter()
+        .map(|token| {
+            let delta_line = token.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                token.start - previous.character
+            } else {
+                token.start
+            };
+            previous = Position::new(token.line, token.start);
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length: token.length,
+                token_type: token.token_type,
+                token_modifiers_bitset: 0,
+            }
+        })
+        .collect()
+}
+
+/// Get the edits turning one encoded token list into another, as a single replaced span
+/// Edit offsets count integers, five per token
+pub fn edits(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == previous.len() && prefix == current.len() {
+        return Vec::new();
+    }
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: ((previous.len() - prefix - suffix) * 5) as u32,
+        data: Some(current[prefix..current.len() - suffix].to_vec()),
+    }]
+}
+
+/// The last tokens sent for each document, so later requests can send only what changed
+#[derive(Debug, Default)]
+pub struct SemanticTokensCache {
+    documents: DashMap<Url, SemanticTokens>,
+    next_id: AtomicU64,
+}
+
+impl SemanticTokensCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the tokens sent for a document under a new result id
+    pub fn store(&self, uri: &Url, data: Vec<SemanticToken>) -> SemanticTokens {
+        let tokens = SemanticTokens {
+            result_id: Some(self.next_id.fetch_add(1, Ordering::SeqCst).to_string()),
+            data,
+        };
+        self.documents.insert(uri.clone(), tokens.clone());
+        tokens
+    }
+
+    /// Get a document's tokens as edits to those sent under `previous_result_id`
+    /// Falls back to all tokens when those were replaced or never sent
+    pub fn delta(
+        &self,
+        uri: &Url,
+        previous_result_id: &str,
+        data: Vec<SemanticToken>,
+    ) -> SemanticTokensFullDeltaResult {
+        let previous = self
+            .documents
+            .get(uri)
+            .filter(|previous| previous.result_id.as_deref() == Some(previous_result_id))
+            .map(|previous| previous.data.clone());
+        let tokens = self.store(uri, data);
+        match previous {
+            Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                edits: edits(&previous, &tokens.data),
+                result_id: tokens.result_id,
+            }),
+            None => SemanticTokensFullDeltaResult::Tokens(tokens),
+        }
+    }
+
+    /// Forget a document, e.g. when it is closed
+    pub fn remove(&self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Describe tokens as `line:start text type` lines
+    fn describe(content: &str, tokens: &[Token]) -> Vec<String> {
+        let lines: Vec<&str> = content.lines().collect();
+        tokens
+            .iter()
+            .map(|token| {
+                let start = token.start as usize;
+                let text = &lines[token.line as usize][start..start + token.length as usize];
+                format!(
+                    "{}:{} {} {}",
+                    token.line,
+                    token.start,
+                    text,
+                    TOKEN_TYPES[token.token_type as usize].as_str()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens() {
+        let content = r#"defaults:
+  - _self_
+name: Encoder
+model:
+  _target_: pkg.models.Encoder
+  _partial_: true
+  layers: 3
+  head:
+    _target_: "pkg.heads.make_head"
+other:
+  _target_: pkg.models.${name}
+  path: ${oc.env:HOME}/data
+"#;
+        let tokens = tokens(content, None, |target| {
+            (target.value == "pkg.models.Encoder").then_some(SymbolKind::CLASS)
+        });
+        assert_eq!(
+            describe(content, &tokens),
+            vec![
+                "0:0 defaults property",
+                "2:0 name property",
+                "3:0 model property",
+                "4:2 _target_ keyword",
+                "4:12 pkg namespace",
+                "4:16 models namespace",
+                "4:23 Encoder class",
+                "5:2 _partial_ keyword",
+                "6:2 layers parameter",
+                "7:2 head parameter",
+                "8:4 _target_ keyword",
+                "8:15 pkg namespace",
+                "8:19 heads namespace",
+                "8:25 make_head function",
+                "9:0 other property",
+                "10:2 _target_ keyword",
+                "10:23 ${name} variable",
+                "11:2 path parameter",
+                "11:8 ${oc.env:HOME} variable",
+            ]
+        );
+
+        // Only the targets on the lines of a range are resolved
+        let mut resolved = Vec::new();
+        let range = Range::new(Position::new(8, 0), Position::new(9, 0));
+        let in_range = super::tokens(content, Some(&range), |target| {
+            resolved.push(target.value.clone());
+            None
+        });
+        assert_eq!(resolved, vec!["pkg.heads.make_head"]);
+        assert_eq!(
+            describe(content, &in_range),
+            vec![
+                "8:4 _target_ keyword",
+                "8:15 pkg namespace",
+                "8:19 heads namespace",
+                "8:25 make_head function",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_and_edits() {
+        let encoded = encode(&[
+            Token::new(1, 2, 3, SemanticTokenType::PROPERTY),
+            Token::new(1, 8, 4, SemanticTokenType::VARIABLE),
+            Token::new(3, 4, 5, SemanticTokenType::PARAMETER),
+        ]);
+        let relative: Vec<(u32, u32)> = encoded
+            .iter()
+            .map(|token| (token.delta_line, token.delta_start))
+            .collect();
+        assert_eq!(relative, vec![(1, 2), (0, 6), (2, 4)]);
+
+        // Only the changed token is sent again
+        let mut changed = encoded.clone();
+        changed[1].length = 6;
+        let edits = edits(&encoded, &changed);
+        assert_eq!(edits.len(), 1);
+        assert_eq!((edits[0].start, edits[0].delete_count), (5, 5));
+        assert_eq!(edits[0].data.as_deref(), Some(&changed[1..2]));
+        assert!(super::edits(&encoded, &encoded).is_empty());
+    }
+
+    #[test]
+    fn test_cache_delta() {
+        let cache = SemanticTokensCache::new();
+        let uri = Url::parse("file:///conf/config.yaml").unwrap();
+        let encoded = encode(&[Token::new(0, 0, 4, SemanticTokenType::PROPERTY)]);
+        let first = cache.store(&uri, encoded.clone());
+
+        let result_id = first.result_id.unwrap();
+        match cache.delta(&uri, &result_id, encoded.clone()) {
+            SemanticTokensFullDeltaResult::TokensDelta(delta) => {
+                assert!(delta.edits.is_empty());
+                assert_ne!(delta.result_id, Some(result_id.clone()));
+            }
+            other => panic!("Expected a delta, got {:?}", other),
+        }
+
+        // The first result was replaced, so everything is sent again
+        assert!(matches!(
+            cache.delta(&uri, &result_id, encoded),
+            SemanticTokensFullDeltaResult::Tokens(_)
+        ));
+    }
+}
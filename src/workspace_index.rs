@@ -0,0 +1,558 @@
+use crate::composition::{self, DefaultsEntry};
+use crate::interpolation::{self, InterpolationResolver};
+use crate::yaml_parser::YamlParser;
+use dashmap::DashMap;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directories that don't hold the workspace's own configs
+/// `outputs` and `multirun` are where Hydra saves copies of the configs of each run
+const SKIPPED_DIRS: [&str; 5] = [
+    "node_modules",
+    "__pycache__",
+    "site-packages",
+    "outputs",
+    "multirun",
+];
+
+/// Check if a directory is skipped when indexing, like `.git` or a virtual environment such as
+/// `.venv`, or one of `SKIPPED_DIRS`
+fn is_skipped_dir(name: &str) -> bool {
+    name.starts_with('.') || SKIPPED_DIRS.contains(&name)
+}
+
+/// A `_target_` found in a config file of the workspace
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTarget {
+    /// The dotted path, with static interpolations substituted
+    pub target: String,
+    pub line: u32,
+    pub value_start: u32,
+    pub value_end: u32,
+    /// Whether the value has interpolations, so its text isn't the dotted path
+    pub interpolated: bool,
+}
+
+impl IndexedTarget {
+    /// Get the symbol name, the last part of the dotted path
+    pub fn symbol_name(&self) -> &str {
+        self.target.rsplit('.').next().unwrap_or(&self.target)
+    }
+}
+
+/// What the index keeps about a config file
+#[derive(Debug, Clone, Default)]
+struct IndexedFile {
+    targets: Vec<IndexedTarget>,
+    /// Config groups its defaults list selects from, and whether each is absolute like `/optim`
+    groups: Vec<(String, bool)>,
+}
+
+/// A config group option, a file of a group directory selected from some defaults list
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupOption {
+    pub group: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Every `_target_` and config group in the Hydra config files of the workspace folders
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    files: DashMap<PathBuf, IndexedFile>,
+    /// Set once the first walk of the workspace folders has finished
+    indexed: OnceLock<()>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a path is a YAML file that can hold Hydra configs
+    pub fn is_config_file(path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml" | "yml")
+        )
+    }
+
+    /// Index every config file under the workspace folders, unless that was already done
+    /// Concurrent callers wait for the first walk to finish rather than see a partial index
+    /// Files opened in the editor are kept up to date through `update` instead
+    pub fn ensure_indexed(&self, folders: &[PathBuf]) {
+        self.indexed.get_or_init(|| {
+            for folder in folders {
+                self.index_dir(folder);
+            }
+        });
+    }
+
+    /// Check if the first walk of the workspace folders has finished
+    pub fn is_indexed(&self) -> bool {
+        self.indexed.get().is_some()
+    }
+
+    /// Index the config files of a workspace folder, e.g. one added after `ensure_indexed`
+    pub fn index_folder(&self, folder: &Path) {
+        self.index_dir(folder);
+    }
+
+    /// Drop the config files that aren't under any of the workspace folders
+    pub fn retain_folders(&self, folders: &[PathBuf]) {
+        self.files
+            .retain(|path, _| folders.iter().any(|folder| path.starts_with(folder)));
+    }
+
+    /// Check if a path is a config file that indexing the workspace folders would find
+    pub fn is_workspace_config(path: &Path, folders: &[PathBuf]) -> bool {
+        Self::is_config_file(path)
+            && folders.iter().any(|folder| {
+                path.parent()
+                    .and_then(|dir| dir.strip_prefix(folder).ok())
+                    .is_some_and(|dirs| {
+                        dirs.components()
+                            .all(|dir| !is_skipped_dir(&dir.as_os_str().to_string_lossy()))
+                    })
+            })
+    }
+
+    fn index_dir(&self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            if path.is_dir() {
+                if !is_skipped_dir(&name.to_string_lossy()) {
+                    self.index_dir(&path);
+                }
+            } else if Self::is_config_file(&path) && !self.files.contains_key(&path) {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    self.update(&path, &content);
+                }
+            }
+        }
+    }
+
+    /// Re-index a config file from its content, dropping it if it isn't a Hydra file
+    pub fn update(&self, path: &Path, content: &str) {
+        let groups = Self::config_groups(path, content);
+        if groups.is_empty() && !YamlParser::is_hydra_file(content) {
+            self.files.remove(path);
+            return;
+        }
+        // Targets come from the outline, which also covers those in lists and broken documents
+        let resolver = interpolation::has_interpolation(content)
+            .then(|| InterpolationResolver::new(content, Some(path)));
+        let outline = YamlParser::outline(content);
+        let targets = YamlParser::outline_targets(&outline)
+            .into_iter()
+            .map(|(_, mut target)| {
+                if let Some(resolver) = &resolver {
+                    resolver.resolve_target(&mut target);
+                }
+                IndexedTarget {
+                    target: target.target_path().to_string(),
+                    line: target.line,
+                    value_start: target.value_start,
+                    value_end: target.value_end(),
+                    interpolated: interpolation::has_interpolation(&target.value),
+                }
+            })
+            .collect();
+        self.files
+            .insert(path.to_path_buf(), IndexedFile { targets, groups });
+    }
+
+    /// Get the config groups selected by the defaults list of a config
+    /// Overrides and Hydra's own groups are skipped
+    fn config_groups(path: &Path, content: &str) -> Vec<(String, bool)> {
+        if path.parent().is_none() {
+            return Vec::new();
+        }
+        let Some(entries) = serde_yaml::from_str::<Value>(content)
+            .ok()
+            .as_ref()
+            .and_then(composition::defaults_list)
+        else {
+            return Vec::new();
+        };
+        entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                DefaultsEntry::Group {
+                    group,
+                    is_override: false,
+                    ..
+                } => {
+                    let name = group.trim_start_matches('/');
+                    (name != "hydra" && !name.starts_with("hydra/"))
+                        .then(|| (name.to_string(), group.starts_with('/')))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Re-index a config file from disk, e.g. when it is closed without saving
+    pub fn reload(&self, path: &Path) {
+        match fs::read_to_string(path) {
+            Ok(content) => self.update(path, &content),
+            Err(_) => self.remove(path),
+        }
+    }
+
+    /// Drop a config file, e.g. when it is deleted
+    pub fn remove(&self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Get the targets whose symbol name is `name`, with the config file holding them
+    /// Re-exports mean the module part can differ, so callers check where each one resolves
+    pub fn targets_named(&self, name: &str) -> Vec<(PathBuf, IndexedTarget)> {
+        self.find(|target| target.symbol_name() == name)
+    }
+
+    /// Get the targets written as `prefix` or as a path under it, with the config file holding them
+    /// Example: "pkg.models" matches "pkg.models" and "pkg.models.Encoder", but not "pkg.models2"
+    pub fn targets_with_prefix(&self, prefix: &str) -> Vec<(PathBuf, IndexedTarget)> {
+        let under = format!("{}.", prefix);
+        self.find(|target| {
+            !target.interpolated && (target.target == prefix || target.target.starts_with(&under))
+        })
+    }
+
+    /// Get every target, with the config file holding it
+    pub fn targets(&self) -> Vec<(PathBuf, IndexedTarget)> {
+        self.find(|_| true)
+    }
+
+    /// Get the options of every config group selected in the workspace, ordered by group and name
+    /// Options are read from the group directories, so new files show up without re-indexing
+    pub fn group_options(&self) -> Vec<GroupOption> {
+        let mut options = Vec::new();
+        for (dir, group) in self.group_dirs() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if !path.is_file() || !Self::is_config_file(&path) {
+                    continue;
+                }
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    options.push(GroupOption {
+                        group: group.clone(),
+                        name: name.to_string(),
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+        options.sort_by(|a, b| (&a.group, &a.name).cmp(&(&b.group, &b.name)));
+        options
+    }
+
+    /// Get the directory of every config group selected in the workspace, with the group name
+    /// Relative groups are under the selecting config's directory, absolute ones under its config
+    /// root: the directory of the primary config that, through groups, selected it
+    fn group_dirs(&self) -> BTreeMap<PathBuf, String> {
+        let selections: Vec<(PathBuf, String, bool)> = self
+            .files
+            .iter()
+            .filter_map(|file| {
+                Some((
+                    file.key().parent()?.to_path_buf(),
+                    file.value().groups.clone(),
+                ))
+            })
+            .flat_map(|(dir, groups)| {
+                groups
+                    .into_iter()
+                    .map(move |(group, absolute)| (dir.clone(), group, absolute))
+            })
+            .collect();
+
+        // Configs are their own root until found in a group, which puts them under the root of
+        // the config selecting it. Each pass follows the selections one level further
+        let mut roots: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let root_of = |roots: &HashMap<PathBuf, PathBuf>, dir: &Path| {
+            roots.get(dir).cloned().unwrap_or_else(|| dir.to_path_buf())
+        };
+        let group_dir = |roots: &HashMap<PathBuf, PathBuf>, dir: &Path, group: &str, absolute| {
+            let root = root_of(roots, dir);
+            let group_dir = if absolute { &root } else { dir }.join(group);
+            (root, group_dir)
+        };
+        for _ in 0..selections.len() {
+            let mut changed = false;
+            for (dir, group, absolute) in &selections {
+                let (root, group_dir) = group_dir(&roots, dir, group, *absolute);
+                if root_of(&roots, &group_dir) != root {
+                    roots.insert(group_dir, root);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        // Groups are named from the root, e.g. `encoder` selected in `model` is `model/encoder`
+        selections
+            .iter()
+            .map(|(dir, group, absolute)| {
+                let (root, group_dir) = group_dir(&roots, dir, group, *absolute);
+                let name = group_dir
+                    .strip_prefix(&root)
+                    .ok()
+                    .and_then(|relative| relative.to_str())
+                    .map_or_else(|| group.clone(), |relative| relative.replace('\\', "/"));
+                (group_dir, name)
+            })
+            .collect()
+    }
+
+    /// Get the matching targets, ordered by file and line
+    fn find(&self, predicate: impl Fn(&IndexedTarget) -> bool) -> Vec<(PathBuf, IndexedTarget)> {
+        let mut found: Vec<(PathBuf, IndexedTarget)> = self
+            .files
+            .iter()
+            .flat_map(|file| {
+                file.value()
+                    .targets
+                    .iter()
+                    .filter(|target| predicate(target))
+                    .map(|target| (file.key().clone(), target.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        found.sort_by(|a, b| (&a.0, a.1.line).cmp(&(&b.0, b.1.line)));
+        found
+    }
+}
+
+/// Score how well a name matches a fuzzy query, `None` if the query isn't a subsequence of it
+/// Matching ignores case, and favors consecutive characters, word starts and prefixes
+/// Example: "EarlyStop" and "es" both match "EarlyStopping", "es" scoring lower
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let name: Vec<char> = name.chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let index = (next..name.len()).find(|&i| name[i].to_lowercase().eq(std::iter::once(q)))?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        let word_start = index == 0
+            || matches!(name[index - 1], '_' | '.' | '-' | '/')
+            || (name[index].is_uppercase() && name[index - 1].is_lowercase());
+        if word_start {
+            score += 3;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+    let query_len = query.chars().count();
+    if query_len > 0 && previous == Some(query_len - 1) {
+        score += 50;
+    }
+    if query_len == name.len() && previous.is_some_and(|last| last + 1 == name.len()) {
+        score += 100;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_workspace() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "conf/model.yaml",
+            "name: Encoder\nmodel:\n  _target_: pkg.models.Encoder\nother:\n  _target_: pkg.models.${name}\n",
+        );
+        write("conf/plain.yaml", "key: value\n");
+        write(
+            ".venv/lib/config.yaml",
+            "a:\n  _target_: pkg.models.Encoder\n",
+        );
+        write("README.md", "_target_: pkg.models.Encoder\n");
+
+        let index = WorkspaceIndex::new();
+        assert!(!index.is_indexed());
+        index.ensure_indexed(&[dir.path().to_path_buf()]);
+        assert!(index.is_indexed());
+        let model = dir.path().join("conf/model.yaml");
+        let found = index.targets_named("Encoder");
+        assert_eq!(
+            found
+                .iter()
+                .map(|(path, target)| (path, target.line, target.target.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (&model, 2, "pkg.models.Encoder"),
+                (&model, 4, "pkg.models.Encoder"),
+            ]
+        );
+
+        // Edits replace the file's targets
+        index.update(&model, "model:\n  _target_: pkg.models.Decoder\n");
+        assert!(index.targets_named("Encoder").is_empty());
+        assert_eq!(index.targets_named("Decoder").len(), 1);
+
+        // Watched files in skipped directories are left out, like when walking the folders
+        let folders = [dir.path().to_path_buf()];
+        assert!(WorkspaceIndex::is_workspace_config(&model, &folders));
+        assert!(!WorkspaceIndex::is_workspace_config(
+            &dir.path().join(".venv/lib/config.yaml"),
+            &folders
+        ));
+        assert!(!WorkspaceIndex::is_workspace_config(
+            &dir.path().join("README.md"),
+            &folders
+        ));
+        assert!(!WorkspaceIndex::is_workspace_config(
+            Path::new("/elsewhere/config.yaml"),
+            &folders
+        ));
+
+        // Removing the folder drops its files
+        index.retain_folders(&[]);
+        assert!(index.targets_named("Decoder").is_empty());
+    }
+
+    #[test]
+    fn test_targets_with_prefix() {
+        let index = WorkspaceIndex::new();
+        let path = PathBuf::from("/conf/config.yaml");
+        index.update(
+            &path,
+            "name: Encoder\na:\n  _target_: pkg.models.Encoder\nb:\n  _target_: pkg.models2.Encoder\nc:\n  _target_: pkg.models.${name}\n",
+        );
+        let lines = |prefix: &str| {
+            index
+                .targets_with_prefix(prefix)
+                .iter()
+                .map(|(_, target)| target.line)
+                .collect::<Vec<_>>()
+        };
+        // Interpolated values can't be rewritten as text
+        assert_eq!(lines("pkg.models"), vec![2]);
+        assert_eq!(lines("pkg"), vec![2, 4]);
+        assert_eq!(lines("pkg.models.Encoder"), vec![2]);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("EarlyStop", "EarlyStopping").is_some());
+        assert!(fuzzy_score("earlystop", "EarlyStopping").is_some());
+        assert!(fuzzy_score("StopEarly", "EarlyStopping").is_none());
+        assert_eq!(fuzzy_score("", "EarlyStopping"), Some(0));
+
+        // Word starts beat scattered characters, prefixes beat both and exact matches win
+        let score = |query| fuzzy_score(query, "EarlyStopping").unwrap();
+        assert!(score("es") > score("ep"));
+        assert!(score("early") > score("stop"));
+        assert!(fuzzy_score("adam", "adam") > fuzzy_score("adam", "adamw"));
+    }
+
+    #[test]
+    fn test_group_options() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "conf/config.yaml",
+            "defaults:\n  - model: resnet\n  - /optim@trainer: adam\n  - override hydra/launcher: joblib\n  - _self_\n",
+        );
+        write("conf/model/resnet.yaml", "depth: 50\n");
+        write("conf/model/vit.yml", "patch: 16\n");
+        write("conf/model/notes.txt", "");
+        write("conf/optim/adam.yaml", "lr: 0.001\n");
+        write("conf/unused/option.yaml", "key: value\n");
+
+        let index = WorkspaceIndex::new();
+        index.ensure_indexed(&[dir.path().to_path_buf()]);
+        let options: Vec<(String, String)> = index
+            .group_options()
+            .into_iter()
+            .map(|option| (option.group, option.name))
+            .collect();
+        assert_eq!(
+            options,
+            vec![
+                ("model".to_string(), "resnet".to_string()),
+                ("model".to_string(), "vit".to_string()),
+                ("optim".to_string(), "adam".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_options_nested() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "conf/config.yaml",
+            "defaults:\n  - model: resnet\n  - _self_\n",
+        );
+        write(
+            "conf/model/resnet.yaml",
+            "defaults:\n  - /optim: adam\n  - encoder: conv\n  - _self_\ndepth: 50\n",
+        );
+        write("conf/model/encoder/conv.yaml", "kernel: 3\n");
+        write("conf/model/optim/sgd.yaml", "lr: 0.1\n");
+        write("conf/optim/adam.yaml", "lr: 0.001\n");
+
+        let index = WorkspaceIndex::new();
+        index.ensure_indexed(&[dir.path().to_path_buf()]);
+        let options: Vec<(String, String)> = index
+            .group_options()
+            .into_iter()
+            .map(|option| (option.group, option.name))
+            .collect();
+        // `/optim` in a group option is under the primary config's directory, not the option's
+        assert_eq!(
+            options,
+            vec![
+                ("model".to_string(), "resnet".to_string()),
+                ("model/encoder".to_string(), "conv".to_string()),
+                ("optim".to_string(), "adam".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_list_targets() {
+        let index = WorkspaceIndex::new();
+        let path = PathBuf::from("/conf/config.yaml");
+        index.update(
+            &path,
+            "trainer:\n  callbacks:\n    - _target_: pkg.callbacks.EarlyStopping\n      patience: 3\n",
+        );
+        let found = index.targets_named("EarlyStopping");
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].1.line, found[0].1.value_start), (2, 16));
+    }
+}
@@ -1,23 +1,119 @@
+use crate::interpolation;
 use serde_yaml::Value;
 use std::collections::{HashMap, VecDeque};
 use tower_lsp::lsp_types::Position;
 
 pub const TARGET_KEY: &str = "_target_";
+pub const SUPPRESSION_PREFIX: &str = "hydra-lsp:";
+pub const PARTIAL_KEY: &str = "_partial_";
+pub const ARGS_KEY: &str = "_args_";
+pub const CONVERT_KEY: &str = "_convert_";
+pub const RECURSIVE_KEY: &str = "_recursive_";
+
+/// Maximum number of syntax errors reported before giving up on recovery
+const MAX_SYNTAX_ERRORS: usize = 10;
+
+/// Errors whose context start, rather than where the parser gave up, is the real problem
+/// Example: an unclosed quote is only detected at the end of the stream
+const CONTEXT_LOCATED_ERRORS: [&str; 2] =
+    ["while scanning a quoted scalar", "while parsing a flow"];
+
+/// Check if a key is reserved by Hydra's instantiate and is therefore not a parameter
+pub fn is_reserved_key(key: &str) -> bool {
+    matches!(
+        key,
+        TARGET_KEY | PARTIAL_KEY | ARGS_KEY | CONVERT_KEY | RECURSIVE_KEY
+    )
+}
+
+/// How `instantiate` converts OmegaConf containers before passing them on, set by `_convert_`
+/// Nested configs inherit the mode of the config they are in unless they set their own
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConvertMode {
+    /// `DictConfig` and `ListConfig` are passed as they are
+    #[default]
+    None,
+    /// Containers become `dict` and `list`, except structured configs
+    Partial,
+    /// Like `Partial`, but structured configs become instances of their dataclass
+    Object,
+    /// Everything becomes `dict`, `list` and primitives
+    All,
+}
+
+impl ConvertMode {
+    pub const NAMES: [&'static str; 4] = ["none", "partial", "object", "all"];
+
+    /// Parse a `_convert_` value, which Hydra compares case-insensitively
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" => Some(ConvertMode::None),
+            "partial" => Some(ConvertMode::Partial),
+            "object" => Some(ConvertMode::Object),
+            "all" => Some(ConvertMode::All),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConvertMode::None => "none",
+            ConvertMode::Partial => "partial",
+            ConvertMode::Object => "object",
+            ConvertMode::All => "all",
+        }
+    }
+}
 
 /// Represents a parameter in a YAML configuration with position information
 /// Can either be a simple value or a nested target
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ParameterValue {
     pub kind: ParameterKind,
     pub line: u32,
     pub key: String,
 }
 
-/// The kind of parameter value - either a simple value or a nested target
+/// The kind of parameter value - a simple value, a nested target or a plain mapping
 #[derive(Debug, Clone)]
 pub enum ParameterKind {
     Value(Value),
     NestedTargetIndex(usize),
+    Mapping(MappingValue),
+}
+
+impl ParameterKind {
+    /// Get the YAML value, unless this is a nested target
+    pub fn value(&self) -> Option<&Value> {
+        match self {
+            ParameterKind::Value(value) => Some(value),
+            ParameterKind::Mapping(mapping) => Some(&mapping.value),
+            ParameterKind::NestedTargetIndex(_) => None,
+        }
+    }
+}
+
+/// A mapping without `_target_`, which OmegaConf can build into a dataclass or TypedDict
+#[derive(Debug, Clone, Default)]
+pub struct MappingValue {
+    pub value: Value,
+    /// The mapping's keys, with their own positions
+    pub fields: Vec<ParameterValue>,
+    /// Column of the field keys, or of the parameter key for a flow mapping
+    pub key_start: u32,
+}
+
+impl MappingValue {
+    /// Check if this is a `_target_` block passed on as a config because of `_recursive_: false`
+    pub fn is_raw_target(&self) -> bool {
+        self.value.get(TARGET_KEY).is_some()
+    }
+}
+
+impl Default for ParameterKind {
+    fn default() -> Self {
+        ParameterKind::Value(Value::Null)
+    }
 }
 
 impl ParameterValue {
@@ -38,13 +134,24 @@ impl ParameterValue {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TargetInfo {
     pub value: String,
     pub parameters: Vec<ParameterValue>,
     pub line: u32,
     pub key_start: u32,
     pub value_start: u32,
+    /// Whether `_partial_: true` is set, making Hydra return a `functools.partial`
+    pub partial: bool,
+    /// Number of positional arguments given in `_args_`
+    pub args: usize,
+    /// The `_convert_` mode in effect, set here or inherited from an enclosing target
+    pub convert: ConvertMode,
+    /// Whether an enclosing target sets `_recursive_: false`, so this block is passed on as a
+    /// config instead of being instantiated
+    pub raw: bool,
+    /// The value with its `${...}` interpolations substituted, see `InterpolationResolver`
+    pub resolved: Option<String>,
 }
 
 impl TargetInfo {
@@ -52,9 +159,7 @@ impl TargetInfo {
         Self {
             value,
             parameters,
-            line: 0,
-            key_start: 0,
-            value_start: 0,
+            ..Default::default()
         }
     }
 
@@ -62,6 +167,75 @@ impl TargetInfo {
     pub fn value_end(&self) -> u32 {
         self.value_start + self.value.len() as u32
     }
+
+    /// Get the dotted path to analyze, which is the resolved value for an interpolated target
+    pub fn target_path(&self) -> &str {
+        self.resolved.as_deref().unwrap_or(&self.value)
+    }
+
+    /// Check if the value has interpolations that couldn't be resolved statically
+    pub fn is_dynamic(&self) -> bool {
+        self.resolved.is_none() && interpolation::has_interpolation(&self.value)
+    }
+
+    /// Get the byte range within the value of the dotted path segment at a column
+    /// Example: column on "models" in "myproject.models.Model" -> 10..16
+    pub fn segment_at(&self, character: u32) -> Option<std::ops::Range<usize>> {
+        let offset = character.checked_sub(self.value_start)? as usize;
+        if offset > self.value.len() || !self.value.is_char_boundary(offset) {
+            return None;
+        }
+        let start = self.value[..offset].rfind('.').map_or(0, |dot| dot + 1);
+        let end = self.value[offset..]
+            .find('.')
+            .map_or(self.value.len(), |dot| offset + dot);
+        Some(start..end)
+    }
+}
+
+/// An inline `# hydra-lsp: ignore` comment that suppresses diagnostics on its line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suppression {
+    pub line: u32,
+    pub start: u32,
+    pub end: u32,
+    /// Diagnostic codes to suppress, or empty to suppress everything on the line
+    pub codes: Vec<String>,
+}
+
+/// A mapping key or sequence item found by `YamlParser::outline`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    /// Keys and sequence indices from the document root to this entry
+    /// Example: ["model", "layers", "0"]
+    pub path: Vec<String>,
+    pub line: u32,
+    /// Column range of the key, or of the `-` marker for a sequence item
+    pub key_start: u32,
+    pub key_end: u32,
+    pub value_start: u32,
+    /// The inline scalar value without comments, empty for nested blocks
+    pub value: String,
+}
+
+impl OutlineEntry {
+    /// Check if the entry is a sequence item, whose key range is its `-` marker
+    pub fn is_item(&self) -> bool {
+        self.key_end == self.key_start + 1
+            && self
+                .path
+                .last()
+                .is_some_and(|key| key.parse::<usize>().is_ok())
+    }
+}
+
+/// A YAML syntax error located on a single line of the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub line: u32,
+    pub start: u32,
+    pub end: u32,
+    pub message: String,
 }
 
 #[derive(Debug)]
@@ -76,7 +250,7 @@ impl YamlParser {
         // Changed return type
         let value: Value = serde_yaml::from_str(content)?;
         let mut targets: VecDeque<TargetInfo> = VecDeque::new();
-        Self::extract_targets(&value, &mut targets);
+        Self::extract_targets(&value, &mut targets, ConvertMode::None, false);
 
         // Find positions for all targets
         let targets = Self::find_positions(content, targets);
@@ -90,6 +264,66 @@ impl YamlParser {
         Ok((targets, line_map))
     }
 
+    /// Parse YAML content, recovering from syntax errors to extract as many targets as possible
+    /// Each offending line is blanked out and the content reparsed, which keeps line numbers intact
+    pub fn parse_with_recovery(content: &str) -> (Vec<TargetInfo>, Vec<SyntaxError>) {
+        let mut lines: Vec<&str> = content.lines().collect();
+        let mut errors = Vec::new();
+
+        loop {
+            let error = match Self::parse(&lines.join("\n")) {
+                Ok((targets, _line_map)) => return (targets, errors),
+                Err(e) => Self::syntax_error(&e, &lines),
+            };
+            let line = error.line as usize;
+            errors.push(error);
+
+            // Stop when blanking the line can't make progress
+            if errors.len() >= MAX_SYNTAX_ERRORS
+                || lines.get(line).is_none_or(|text| text.trim().is_empty())
+            {
+                return (Vec::new(), errors);
+            }
+            lines[line] = "";
+        }
+    }
+
+    /// Locate a parser error on a line of the document
+    fn syntax_error(error: &serde_yaml::Error, lines: &[&str]) -> SyntaxError {
+        let message = error.to_string();
+        let location = Self::context_location(&message)
+            .or_else(|| error.location().map(|loc| (loc.line(), loc.column())));
+
+        // serde_yaml locations are 1-based and may point past the last line at the end of the stream
+        let (line, column) = location.map_or((0, 0), |(line, column)| {
+            (line.saturating_sub(1), column.saturating_sub(1))
+        });
+        let line = line.min(lines.len().saturating_sub(1));
+        let text = lines.get(line).map_or("", |text| text.trim_end());
+        let start = column.min(text.len());
+
+        SyntaxError {
+            line: line as u32,
+            start: start as u32,
+            end: text.len().max(start) as u32,
+            message,
+        }
+    }
+
+    /// Get the location of the construct an error occurred in, for errors where that is more useful
+    /// Example: "... at line 5 column 1, while scanning a quoted scalar at line 2 column 6" -> (2, 6)
+    fn context_location(message: &str) -> Option<(usize, usize)> {
+        if !CONTEXT_LOCATED_ERRORS
+            .iter()
+            .any(|context| message.contains(context))
+        {
+            return None;
+        }
+        let (_, location) = message.rsplit_once(" at line ")?;
+        let (line, column) = location.split_once(" column ")?;
+        Some((line.parse().ok()?, column.trim().parse().ok()?))
+    }
+
     /// Check if a YAML file is a Hydra configuration file
     pub fn is_hydra_file(content: &str) -> bool {
         // Strategy 1: Check for comment markers
@@ -105,15 +339,95 @@ impl YamlParser {
         false
     }
 
-    /// Check for Hydra comment markers (# @hydra or # hydra:)
-    fn has_hydra_comment(content: &str) -> bool {
+    /// Get the trimmed lines of the header block that is scanned for file-level comments
+    fn header_lines(content: &str) -> impl Iterator<Item = &str> {
         content
             .lines()
             .take(10) // Check first 10 lines
-            .any(|line| {
-                let trimmed = line.trim();
-                trimmed.starts_with("# @hydra") || trimmed.starts_with("# hydra:")
-            })
+            .map(|line| line.trim())
+    }
+
+    /// Check for Hydra comment markers (# @hydra or # hydra:)
+    fn has_hydra_comment(content: &str) -> bool {
+        Self::header_lines(content)
+            .any(|trimmed| trimmed.starts_with("# @hydra") || trimmed.starts_with("# hydra:"))
+    }
+
+    /// Find a file-level `# hydra-lsp: ignore-file` comment in the header
+    pub fn find_ignore_file_comment(content: &str) -> Option<Suppression> {
+        let line = Self::header_lines(content)
+            .position(|trimmed| Self::suppression_directive(trimmed) == Some("ignore-file"))?;
+        let text = content.lines().nth(line)?;
+        let start = text.len() - text.trim_start().len();
+        Some(Suppression {
+            line: line as u32,
+            start: start as u32,
+            end: text.trim_end().len() as u32,
+            codes: Vec::new(),
+        })
+    }
+
+    /// Find all inline `# hydra-lsp: ignore` and `# hydra-lsp: ignore[code, ...]` comments
+    pub fn find_suppressions(content: &str) -> Vec<Suppression> {
+        let mut suppressions = Vec::new();
+        for (line_num, line) in content.lines().enumerate() {
+            let Some(comment_start) = Self::comment_start(line) else {
+                continue;
+            };
+            let comment = line[comment_start..].trim_end();
+            let Some(directive) = Self::suppression_directive(comment) else {
+                continue;
+            };
+            let Some(rest) = directive.strip_prefix("ignore") else {
+                continue;
+            };
+            let codes = if rest.is_empty() {
+                Vec::new()
+            } else if let Some(list) = rest.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+                list.split(',')
+                    .map(|code| code.trim().to_string())
+                    .filter(|code| !code.is_empty())
+                    .collect()
+            } else {
+                // Something like ignore-file or a typo, not an inline suppression
+                continue;
+            };
+            suppressions.push(Suppression {
+                line: line_num as u32,
+                start: comment_start as u32,
+                end: (comment_start + comment.len()) as u32,
+                codes,
+            });
+        }
+        suppressions
+    }
+
+    /// Get the directive of a `# hydra-lsp: <directive>` comment
+    fn suppression_directive(comment: &str) -> Option<&str> {
+        comment
+            .strip_prefix('#')?
+            .trim_start()
+            .strip_prefix(SUPPRESSION_PREFIX)
+            .map(|directive| directive.trim())
+    }
+
+    /// Find the byte offset of the comment on a line, ignoring `#` inside quoted strings
+    fn comment_start(line: &str) -> Option<usize> {
+        let mut quote: Option<char> = None;
+        let mut previous = None;
+        for (idx, ch) in line.char_indices() {
+            match quote {
+                Some(open) if ch == open => quote = None,
+                Some(_) => {}
+                None if ch == '"' || ch == '\'' => quote = Some(ch),
+                None if ch == '#' && previous.is_none_or(|c: char| c.is_whitespace()) => {
+                    return Some(idx);
+                }
+                None => {}
+            }
+            previous = Some(ch);
+        }
+        None
     }
 
     /// Check if content contains `_target_` keyword
@@ -198,32 +512,72 @@ impl YamlParser {
         Ok(None)
     }
 
-    /// Recursively extract all `_target_` references from YAML value and build tree structure
-    fn extract_targets(value: &Value, targets: &mut VecDeque<TargetInfo>) {
+    /// Find the parameter key at a specific position, with the target it belongs to
+    pub fn find_parameter_at_position(
+        content: &str,
+        position: Position,
+    ) -> Result<Option<(TargetInfo, ParameterValue)>, serde_yaml::Error> {
+        let (targets, _) = Self::parse(content)?;
+        for target in &targets {
+            // Parameter keys are aligned with the `_target_` key
+            let parameter = target.parameters.iter().find(|param| {
+                param.line == position.line
+                    && position.character >= target.key_start
+                    && position.character <= target.key_start + param.key.len() as u32
+            });
+            if let Some(parameter) = parameter {
+                return Ok(Some((target.clone(), parameter.clone())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Recursively extract all `_target_` references from YAML value and build tree structure.
+    /// Everything below a `_recursive_: false` target is `raw`
+    fn extract_targets(
+        value: &Value,
+        targets: &mut VecDeque<TargetInfo>,
+        convert: ConvertMode,
+        raw: bool,
+    ) {
         match value {
             Value::Mapping(map) => {
                 // Check if this mapping has a _target_ key
                 if let Some(Value::String(target_str)) = map.get(TARGET_KEY) {
                     // Create and push the target immediately to preserve order
                     let target_index = targets.len();
-                    targets.push_back(TargetInfo::new(target_str.clone(), Vec::new()));
+                    let mut target = TargetInfo::new(target_str.clone(), Vec::new());
+                    target.partial = matches!(map.get(PARTIAL_KEY), Some(Value::Bool(true)));
+                    if let Some(Value::Sequence(args)) = map.get(ARGS_KEY) {
+                        target.args = args.len();
+                    }
+                    target.convert = map
+                        .get(CONVERT_KEY)
+                        .and_then(Value::as_str)
+                        .and_then(ConvertMode::parse)
+                        .unwrap_or(convert);
+                    let convert = target.convert;
+                    target.raw = raw;
+                    let recursive =
+                        !raw && !matches!(map.get(RECURSIVE_KEY), Some(Value::Bool(false)));
+                    targets.push_back(target);
 
                     // Extract parameters, checking for nested targets
-                    let parameters = Self::extract_parameters(map, targets);
+                    let parameters = Self::extract_parameters(map, targets, convert, recursive);
 
                     // Update the target with the collected parameters
                     targets[target_index].parameters = parameters;
                 } else {
                     // If no _target_ found, recursively process nested mappings
                     for (_key, val) in map {
-                        Self::extract_targets(val, targets);
+                        Self::extract_targets(val, targets, convert, raw);
                     }
                 }
             }
             Value::Sequence(seq) => {
                 // Recursively process sequences
                 for item in seq {
-                    Self::extract_targets(item, targets);
+                    Self::extract_targets(item, targets, convert, raw);
                 }
             }
             _ => {}
@@ -234,25 +588,56 @@ impl YamlParser {
     fn extract_parameters(
         map: &serde_yaml::Mapping,
         targets: &mut VecDeque<TargetInfo>,
+        convert: ConvertMode,
+        recursive: bool,
     ) -> Vec<ParameterValue> {
         let mut parameters = Vec::new();
 
         for (key, val) in map {
             if let Value::String(key_str) = key {
-                // The _target_ key itself is not a parameter, but is the target identifier
-                if key_str != TARGET_KEY {
+                // Reserved keys such as _target_ configure instantiation and are not parameters
+                if !is_reserved_key(key_str) {
                     // Check if this parameter value is a nested target
                     if let Value::Mapping(nested_map) = val {
                         if nested_map.get(TARGET_KEY).is_some() {
                             // This is a nested target - extract it recursively
                             let nested_index = targets.len();
-                            Self::extract_targets(val, targets);
-                            parameters
-                                .push(ParameterValue::new_nested(key_str.clone(), nested_index));
-                            continue; // Skip the regular value insertion below
+                            Self::extract_targets(val, targets, convert, !recursive);
+                            if recursive {
+                                parameters.push(ParameterValue::new_nested(
+                                    key_str.clone(),
+                                    nested_index,
+                                ));
+                                continue; // Skip the regular value insertion below
+                            }
+
+                            // Without recursion the parent receives the block as a config
+                            parameters.push(ParameterValue {
+                                kind: ParameterKind::Mapping(MappingValue {
+                                    value: val.clone(),
+                                    ..Default::default()
+                                }),
+                                line: 0,
+                                key: key_str.clone(),
+                            });
+                            continue;
                         }
+
+                        // A plain mapping may be a structured config, and can hold targets itself
+                        let fields =
+                            Self::extract_parameters(nested_map, targets, convert, recursive);
+                        parameters.push(ParameterValue {
+                            kind: ParameterKind::Mapping(MappingValue {
+                                value: val.clone(),
+                                fields,
+                                key_start: 0,
+                            }),
+                            line: 0,
+                            key: key_str.clone(),
+                        });
+                        continue;
                     }
-                    // Simple value (string, number, mapping without _target_, etc.)
+                    // Simple value (string, number, sequence, etc.)
                     parameters.push(ParameterValue::new_value(key_str.clone(), val.clone()));
                 }
             }
@@ -265,11 +650,14 @@ impl YamlParser {
     fn find_positions(content: &str, targets: VecDeque<TargetInfo>) -> Vec<TargetInfo> {
         let mut targets = targets;
         let mut positioned_targets = Vec::new();
-        for (line_num, line) in content.lines().enumerate() {
+        let lines: Vec<&str> = content.lines().collect();
+        for (line_num, line) in lines.iter().enumerate() {
             // Look for _target_ followed by optional whitespace and colon
             if let Some((col, quote_offset)) = Self::find_target_with_colon(line) {
                 // remove the first entry from targets
-                let mut target = targets.pop_front().unwrap();
+                let Some(mut target) = targets.pop_front() else {
+                    break;
+                };
                 target.line = line_num as u32;
                 target.key_start = col as u32;
 
@@ -293,8 +681,8 @@ impl YamlParser {
                     }
                 }
 
-                // Find parameter positions in subsequent lines
-                Self::find_parameter_positions(content, line_num + 1, &mut target);
+                // Find parameter positions within the target's block
+                Self::find_parameter_positions(&lines, line_num, &mut target);
                 positioned_targets.push(target);
             }
         }
@@ -303,28 +691,345 @@ impl YamlParser {
     }
 
     /// Find positions for parameters associated with a `_target_`
-    fn find_parameter_positions(content: &str, start_line: usize, target_info: &mut TargetInfo) {
-        let lines: Vec<&str> = content.lines().collect();
-        if start_line >= lines.len() {
-            return;
+    ///
+    /// Parameters are matched to lines by key, so comments and reserved keys at the
+    /// same indentation are skipped, and keys written before `_target_` are found too
+    fn find_parameter_positions(lines: &[&str], target_line: usize, target_info: &mut TargetInfo) {
+        Self::find_key_positions(
+            lines,
+            target_line,
+            target_info.key_start as usize,
+            &mut target_info.parameters,
+        );
+    }
+
+    /// Find the lines of the keys at column `indent` in the block around `key_line`
+    /// Fields of plain mappings are positioned recursively
+    fn find_key_positions(
+        lines: &[&str],
+        key_line: usize,
+        indent: usize,
+        parameters: &mut [ParameterValue],
+    ) {
+        for idx in Self::block_key_lines(lines, key_line, indent) {
+            let Some(key) = Self::line_key(lines[idx], indent) else {
+                continue;
+            };
+            if let Some(param) = parameters.iter_mut().find(|p| p.key == key) {
+                param.line = idx as u32;
+            }
+        }
+
+        for param in parameters.iter_mut() {
+            let ParameterKind::Mapping(mapping) = &mut param.kind else {
+                continue;
+            };
+            // A block mapping continues on the next, more indented line
+            let line = param.line as usize;
+            let field_line =
+                (line + 1..lines.len()).find(|&idx| !Self::is_blank_or_comment(lines[idx]));
+            match field_line {
+                Some(field_line) if Self::indentation(lines[field_line]) > indent => {
+                    let field_indent = Self::indentation(lines[field_line]);
+                    mapping.key_start = field_indent as u32;
+                    Self::find_key_positions(lines, line, field_indent, &mut mapping.fields);
+                }
+                // Flow mappings are reported at the parameter key
+                _ => Self::place_flow_mapping(mapping, param.line, indent as u32),
+            }
         }
+    }
 
-        let mut remaining_params = std::mem::take(&mut target_info.parameters);
+    /// Position the fields of a flow mapping, which all sit on the line of its key
+    fn place_flow_mapping(mapping: &mut MappingValue, line: u32, key_start: u32) {
+        mapping.key_start = key_start;
+        for field in &mut mapping.fields {
+            field.line = line;
+            if let ParameterKind::Mapping(nested) = &mut field.kind {
+                Self::place_flow_mapping(nested, line, key_start);
+            }
+        }
+    }
 
-        // Look through subsequent lines for parameters at the same or deeper indentation
-        for (idx, line) in lines.iter().enumerate().skip(start_line) {
-            if remaining_params.is_empty() {
-                return;
+    /// Find the lines holding sibling keys of the key at `key_line`, which starts at column `indent`
+    /// The search stops in both directions once the indentation drops below the key's column
+    fn block_key_lines(lines: &[&str], key_line: usize, indent: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+
+        // A key that opens a sequence item (`- key: value`) has no siblings before it
+        let opens_item = lines
+            .get(key_line)
+            .is_some_and(|line| Self::indentation(line) < indent);
+        if !opens_item {
+            for idx in (0..key_line).rev() {
+                let line = lines[idx];
+                if Self::is_blank_or_comment(line) {
+                    continue;
+                }
+                let line_indent = Self::indentation(line);
+                if line_indent > indent {
+                    continue;
+                }
+                if Self::key_column(line) == indent {
+                    result.push(idx);
+                }
+                if line_indent < indent {
+                    break;
+                }
             }
+            result.reverse();
+        }
 
-            let line_indent = line.find(|c: char| !c.is_whitespace()).unwrap_or(0);
+        for (idx, line) in lines.iter().enumerate().skip(key_line + 1) {
+            if Self::is_blank_or_comment(line) {
+                continue;
+            }
+            let line_indent = Self::indentation(line);
+            if line_indent < indent {
+                break;
+            }
+            if line_indent == indent {
+                result.push(idx);
+            }
+        }
 
-            if line_indent == target_info.key_start as usize {
-                // Same indentation as target so we're looking at a paremeter line
-                let mut param = remaining_params.remove(0);
-                param.line = idx as u32;
-                target_info.parameters.push(param);
+        result
+    }
+
+    /// Get the number of leading whitespace characters of a line
+    fn indentation(line: &str) -> usize {
+        line.len() - line.trim_start().len()
+    }
+
+    /// Get the column at which a line's mapping key starts, skipping any `- ` sequence markers
+    fn key_column(line: &str) -> usize {
+        let mut col = Self::indentation(line);
+        while line[col..].starts_with("- ") {
+            col += 2;
+            col += Self::indentation(&line[col..]);
+        }
+        col
+    }
+
+    /// Check if a line is empty or only contains a comment
+    fn is_blank_or_comment(line: &str) -> bool {
+        let trimmed = line.trim();
+        trimmed.is_empty() || trimmed.starts_with('#')
+    }
+
+    /// Extract the mapping key that starts at column `col` of a line, without quotes
+    fn line_key(line: &str, col: usize) -> Option<String> {
+        Self::split_key_line(line, col).map(|(key, _, _)| key)
+    }
+
+    /// Split a `key: value` line at column `col` into the unquoted key, the column just past the
+    /// key (including any quotes), and the column just past the colon
+    fn split_key_line(line: &str, col: usize) -> Option<(String, usize, usize)> {
+        let rest = line.get(col..)?;
+        let (key, key_len) = match rest.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let end = rest[1..].find(quote)? + 1;
+                (&rest[1..end], end + 1)
+            }
+            _ => {
+                let colon = rest.find(':')?;
+                let key = rest[..colon].trim_end();
+                (key, key.len())
+            }
+        };
+        let after = &rest[key_len..];
+        let colon = key_len + (after.len() - after.trim_start().len());
+        let is_key = rest[colon..].starts_with(':')
+            && rest[colon + 1..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace());
+        is_key.then(|| (key.to_string(), col + key_len, col + colon + 1))
+    }
+
+    /// Build an outline of every mapping key and sequence item in a document, with its key path
+    /// This is a line-based scan, so it also works on documents that don't fully parse
+    pub fn outline(content: &str) -> Vec<OutlineEntry> {
+        struct Frame {
+            indent: usize,
+            segment: String,
+            is_item: bool,
+            items: usize,
+        }
+
+        let mut entries = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut root_items = 0;
+        // Lines of a block scalar (`key: |`) are content, not keys
+        let mut block_scalar_indent: Option<usize> = None;
+
+        for (line_num, line) in content.lines().enumerate() {
+            if Self::is_blank_or_comment(line) || line.starts_with("---") {
+                continue;
+            }
+            let mut col = Self::indentation(line);
+            if let Some(indent) = block_scalar_indent {
+                if col > indent {
+                    continue;
+                }
+                block_scalar_indent = None;
+            }
+
+            // A sequence item at the same indentation as its parent key still belongs to it
+            let is_item = line[col..].starts_with("- ") || line[col..].trim_end() == "-";
+            while stack.last().is_some_and(|top| {
+                top.indent > col || (top.indent == col && (top.is_item || !is_item))
+            }) {
+                stack.pop();
+            }
+
+            while line[col..].starts_with("- ") || line[col..].trim_end() == "-" {
+                let counter = match stack.last_mut() {
+                    Some(parent) => &mut parent.items,
+                    None => &mut root_items,
+                };
+                let index = *counter;
+                *counter += 1;
+                stack.push(Frame {
+                    indent: col,
+                    segment: index.to_string(),
+                    is_item: true,
+                    items: 0,
+                });
+
+                let item_start = col;
+                col = (col + 2).min(line.len());
+                col += Self::indentation(&line[col..]);
+                let value = Self::strip_comment(&line[col..]);
+                entries.push(OutlineEntry {
+                    path: stack.iter().map(|f| f.segment.clone()).collect(),
+                    line: line_num as u32,
+                    key_start: item_start as u32,
+                    key_end: item_start as u32 + 1,
+                    value_start: col as u32,
+                    value: if Self::split_key_line(line, col).is_some() {
+                        String::new()
+                    } else {
+                        value.to_string()
+                    },
+                });
             }
+
+            let Some((key, key_end, colon_end)) = Self::split_key_line(line, col) else {
+                continue;
+            };
+            stack.push(Frame {
+                indent: col,
+                segment: key,
+                is_item: false,
+                items: 0,
+            });
+
+            let value_start = colon_end + Self::indentation(&line[colon_end..]);
+            let value = Self::strip_comment(&line[value_start..]);
+            if value.starts_with('|') || value.starts_with('>') {
+                block_scalar_indent = Some(col);
+            }
+            entries.push(OutlineEntry {
+                path: stack.iter().map(|f| f.segment.clone()).collect(),
+                line: line_num as u32,
+                key_start: col as u32,
+                key_end: key_end as u32,
+                value_start: value_start as u32,
+                value: value.to_string(),
+            });
+        }
+
+        entries
+    }
+
+    /// Find every `_target_` key in an outline, with the path of the block holding it
+    /// Unlike `parse` this works on documents that don't parse, but parameters aren't extracted
+    pub fn outline_targets(outline: &[OutlineEntry]) -> Vec<(&[String], TargetInfo)> {
+        outline
+            .iter()
+            .filter(|entry| entry.path.last().map(String::as_str) == Some(TARGET_KEY))
+            .map(|entry| {
+                // Like `parse`, the value starts after an opening quote
+                let quoted = entry.value.starts_with(['"', '\'']);
+                let target = TargetInfo {
+                    value: entry.value.trim_matches(['"', '\'']).to_string(),
+                    line: entry.line,
+                    key_start: entry.key_start,
+                    value_start: entry.value_start + quoted as u32,
+                    ..Default::default()
+                };
+                (&entry.path[..entry.path.len() - 1], target)
+            })
+            .collect()
+    }
+
+    /// Get the keys directly under a block of an outline, in document order
+    pub fn block_keys<'a>(outline: &'a [OutlineEntry], block: &[String]) -> Vec<&'a OutlineEntry> {
+        outline
+            .iter()
+            .filter(|entry| {
+                entry.path.len() == block.len() + 1
+                    && entry.path.starts_with(block)
+                    && !entry.is_item()
+            })
+            .collect()
+    }
+
+    /// Get where each item of a sequence value starts, for block and flow sequences
+    /// Example: `_args_: [10, [1, 2]]` -> the positions of `10` and `[1, 2]`
+    pub fn sequence_item_positions(
+        outline: &[OutlineEntry],
+        entry: &OutlineEntry,
+    ) -> Vec<Position> {
+        if entry.value.is_empty() {
+            return outline
+                .iter()
+                .filter(|item| {
+                    item.is_item()
+                        && item.path.len() == entry.path.len() + 1
+                        && item.path.starts_with(&entry.path)
+                })
+                .map(|item| Position::new(item.line, item.value_start))
+                .collect();
+        }
+        let Some(inner) = entry
+            .value
+            .strip_prefix('[')
+            .and_then(|value| value.strip_suffix(']'))
+        else {
+            return Vec::new();
+        };
+
+        // Split on the commas of the outer list, skipping those in nested lists, mappings and quotes
+        let mut positions = Vec::new();
+        let mut item_start = true;
+        let mut depth = 0;
+        let mut quote = None;
+        for (offset, c) in inner.char_indices() {
+            if item_start && !c.is_whitespace() {
+                let column = entry.value_start + 1 + offset as u32;
+                positions.push(Position::new(entry.line, column));
+                item_start = false;
+            }
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '[' | '{') => depth += 1,
+                (None, ']' | '}') => depth -= 1,
+                (None, ',') if depth == 0 => item_start = true,
+                _ => {}
+            }
+        }
+        positions
+    }
+
+    /// Remove a trailing comment and surrounding whitespace from a scalar
+    fn strip_comment(text: &str) -> &str {
+        match Self::comment_start(text) {
+            Some(start) => text[..start].trim(),
+            None => text.trim(),
         }
     }
 
@@ -461,6 +1166,226 @@ mod tests {
         assert!(YamlParser::is_hydra_file(content));
     }
 
+    #[test]
+    fn test_parse_with_recovery_valid() {
+        let content = "model:\n  _target_: my.Model\n  size: 1\n";
+        let (targets, errors) = YamlParser::parse_with_recovery(content);
+        assert_eq!(targets.len(), 1);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_parse_with_recovery_bad_indentation() {
+        let content = r#"model:
+  _target_: my.Model
+  size: 1
+ bad: 2
+optimizer:
+  _target_: torch.optim.Adam
+  lr: 0.1
+"#;
+        let (targets, errors) = YamlParser::parse_with_recovery(content);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(errors[0].start, 1);
+        assert_eq!(errors[0].end, 7);
+
+        // Targets in the parts of the file that still parse are kept, on their original lines
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].value, "torch.optim.Adam");
+        assert_eq!(targets[1].line, 5);
+    }
+
+    #[test]
+    fn test_parse_with_recovery_unclosed_quote() {
+        let content = r#"model:
+  _target_: my.Model
+  name: "unclosed
+  size: 1
+"#;
+        let (targets, errors) = YamlParser::parse_with_recovery(content);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("quoted scalar"));
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[0].start, 8);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].parameters.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_with_recovery_multiple_errors() {
+        let content = "a:\n\tb: 1\nc:\n  d: 1\n e: 2\n";
+        let (_, errors) = YamlParser::parse_with_recovery(content);
+        let lines: Vec<u32> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_parse_plain_mapping_parameters() {
+        let content = r#"trainer:
+  _target_: my.Trainer
+  optim:
+    lr: 0.1
+    # comment
+    schedule:
+      warmup: 10
+    encoder:
+      _target_: my.Encoder
+  data: {batch_size: 32}
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[1].value, "my.Encoder");
+        assert_eq!(targets[1].line, 8);
+
+        let ParameterKind::Mapping(optim) = &targets[0].parameters[0].kind else {
+            panic!("Expected a mapping parameter");
+        };
+        assert_eq!(targets[0].parameters[0].line, 2);
+        assert_eq!(optim.key_start, 4);
+        let fields: Vec<_> = optim
+            .fields
+            .iter()
+            .map(|f| (f.key.as_str(), f.line))
+            .collect();
+        assert_eq!(fields, vec![("lr", 3), ("schedule", 5), ("encoder", 7)]);
+        assert!(matches!(
+            optim.fields[2].kind,
+            ParameterKind::NestedTargetIndex(1)
+        ));
+
+        let ParameterKind::Mapping(schedule) = &optim.fields[1].kind else {
+            panic!("Expected a nested mapping");
+        };
+        assert_eq!(schedule.key_start, 6);
+        assert_eq!(schedule.fields[0].line, 6);
+
+        let ParameterKind::Mapping(data) = &targets[0].parameters[1].kind else {
+            panic!("Expected a flow mapping");
+        };
+        assert_eq!(data.key_start, 2);
+        assert_eq!(data.fields[0].line, 9);
+    }
+
+    #[test]
+    fn test_outline() {
+        let content = r#"defaults:
+  - model: resnet
+  - _self_
+
+trainer:
+  max_epochs: ???  # set per experiment
+  callbacks:
+  - _target_: my.Callback
+    patience: 3
+  description: |
+    not: a key
+  "quoted key": 1
+"#;
+        let outline = YamlParser::outline(content);
+        let paths: Vec<String> = outline.iter().map(|e| e.path.join(".")).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "defaults",
+                "defaults.0",
+                "defaults.0.model",
+                "defaults.1",
+                "trainer",
+                "trainer.max_epochs",
+                "trainer.callbacks",
+                "trainer.callbacks.0",
+                "trainer.callbacks.0._target_",
+                "trainer.callbacks.0.patience",
+                "trainer.description",
+                "trainer.quoted key",
+            ]
+        );
+
+        let max_epochs = &outline[5];
+        assert_eq!(max_epochs.line, 5);
+        assert_eq!((max_epochs.key_start, max_epochs.key_end), (2, 12));
+        assert_eq!(max_epochs.value_start, 14);
+        assert_eq!(max_epochs.value, "???");
+
+        assert_eq!(outline[3].value, "_self_");
+        assert_eq!(outline[11].key_end, 14);
+    }
+
+    #[test]
+    fn test_outline_targets() {
+        let content =
+            "model:\n  _target_: \"pkg.Model\"\n  callbacks:\n    - _target_: pkg.Callback\n";
+        let outline = YamlParser::outline(content);
+        let targets = YamlParser::outline_targets(&outline);
+        let found: Vec<(String, &str, u32, u32)> = targets
+            .iter()
+            .map(|(block, target)| {
+                (
+                    block.join("."),
+                    target.value.as_str(),
+                    target.line,
+                    target.value_start,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("model".to_string(), "pkg.Model", 1, 13),
+                ("model.callbacks.0".to_string(), "pkg.Callback", 3, 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sequence_item_positions() {
+        let content = "a:\n  _args_: [10, [1, 2], 'x, y']\nb:\n  _args_:\n    - 1\n    - 2\n";
+        let outline = YamlParser::outline(content);
+        let positions = |path: &str| {
+            let entry = outline.iter().find(|e| e.path.join(".") == path).unwrap();
+            YamlParser::sequence_item_positions(&outline, entry)
+                .iter()
+                .map(|p| (p.line, p.character))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions("a._args_"), vec![(1, 11), (1, 15), (1, 23)]);
+        assert_eq!(positions("b._args_"), vec![(4, 6), (5, 6)]);
+    }
+
+    #[test]
+    fn test_find_suppressions() {
+        let content = r##"# @hydra
+# hydra-lsp: ignore-file
+model:
+  _target_: my.Model  # hydra-lsp: ignore
+  size: 1 # hydra-lsp: ignore[unknown-parameter, missing-parameter]
+  name: "# hydra-lsp: ignore"
+  other: 2 # hydra-lsp: ignore-file
+"##;
+        let suppressions = YamlParser::find_suppressions(content);
+        assert_eq!(suppressions.len(), 2);
+
+        assert_eq!(suppressions[0].line, 3);
+        assert_eq!(suppressions[0].start, 22);
+        assert_eq!(suppressions[0].end, 41);
+        assert!(suppressions[0].codes.is_empty());
+
+        assert_eq!(suppressions[1].line, 4);
+        assert_eq!(
+            suppressions[1].codes,
+            vec![
+                "unknown-parameter".to_string(),
+                "missing-parameter".to_string()
+            ]
+        );
+
+        let ignore_file = YamlParser::find_ignore_file_comment(content).unwrap();
+        assert_eq!(ignore_file.line, 1);
+        assert_eq!(ignore_file.end, 24);
+        assert!(YamlParser::find_ignore_file_comment("model: 1\n").is_none());
+    }
+
     #[test]
     fn test_parse_simple_config() {
         let content = r#"
@@ -512,6 +1437,140 @@ model:
         assert_eq!(*line_map.get(&7).unwrap(), 2);
     }
 
+    #[test]
+    fn test_parse_partial_target() {
+        let content = r#"
+model:
+  _target_: myproject.make_model
+  _partial_: true
+  hidden_size: 256
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let target = targets.first().unwrap();
+        assert!(target.partial);
+        // _partial_ is reserved and is not a parameter
+        assert_eq!(target.parameters.len(), 1);
+        assert_eq!(target.parameters[0].key, "hidden_size");
+        assert_eq!(target.parameters[0].line, 4);
+    }
+
+    #[test]
+    fn test_parse_convert_mode() {
+        let content = r#"
+trainer:
+  _target_: myproject.Trainer
+  _convert_: Partial
+  optimizer:
+    _target_: myproject.Adam
+  logger:
+    _target_: myproject.Logger
+    _convert_: all
+plain:
+  _target_: myproject.Plain
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let modes: Vec<ConvertMode> = targets.iter().map(|t| t.convert).collect();
+        // Nested targets inherit the mode unless they set their own
+        assert_eq!(
+            modes,
+            vec![
+                ConvertMode::Partial,
+                ConvertMode::Partial,
+                ConvertMode::All,
+                ConvertMode::None
+            ]
+        );
+        assert_eq!(targets[0].parameters.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_non_recursive_target() {
+        let content = r#"
+trainer:
+  _target_: myproject.Trainer
+  _recursive_: false
+  optimizer:
+    _target_: myproject.Adam
+    lr: 0.1
+  epochs: 3
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert!(!targets[0].raw);
+        // The nested block is still a target of its own, but its parent gets a mapping
+        assert!(targets[1].raw);
+        assert_eq!(targets[1].line, 5);
+        assert_eq!(targets[1].parameters[0].key, "lr");
+
+        let optimizer = &targets[0].parameters[0];
+        assert_eq!(optimizer.key, "optimizer");
+        assert_eq!(optimizer.line, 4);
+        assert!(matches!(&optimizer.kind, ParameterKind::Mapping(m) if m.is_raw_target()));
+        assert_eq!(targets[0].parameters[1].line, 7);
+    }
+
+    #[test]
+    fn test_parse_non_recursive_subtree() {
+        let content = r#"
+trainer:
+  _target_: myproject.Trainer
+  _recursive_: false
+  optimizer:
+    _target_: myproject.Adam
+    scheduler:
+      _target_: myproject.Cosine
+  callbacks:
+    early_stop:
+      _target_: myproject.EarlyStop
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        assert_eq!(targets.len(), 4);
+        assert!(!targets[0].raw);
+        // Everything below the non-recursive target is passed on as config, however deep
+        assert!(targets[1..].iter().all(|target| target.raw));
+
+        let scheduler = &targets[1].parameters[0];
+        assert_eq!(scheduler.key, "scheduler");
+        assert!(matches!(&scheduler.kind, ParameterKind::Mapping(m) if m.is_raw_target()));
+    }
+
+    #[test]
+    fn test_parameter_positions_matched_by_key() {
+        let content = r#"
+model:
+  hidden_size: 256
+  _target_: myproject.Model
+  # a comment at the same indentation
+  num_layers: 12
+other:
+  num_layers: 3
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        let target = targets.first().unwrap();
+        assert!(!target.partial);
+        assert_eq!(target.parameters.len(), 2);
+        let hidden = target.parameters.iter().find(|p| p.key == "hidden_size");
+        assert_eq!(hidden.unwrap().line, 2);
+        let layers = target.parameters.iter().find(|p| p.key == "num_layers");
+        assert_eq!(layers.unwrap().line, 5);
+    }
+
+    #[test]
+    fn test_parameter_positions_in_sequence_item() {
+        let content = r#"
+callbacks:
+  - _target_: myproject.EarlyStopping
+    patience: 3
+  - _target_: myproject.Checkpoint
+    patience: 5
+"#;
+        let (targets, _) = YamlParser::parse(content).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].key_start, 4);
+        assert_eq!(targets[0].parameters[0].line, 3);
+        assert_eq!(targets[1].parameters[0].line, 5);
+    }
+
     #[test]
     fn test_find_target_at_position_positive() {
         let content = r#"
@@ -555,6 +1614,19 @@ model:
         assert!(target_info.is_none());
     }
 
+    #[test]
+    fn test_target_segment_at() {
+        let target = TargetInfo {
+            value: "myproject.models.Model".to_string(),
+            value_start: 12,
+            ..Default::default()
+        };
+        assert_eq!(target.segment_at(13), Some(0..9));
+        assert_eq!(target.segment_at(25), Some(10..16));
+        assert_eq!(target.segment_at(34), Some(17..22));
+        assert_eq!(target.segment_at(11), None);
+    }
+
     #[test]
     fn test_find_target_at_position_negative_col_after() {
         let content = r#"
@@ -568,6 +1640,37 @@ model:
         assert!(target_info.is_none());
     }
 
+    #[test]
+    fn test_find_parameter_at_position() {
+        let content = r#"
+model:
+  _target_: myproject.Model
+  hidden_size: 256
+  encoder:
+    _target_: myproject.Encoder
+    depth: 4
+"#;
+        let (target, parameter) =
+            YamlParser::find_parameter_at_position(content, Position::new(3, 5))
+                .unwrap()
+                .unwrap();
+        assert_eq!(target.value, "myproject.Model");
+        assert_eq!(parameter.key, "hidden_size");
+
+        let (target, parameter) =
+            YamlParser::find_parameter_at_position(content, Position::new(6, 4))
+                .unwrap()
+                .unwrap();
+        assert_eq!(target.value, "myproject.Encoder");
+        assert_eq!(parameter.key, "depth");
+
+        // Values and `_target_` keys aren't parameter keys
+        for position in [Position::new(3, 16), Position::new(2, 4)] {
+            let found = YamlParser::find_parameter_at_position(content, position).unwrap();
+            assert!(found.is_none());
+        }
+    }
+
     #[test]
     fn test_get_completion_context_target_value() {
         let content = r#"
@@ -0,0 +1,52 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+#[tokio::test]
+async fn test_code_lens() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+loader:
+  _target_: my_module.DataLoader
+  batch_size: 8
+model:
+  _target_: my_module.create_model
+  input_dim: 4
+"#;
+    ctx.open_document("test.yaml", content.to_string()).await;
+
+    let lenses = ctx
+        .request::<request::CodeLensRequest>(CodeLensParams {
+            text_document: TextDocumentIdentifier {
+                uri: ctx.doc_uri("test.yaml"),
+            },
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        })
+        .await
+        .expect("Expected code lenses");
+
+    // Two lenses per target, resolved when shown
+    assert_eq!(lenses.len(), 4);
+    assert!(lenses.iter().all(|lens| lens.command.is_none()));
+    assert_eq!(lenses[2].range.start, Position::new(5, 2));
+
+    let mut titles = Vec::new();
+    for lens in lenses {
+        let lens = ctx.request::<request::CodeLensResolve>(lens).await;
+        let command = lens.command.expect("Expected a resolved command");
+        titles.push(command.title);
+    }
+    assert!(titles[0].starts_with("class in ") && titles[0].ends_with("my_module.py"));
+    assert_eq!(titles[1], "1/1 required parameters");
+    assert!(titles[2].starts_with("function in ") && titles[2].ends_with("my_module.py"));
+    assert_eq!(titles[3], "1/2 required parameters, insert 1 missing");
+}
@@ -182,6 +182,10 @@ impl TestContext {
     }
 
     pub async fn initialize(&mut self) {
+        self.initialize_with_options(None).await;
+    }
+
+    pub async fn initialize_with_options(&mut self, options: Option<serde_json::Value>) {
         // Real set of initialize params with workspace configuration
         let initialize = r#"{
             "capabilities": {
@@ -235,6 +239,7 @@ impl TestContext {
             name: "test".to_owned(),
             uri: workspace_url.clone(),
         }]);
+        initialize.initialization_options = options;
         self.request::<lsp_types::request::Initialize>(initialize)
             .await;
         self.notify::<lsp_types::notification::Initialized>(InitializedParams {})
@@ -289,6 +289,178 @@ model:
 
 // ==================== Nested Target Tests ====================
 
+#[tokio::test]
+async fn test_diagnostics_syntax_error_location() {
+    let mut ctx = TestContext::new(TestWorkspace::Diagnostics);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+model:
+  _target_: my_module.DataLoader
+  shuffle: true
+ broken: 1
+config:
+  _target_: my_module.Config
+  name: "test"
+  value: 1.0
+"#;
+    ctx.open_document("syntax_error.yaml", content.to_string())
+        .await;
+
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    let diagnostics = dp.diagnostics;
+
+    // The syntax error lands on the badly indented line
+    let syntax_error = diagnostics
+        .iter()
+        .find(|d| extract_code(d) == "yaml-syntax-error")
+        .expect("Should have a syntax error diagnostic");
+    assert_eq!(syntax_error.range.start.line, 4);
+    assert_eq!(syntax_error.range.start.character, 1);
+    assert_eq!(syntax_error.range.end.character, 10);
+
+    // Targets in the rest of the file are still validated
+    let missing = diagnostics
+        .iter()
+        .find(|d| extract_code(d) == "missing-parameter")
+        .expect("Should still have target diagnostics");
+    assert_eq!(missing.range.start.line, 2);
+    assert!(missing.message.contains("batch_size"));
+}
+
+#[tokio::test]
+async fn test_diagnostics_suppressed() {
+    let mut ctx = TestContext::new(TestWorkspace::Diagnostics);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+model:
+  _target_: my_module.DataLoader  # hydra-lsp: ignore[missing-parameter]
+  shuffle: true
+
+config:
+  _target_: my_module.Config
+  name: "test"  # hydra-lsp: ignore
+  value: 1.0
+  unknown_param: "should error"  # hydra-lsp: ignore[unknown-parameter]
+"#;
+    ctx.open_document("suppressed.yaml", content.to_string())
+        .await;
+
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    let diagnostics = dp.diagnostics;
+
+    // Only the suppression that matched nothing should be reported
+    assert_eq!(
+        diagnostics.len(),
+        1,
+        "Unexpected diagnostics: {:?}",
+        diagnostics
+    );
+    let unused = &diagnostics[0];
+    assert_eq!(unused.range.start.line, 7);
+    assert_eq!(unused.severity, Some(DiagnosticSeverity::HINT));
+    assert_eq!(extract_code(unused), "unused-suppression");
+    assert_eq!(unused.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+}
+
+#[tokio::test]
+async fn test_diagnostics_rule_settings_from_options() {
+    let mut ctx = TestContext::new(TestWorkspace::Diagnostics);
+    ctx.initialize_with_options(Some(serde_json::json!({
+        "rules": {
+            "parameters": "warning",
+            "unknown-parameter": "off"
+        }
+    })))
+    .await;
+
+    let content = std::fs::read_to_string(ctx.workspace.path().join("config.yaml")).unwrap();
+    ctx.open_document("config.yaml", content).await;
+
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    let diagnostics = dp.diagnostics;
+
+    assert!(!diagnostics
+        .iter()
+        .any(|d| extract_code(d) == "unknown-parameter"));
+    let missing: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| extract_code(d) == "missing-parameter")
+        .collect();
+    assert_eq!(missing.len(), 2);
+    assert!(missing
+        .iter()
+        .all(|d| d.severity == Some(DiagnosticSeverity::WARNING)));
+}
+
+#[tokio::test]
+async fn test_diagnostics_rule_settings_from_project_file() {
+    let mut ctx = TestContext::new(TestWorkspace::Diagnostics);
+    let project_config = r#"rules:
+  missing-parameter: hint
+overrides:
+  - path: vendor
+    rules:
+      resolution: off
+"#;
+    std::fs::write(ctx.workspace.path().join(".hydra-lsp.yaml"), project_config).unwrap();
+    std::fs::create_dir(ctx.workspace.path().join("vendor")).unwrap();
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+model:
+  _target_: my_module.DataLoader
+  shuffle: true
+"#;
+    ctx.open_document("config.yaml", content.to_string()).await;
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    assert_eq!(dp.diagnostics.len(), 1);
+    assert_eq!(extract_code(&dp.diagnostics[0]), "missing-parameter");
+    assert_eq!(dp.diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+
+    // Unresolvable targets under the overridden directory are not reported
+    let content = r#"# @hydra
+model:
+  _target_: third_party.models.Model
+"#;
+    ctx.open_document("vendor/config.yaml", content.to_string())
+        .await;
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    assert!(dp.diagnostics.is_empty(), "{:?}", dp.diagnostics);
+}
+
+#[tokio::test]
+async fn test_diagnostics_republished_when_project_file_changes() {
+    let mut ctx = TestContext::new(TestWorkspace::Diagnostics);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+model:
+  _target_: my_module.DataLoader
+  shuffle: true
+"#;
+    ctx.open_document("config.yaml", content.to_string()).await;
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    assert_eq!(dp.diagnostics.len(), 1);
+    assert_eq!(dp.diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+
+    let project_config = ctx.workspace.path().join(".hydra-lsp.yaml");
+    std::fs::write(&project_config, "rules:\n  missing-parameter: hint\n").unwrap();
+    ctx.notify::<notification::DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+        changes: vec![FileEvent {
+            uri: Url::from_file_path(&project_config).unwrap(),
+            typ: FileChangeType::CREATED,
+        }],
+    })
+    .await;
+
+    let dp = ctx.recv::<PublishDiagnosticsParams>().await;
+    assert_eq!(dp.uri, ctx.doc_uri("config.yaml"));
+    assert_eq!(dp.diagnostics.len(), 1);
+    assert_eq!(dp.diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+}
+
 #[tokio::test]
 async fn test_nested_diagnostics_all_valid() {
     let mut ctx = TestContext::new(TestWorkspace::Nested);
@@ -0,0 +1,74 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+/// Flatten symbols to indented `name kind (detail) range` lines
+fn describe(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+    for symbol in symbols {
+        let detail = symbol
+            .detail
+            .as_ref()
+            .map(|detail| format!(" ({})", detail))
+            .unwrap_or_default();
+        lines.push(format!(
+            "{}{} {:?}{} {}:{}-{}:{}",
+            "  ".repeat(depth),
+            symbol.name,
+            symbol.kind,
+            detail,
+            symbol.range.start.line,
+            symbol.range.start.character,
+            symbol.range.end.line,
+            symbol.range.end.character
+        ));
+        describe(
+            symbol.children.as_deref().unwrap_or_default(),
+            depth + 1,
+            lines,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_document_symbols() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+defaults:
+  - _self_
+loader:
+  _target_: my_module.DataLoader
+  batch_size: 8
+model:
+  _target_: my_module.create_model
+  input_dim: 4
+  output_dim: 2
+"#;
+    ctx.open_document("test.yaml", content.to_string()).await;
+
+    let res = ctx
+        .request::<request::DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier {
+                uri: ctx.doc_uri("test.yaml"),
+            },
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        })
+        .await;
+
+    match res {
+        Some(DocumentSymbolResponse::Nested(symbols)) => {
+            let mut lines = Vec::new();
+            describe(&symbols, 0, &mut lines);
+            insta::assert_snapshot!("document_symbols", lines.join("\n"));
+        }
+        _ => panic!("Expected nested document symbols"),
+    }
+}
@@ -97,7 +97,7 @@ test:
 }
 
 #[tokio::test]
-async fn test_no_hover_outside_target() {
+async fn test_hover_on_parameter() {
     let mut ctx = TestContext::new(TestWorkspace::Simple);
     ctx.initialize().await;
 
@@ -108,7 +108,6 @@ test:
 "#;
     ctx.open_document("test.yaml", content.to_string()).await;
 
-    // Try hovering on a parameter line (not _target_)
     let res = ctx
         .request::<request::HoverRequest>(HoverParams {
             text_document_position_params: TextDocumentPositionParams {
@@ -126,5 +125,43 @@ test:
         })
         .await;
 
-    assert!(res.is_none(), "Should not get hover on non-target line");
+    match res.map(|hover| hover.contents) {
+        Some(HoverContents::Markup(markup)) => {
+            insta::assert_snapshot!("hover_on_parameter", markup.value);
+        }
+        _ => panic!("Expected markup hover on parameter key"),
+    }
+}
+
+#[tokio::test]
+async fn test_no_hover_outside_target() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+test:
+  _target_: my_module.DataLoader
+  batch_size: 32
+"#;
+    ctx.open_document("test.yaml", content.to_string()).await;
+
+    // Try hovering on a parameter value (not _target_ or a key)
+    let res = ctx
+        .request::<request::HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                position: Position {
+                    line: 3,
+                    character: 15,
+                },
+                text_document: TextDocumentIdentifier {
+                    uri: ctx.doc_uri("test.yaml"),
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        })
+        .await;
+
+    assert!(res.is_none(), "Should not get hover on a parameter value");
 }
@@ -0,0 +1,56 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+/// One `line:character label` entry per hint
+fn format_hints(hints: &[InlayHint]) -> String {
+    hints
+        .iter()
+        .map(|hint| {
+            let label = match &hint.label {
+                InlayHintLabel::String(label) => label.clone(),
+                InlayHintLabel::LabelParts(parts) => {
+                    parts.iter().map(|part| part.value.as_str()).collect()
+                }
+            };
+            format!(
+                "{}:{} {}",
+                hint.position.line, hint.position.character, label
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn test_inlay_hints() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+
+    let content = r#"# @hydra
+loader:
+  _target_: my_module.DataLoader
+  batch_size: 8
+model:
+  _target_: my_module.create_model
+  _args_: [4, 2]
+"#;
+    ctx.open_document("test.yaml", content.to_string()).await;
+
+    let res = ctx
+        .request::<request::InlayHintRequest>(InlayHintParams {
+            text_document: TextDocumentIdentifier {
+                uri: ctx.doc_uri("test.yaml"),
+            },
+            range: Range::new(Position::new(0, 0), Position::new(7, 0)),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        })
+        .await
+        .expect("Expected inlay hints");
+
+    insta::assert_snapshot!("inlay_hints", format_hints(&res));
+}
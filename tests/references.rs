@@ -0,0 +1,82 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+fn reference_params(uri: Url, line: u32, character: u32) -> ReferenceParams {
+    ReferenceParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: Position { line, character },
+        },
+        work_done_progress_params: WorkDoneProgressParams {
+            work_done_token: None,
+        },
+        partial_result_params: PartialResultParams {
+            partial_result_token: None,
+        },
+        context: ReferenceContext {
+            include_declaration: true,
+        },
+    }
+}
+
+/// One `file:line:start-end` entry per location
+fn format_locations(locations: &[Location]) -> String {
+    locations
+        .iter()
+        .map(|location| {
+            format!(
+                "{}:{}:{}-{}",
+                location.uri.path().split('/').next_back().unwrap_or(""),
+                location.range.start.line,
+                location.range.start.character,
+                location.range.end.character
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const OTHER_CONFIG: &str = r#"# @hydra
+loader:
+  _target_: my_module.DataLoader
+  batch_size: 8
+model:
+  _target_: my_module.create_model
+  input_dim: 4
+  output_dim: 2
+"#;
+
+#[tokio::test]
+async fn test_references_from_target() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    // Cursor on `my_module.DataLoader` in the open config
+    let res = ctx
+        .request::<request::References>(reference_params(ctx.doc_uri("other.yaml"), 2, 25))
+        .await
+        .expect("Expected references");
+
+    insta::assert_snapshot!("references_from_target", format_locations(&res));
+}
+
+#[tokio::test]
+async fn test_references_from_python_definition() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    // Cursor on the name in `class DataLoader:`, in a Python file that isn't open
+    let res = ctx
+        .request::<request::References>(reference_params(ctx.doc_uri("my_module.py"), 3, 8))
+        .await
+        .expect("Expected references");
+
+    insta::assert_snapshot!("references_from_python_definition", format_locations(&res));
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+const OTHER_CONFIG: &str = r#"# @hydra
+loader:
+  _target_: my_module.DataLoader
+  batch_size: 8
+model:
+  _target_: my_module.create_model
+  input_dim: 4
+  output_dim: 2
+"#;
+
+/// One `file:line:start-end -> new text` entry per edit, ordered by file
+fn format_edit(edit: &WorkspaceEdit) -> String {
+    let mut entries: Vec<String> = edit
+        .changes
+        .iter()
+        .flatten()
+        .flat_map(|(uri, edits)| {
+            let file_name = uri.path().split('/').next_back().unwrap_or("").to_string();
+            edits.iter().map(move |edit| {
+                format!(
+                    "{}:{}:{}-{} -> {}",
+                    file_name,
+                    edit.range.start.line,
+                    edit.range.start.character,
+                    edit.range.end.character,
+                    edit.new_text
+                )
+            })
+        })
+        .collect();
+    entries.sort();
+    entries.join("\n")
+}
+
+#[tokio::test]
+async fn test_prepare_rename_segment() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    let res = ctx
+        .request::<request::PrepareRenameRequest>(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: ctx.doc_uri("other.yaml"),
+            },
+            position: Position {
+                line: 2,
+                character: 15,
+            },
+        })
+        .await;
+
+    match res {
+        Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder }) => {
+            assert_eq!(placeholder, "my_module");
+            assert_eq!((range.start.character, range.end.character), (12, 21));
+        }
+        other => panic!("Expected range with placeholder, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_rename_symbol() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    let res = ctx
+        .request::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: ctx.doc_uri("other.yaml"),
+                },
+                position: Position {
+                    line: 2,
+                    character: 25,
+                },
+            },
+            new_name: "BatchLoader".to_string(),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        })
+        .await
+        .expect("Expected a workspace edit");
+
+    insta::assert_snapshot!("rename_symbol", format_edit(&res));
+}
+
+#[tokio::test]
+async fn test_rename_module() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    let res = ctx
+        .request::<request::Rename>(RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: ctx.doc_uri("other.yaml"),
+                },
+                position: Position {
+                    line: 2,
+                    character: 15,
+                },
+            },
+            new_name: "data".to_string(),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+        })
+        .await
+        .expect("Expected a workspace edit");
+
+    insta::assert_snapshot!("rename_module", format_edit(&res));
+}
+
+#[tokio::test]
+async fn test_will_rename_module_file() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("other.yaml", OTHER_CONFIG.to_string())
+        .await;
+
+    let res = ctx
+        .request::<request::WillRenameFiles>(RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: ctx.doc_uri("my_module.py").to_string(),
+                new_uri: ctx.doc_uri("data.py").to_string(),
+            }],
+        })
+        .await
+        .expect("Expected a workspace edit");
+
+    insta::assert_snapshot!("will_rename_module_file", format_edit(&res));
+}
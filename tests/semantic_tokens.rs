@@ -0,0 +1,122 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+/// Decode tokens to `line:start text type` lines
+fn describe(content: &str, tokens: &[SemanticToken]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let (mut line, mut start) = (0, 0);
+    tokens
+        .iter()
+        .map(|token| {
+            line += token.delta_line;
+            start = if token.delta_line == 0 {
+                start + token.delta_start
+            } else {
+                token.delta_start
+            };
+            let text = &lines[line as usize][start as usize..(start + token.length) as usize];
+            format!("{}:{} {} {}", line, start, text, token.token_type)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const CONTENT: &str = r#"# @hydra
+loader:
+  _target_: my_module.DataLoader
+  batch_size: ${batch}
+model:
+  _target_: my_module.create_model
+  input_dim: 4
+"#;
+
+fn document(ctx: &TestContext) -> TextDocumentIdentifier {
+    TextDocumentIdentifier {
+        uri: ctx.doc_uri("test.yaml"),
+    }
+}
+
+#[tokio::test]
+async fn test_semantic_tokens_full_and_delta() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("test.yaml", CONTENT.to_string()).await;
+
+    let res = ctx
+        .request::<request::SemanticTokensFullRequest>(SemanticTokensParams {
+            text_document: document(&ctx),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        })
+        .await;
+    let Some(SemanticTokensResult::Tokens(tokens)) = res else {
+        panic!("Expected semantic tokens");
+    };
+    insta::assert_snapshot!("semantic_tokens_full", describe(CONTENT, &tokens.data));
+
+    // Renaming a parameter only resends its token
+    let changed = CONTENT.replace("input_dim", "output_dim");
+    ctx.notify::<notification::DidChangeTextDocument>(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: ctx.doc_uri("test.yaml"),
+            version: 1,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: changed,
+        }],
+    })
+    .await;
+    let res = ctx
+        .request::<request::SemanticTokensFullDeltaRequest>(SemanticTokensDeltaParams {
+            text_document: document(&ctx),
+            previous_result_id: tokens.result_id.expect("Expected a result id"),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        })
+        .await;
+    let Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) = res else {
+        panic!("Expected a semantic tokens delta");
+    };
+    assert_eq!(delta.edits.len(), 1);
+    let edit = &delta.edits[0];
+    assert_eq!((edit.start, edit.delete_count), (50, 5));
+    assert_eq!(edit.data.as_ref().map(|data| data[0].length), Some(10));
+}
+
+#[tokio::test]
+async fn test_semantic_tokens_range() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document("test.yaml", CONTENT.to_string()).await;
+
+    let res = ctx
+        .request::<request::SemanticTokensRangeRequest>(SemanticTokensRangeParams {
+            text_document: document(&ctx),
+            range: Range::new(Position::new(4, 0), Position::new(6, 0)),
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: None,
+            },
+            partial_result_params: PartialResultParams {
+                partial_result_token: None,
+            },
+        })
+        .await;
+    let Some(SemanticTokensRangeResult::Tokens(tokens)) = res else {
+        panic!("Expected semantic tokens");
+    };
+    // Relative to the start of the document, so the first token keeps its line
+    insta::assert_snapshot!("semantic_tokens_range", describe(CONTENT, &tokens.data));
+}
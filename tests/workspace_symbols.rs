@@ -0,0 +1,83 @@
+mod common;
+
+use tower_lsp::lsp_types::*;
+
+use crate::common::*;
+
+fn symbol_params(query: &str) -> WorkspaceSymbolParams {
+    WorkspaceSymbolParams {
+        query: query.to_string(),
+        work_done_progress_params: WorkDoneProgressParams {
+            work_done_token: None,
+        },
+        partial_result_params: PartialResultParams {
+            partial_result_token: None,
+        },
+    }
+}
+
+/// One `name kind (container) file:line:start-end` entry per symbol
+fn format_symbols(symbols: &[SymbolInformation]) -> String {
+    symbols
+        .iter()
+        .map(|symbol| {
+            format!(
+                "{} {:?} ({}) {}:{}:{}-{}",
+                symbol.name,
+                symbol.kind,
+                symbol.container_name.as_deref().unwrap_or(""),
+                symbol
+                    .location
+                    .uri
+                    .path()
+                    .split('/')
+                    .next_back()
+                    .unwrap_or(""),
+                symbol.location.range.start.line,
+                symbol.location.range.start.character,
+                symbol.location.range.end.character
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn test_workspace_symbols() {
+    let mut ctx = TestContext::new(TestWorkspace::Simple);
+    ctx.initialize().await;
+    ctx.open_document(
+        "train.yaml",
+        r#"# @hydra
+trainer:
+  callbacks:
+    - _target_: my_module.callbacks.EarlyStopping
+      patience: 3
+loader:
+  _target_: my_module.DataLoader
+"#
+        .to_string(),
+    )
+    .await;
+
+    let res = ctx
+        .request::<request::WorkspaceSymbolRequest>(symbol_params("EarlyStop"))
+        .await;
+    match res {
+        Some(WorkspaceSymbolResponse::Flat(symbols)) => {
+            insta::assert_snapshot!("workspace_symbols_target", format_symbols(&symbols));
+        }
+        _ => panic!("Expected flat workspace symbols"),
+    }
+
+    // Both the open config and the one on disk instantiate the loader
+    let res = ctx
+        .request::<request::WorkspaceSymbolRequest>(symbol_params("dataload"))
+        .await;
+    match res {
+        Some(WorkspaceSymbolResponse::Flat(symbols)) => {
+            insta::assert_snapshot!("workspace_symbols_loader", format_symbols(&symbols));
+        }
+        _ => panic!("Expected flat workspace symbols"),
+    }
+}